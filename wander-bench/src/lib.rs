@@ -0,0 +1,182 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! This module is the library module for the wander-bench project.
+//! It provides a set of representative workloads for measuring the performance of Wander.
+
+use std::fmt::Display;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use wander::environment::Environment;
+use wander::preludes::common;
+use wander::{
+    compile, execute, run, HostFunction, HostFunctionBinding, NoHostType, WanderError, WanderValue,
+};
+
+/// A workload that can be measured with `bench_run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Workload {
+    /// A self-recursive Lambda called with the given depth.
+    DeepRecursion(usize),
+    /// Mapping, filtering, sorting and folding a List with the given number of elements.
+    BigList(usize),
+    /// Running the given number of small scripts, each with a fresh Environment.
+    ManySmallScripts(usize),
+    /// Running the same script the given number of times with `run`.
+    RepeatedScript(usize),
+    /// Compiling a script once and executing it the given number of times.
+    CompiledScript(usize),
+}
+
+impl Display for Workload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Workload::DeepRecursion(depth) => write!(f, "deep recursion ({depth})"),
+            Workload::BigList(size) => write!(f, "big list ({size})"),
+            Workload::ManySmallScripts(count) => write!(f, "many small scripts ({count})"),
+            Workload::RepeatedScript(count) => write!(f, "repeated script ({count})"),
+            Workload::CompiledScript(count) => write!(f, "compiled script ({count})"),
+        }
+    }
+}
+
+/// The result of running a Workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    /// The Workload that was ran.
+    pub workload: Workload,
+    /// The total time spent running the Workload.
+    pub elapsed: Duration,
+    /// The value the last script in the Workload produced.
+    pub value: WanderValue<NoHostType>,
+}
+
+/// The default set of Workloads.
+pub fn default_workloads() -> Vec<Workload> {
+    vec![
+        Workload::DeepRecursion(10_000),
+        Workload::BigList(10_000),
+        Workload::ManySmallScripts(1_000),
+        Workload::RepeatedScript(1_000),
+        Workload::CompiledScript(1_000),
+    ]
+}
+
+/// Run a single Workload and measure how long it takes.
+pub fn bench_run(workload: &Workload) -> Result<BenchResult, WanderError> {
+    let start = Instant::now();
+    let value = match workload {
+        Workload::DeepRecursion(depth) => deep_recursion(*depth)?,
+        Workload::BigList(size) => big_list(*size)?,
+        Workload::ManySmallScripts(count) => many_small_scripts(*count)?,
+        Workload::RepeatedScript(count) => repeated_script(*count)?,
+        Workload::CompiledScript(count) => compiled_script(*count)?,
+    };
+    Ok(BenchResult {
+        workload: workload.clone(),
+        elapsed: start.elapsed(),
+        value,
+    })
+}
+
+fn deep_recursion(depth: usize) -> Result<WanderValue<NoHostType>, WanderError> {
+    let script = format!(
+        r#"
+        let
+          loop = \n acc -> if Core.eq n 0 then acc else loop (Bench.dec n) (Bool.not acc) end
+        in
+          loop {depth} true
+        end
+        "#
+    );
+    let mut environment = common::<NoHostType>();
+    environment.bind_host_function(Rc::new(DecFunction {}));
+    run(&script, &mut environment)
+}
+
+fn big_list(size: usize) -> Result<WanderValue<NoHostType>, WanderError> {
+    let values = (0..size)
+        .map(|i| i.to_string())
+        .collect::<Vec<String>>()
+        .join(" ");
+    let expected: usize = (0..size).filter(|i| i % 2 == 0).map(|i| i * 2).sum();
+    let script = format!(
+        r#"
+        let xs = [{values}] in
+          let doubled = List.map (\x -> Int.mul x 2) xs in
+            let sorted = List.sort (List.reverse doubled) in
+              let quads = List.filter (\x -> Core.eq (Int.mod x 4) 0) sorted in
+                Core.eq (List.foldLeft (\acc x -> Int.add acc x) 0 quads) {expected}
+              end
+            end
+          end
+        end
+        "#
+    );
+    run(&script, &mut common::<NoHostType>())
+}
+
+fn many_small_scripts(count: usize) -> Result<WanderValue<NoHostType>, WanderError> {
+    let mut value = WanderValue::Nothing;
+    for i in 0..count {
+        let script = format!("let x = {i} in Core.eq x {i} end");
+        value = run(&script, &mut common::<NoHostType>())?;
+    }
+    Ok(value)
+}
+
+// A script that takes more work to parse than to run, to compare `run` with `compile` and `execute`.
+const REPEATED_SCRIPT: &str = r#"
+let isZero = \n -> Core.eq n 0 in
+  let lookup = { first = 0 second = 1 third = 2 } in
+    if isZero lookup.first then Core.eq [0 1 2 3 4 5 6 7 8 9] [0 1 2 3 4 5 6 7 8 9] else false end
+  end
+end
+"#;
+
+fn repeated_script(count: usize) -> Result<WanderValue<NoHostType>, WanderError> {
+    let mut environment = common::<NoHostType>();
+    let mut value = WanderValue::Nothing;
+    for _ in 0..count {
+        value = run(REPEATED_SCRIPT, &mut environment)?;
+    }
+    Ok(value)
+}
+
+fn compiled_script(count: usize) -> Result<WanderValue<NoHostType>, WanderError> {
+    let mut environment = common::<NoHostType>();
+    let compiled = compile(REPEATED_SCRIPT, &environment)?;
+    let mut value = WanderValue::Nothing;
+    for _ in 0..count {
+        value = execute(&compiled, &mut environment)?;
+    }
+    Ok(value)
+}
+
+struct DecFunction {}
+impl HostFunction<NoHostType> for DecFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<NoHostType>],
+        _bindings: &mut Environment<NoHostType>,
+    ) -> Result<WanderValue<NoHostType>, WanderError> {
+        if let [WanderValue::Int(value)] = arguments {
+            Ok(WanderValue::Int(value - 1))
+        } else {
+            Err(WanderError::new("`dec` function requires an Int.".to_owned()))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Bench.dec".to_owned(),
+            parameters: vec![("value".to_owned(), None)],
+            result: None,
+            pure: true,
+            doc_string: "Decrement an Int.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
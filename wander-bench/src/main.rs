@@ -0,0 +1,16 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! This module is the main module for the wander-bench project.
+
+use wander_bench::{bench_run, default_workloads};
+
+fn main() {
+    for workload in default_workloads() {
+        match bench_run(&workload) {
+            Ok(result) => println!("{workload}: {:?}", result.elapsed),
+            Err(err) => println!("{workload}: Error: {err}"),
+        }
+    }
+}
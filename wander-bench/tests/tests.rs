@@ -0,0 +1,21 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::WanderValue;
+use wander_bench::{bench_run, Workload};
+
+#[test]
+fn workloads_run() {
+    let workloads = vec![
+        Workload::DeepRecursion(10),
+        Workload::BigList(10),
+        Workload::ManySmallScripts(10),
+        Workload::RepeatedScript(10),
+        Workload::CompiledScript(10),
+    ];
+    for workload in workloads {
+        let res = bench_run(&workload).unwrap();
+        assert_eq!(res.value, WanderValue::Bool(true));
+    }
+}
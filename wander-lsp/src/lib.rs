@@ -0,0 +1,328 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! This module is the library module for the wander-lsp project.
+//! It reports parse, translation, and type errors as diagnostics, completes bound names,
+//! and shows the documentation of HostFunctions on hover.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationType, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, HoverRequest, Request as RequestType};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, Documentation, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, MarkupContent, MarkupKind, Position, PublishDiagnosticsParams, Range,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use wander::environment::Environment;
+use wander::lexer::tokenize;
+use wander::{introspect, HostFunctionBinding, HostType, Span, WanderError};
+
+/// The capabilities to send to the client while initializing.
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions::default()),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Keeps the open documents and the Environment scripts are checked against.
+pub struct WanderLanguageServer<T: HostType> {
+    environment: Environment<T>,
+    documents: HashMap<Url, String>,
+}
+
+impl<T: HostType> WanderLanguageServer<T> {
+    /// Create a server that checks scripts against the names bound in an Environment.
+    pub fn new(environment: Environment<T>) -> Self {
+        WanderLanguageServer {
+            environment,
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Handle messages from an initialized Connection until the client shuts the server down.
+    pub fn run(&mut self, connection: &Connection) -> Result<(), Box<dyn Error + Sync + Send>> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    connection
+                        .sender
+                        .send(Message::Response(self.respond(request)))?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(published) = self.notify(notification) {
+                        connection.sender.send(Message::Notification(published))?;
+                    }
+                }
+                Message::Response(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn respond(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            Completion::METHOD => {
+                match serde_json::from_value::<CompletionParams>(request.params) {
+                    Ok(params) => {
+                        let position = params.text_document_position;
+                        let items = self
+                            .documents
+                            .get(&position.text_document.uri)
+                            .map(|source| self.completions(source, position.position))
+                            .unwrap_or_default();
+                        Response::new_ok(request.id, CompletionResponse::Array(items))
+                    }
+                    Err(err) => invalid_params(request.id, err),
+                }
+            }
+            HoverRequest::METHOD => match serde_json::from_value::<HoverParams>(request.params) {
+                Ok(params) => {
+                    let position = params.text_document_position_params;
+                    let hover = self
+                        .documents
+                        .get(&position.text_document.uri)
+                        .and_then(|source| self.hover(source, position.position));
+                    Response::new_ok(request.id, hover)
+                }
+                Err(err) => invalid_params(request.id, err),
+            },
+            method => Response::new_err(
+                request.id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request {method}."),
+            ),
+        }
+    }
+
+    // Track open documents, returning the diagnostics to publish when one changes.
+    // Closed documents have their diagnostics cleared.
+    fn notify(&mut self, notification: Notification) -> Option<Notification> {
+        let (uri, diagnostics) = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                self.update(params.text_document.uri, params.text_document.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                let text = params.content_changes.into_iter().last()?.text;
+                self.update(params.text_document.uri, text)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                self.documents.remove(&params.text_document.uri);
+                (params.text_document.uri, vec![])
+            }
+            _ => return None,
+        };
+        Some(Notification::new(
+            PublishDiagnostics::METHOD.to_owned(),
+            PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            },
+        ))
+    }
+
+    fn update(&mut self, uri: Url, text: String) -> (Url, Vec<Diagnostic>) {
+        let diagnostics = self.diagnostics(&text);
+        self.documents.insert(uri.clone(), text);
+        (uri, diagnostics)
+    }
+
+    /// Find the problems in a script, errors tokenizing it, every statement that couldn't be parsed,
+    /// errors translating the rest, and values that can't have the type they need.
+    pub fn diagnostics(&self, source: &str) -> Vec<Diagnostic> {
+        if let Err(err) = tokenize(source) {
+            return vec![diagnostic(source, err)];
+        }
+        match introspect(source, &self.environment) {
+            Ok(introspection) => introspection
+                .diagnostics
+                .into_iter()
+                .map(|parse_diagnostic| parse_diagnostic.error)
+                .chain(introspection.translation_errors)
+                .chain(
+                    introspection
+                        .type_diagnostics
+                        .into_iter()
+                        .map(|type_diagnostic| type_diagnostic.error),
+                )
+                .map(|error| diagnostic(source, error))
+                .collect(),
+            Err(err) => vec![diagnostic(source, err)],
+        }
+    }
+
+    /// Complete the name before a position with the names bound in the Environment,
+    /// and the names of `val` statements in the script.
+    pub fn completions(&self, source: &str, position: Position) -> Vec<CompletionItem> {
+        let end = offset(source, position);
+        let start = source[..end]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_name_char(*c))
+            .map_or(0, |(index, c)| index + c.len_utf8());
+        let prefix = &source[start..end];
+        let mut names: Vec<String> = self.environment.bound_names().into_iter().collect();
+        if let Ok(introspection) = introspect(source, &self.environment) {
+            names.extend(
+                introspection
+                    .dependencies
+                    .into_iter()
+                    .map(|dependencies| dependencies.name),
+            );
+        }
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| match self.environment.read_host_function(&name) {
+                Some(function) => {
+                    let binding = function.binding();
+                    CompletionItem {
+                        label: name,
+                        kind: Some(CompletionItemKind::FUNCTION),
+                        detail: Some(signature(&binding)),
+                        documentation: Some(Documentation::String(binding.doc_string)),
+                        ..CompletionItem::default()
+                    }
+                }
+                None => CompletionItem {
+                    label: name,
+                    kind: Some(CompletionItemKind::VARIABLE),
+                    ..CompletionItem::default()
+                },
+            })
+            .collect()
+    }
+
+    /// Show the signature and documentation of the HostFunction named at a position.
+    pub fn hover(&self, source: &str, position: Position) -> Option<Hover> {
+        let at = offset(source, position);
+        let start = source[..at]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_name_char(*c))
+            .map_or(0, |(index, c)| index + c.len_utf8());
+        let end = source[at..]
+            .char_indices()
+            .find(|(_, c)| !is_name_char(*c))
+            .map_or(source.len(), |(index, _)| at + index);
+        let function = self
+            .environment
+            .read_host_function(&source[start..end].to_owned())?;
+        let binding = function.binding();
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!(
+                    "```\n{}\n```\n\n{}",
+                    signature(&binding),
+                    binding.doc_string
+                ),
+            }),
+            range: Some(range(source, Span { start, end })),
+        })
+    }
+}
+
+fn invalid_params(id: RequestId, err: serde_json::Error) -> Response {
+    Response::new_err(
+        id,
+        lsp_server::ErrorCode::InvalidParams as i32,
+        err.to_string(),
+    )
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '?'
+}
+
+// Write a HostFunction's name with its parameters and result, like `Int.add left right -> Int`.
+fn signature(binding: &HostFunctionBinding) -> String {
+    let mut signature = binding.name.clone();
+    for (name, tag) in &binding.parameters {
+        match tag {
+            Some(tag) => signature += &format!(" ({name}: {tag})"),
+            None => signature += &format!(" {name}"),
+        }
+    }
+    if let Some(result) = &binding.result {
+        signature += &format!(" -> {result}");
+    }
+    signature
+}
+
+fn diagnostic(source: &str, error: WanderError) -> Diagnostic {
+    Diagnostic {
+        range: range(source, error.span.unwrap_or(Span { start: 0, end: 0 })),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("wander".to_owned()),
+        message: error.message,
+        code: None,
+        ..Diagnostic::default()
+    }
+}
+
+fn range(source: &str, span: Span) -> Range {
+    Range {
+        start: position(source, span.start),
+        end: position(source, span.end.max(span.start)),
+    }
+}
+
+/// Find the LSP Position, a line and a character counted in UTF-16 code units, of a byte offset into a script.
+pub fn position(source: &str, offset: usize) -> Position {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+/// Find the byte offset into a script of an LSP Position, positions past the end of a line are moved to its end.
+pub fn offset(source: &str, position: Position) -> usize {
+    let line_start = match position.line {
+        0 => 0,
+        line => match source.match_indices('\n').nth(line as usize - 1) {
+            Some((newline, _)) => newline + 1,
+            None => return source.len(),
+        },
+    };
+    let line_end = source[line_start..]
+        .find('\n')
+        .map_or(source.len(), |newline| line_start + newline);
+    let mut units = 0;
+    for (index, c) in source[line_start..line_end].char_indices() {
+        if units >= position.character as usize {
+            return line_start + index;
+        }
+        units += c.len_utf16();
+    }
+    line_end
+}
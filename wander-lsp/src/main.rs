@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! This module is the main module for the wander-lsp project.
+//! The server communicates over stdio.
+
+use std::error::Error;
+
+use lsp_server::Connection;
+use wander::environment::Environment;
+use wander::preludes::{add_print, common};
+use wander::NoHostType;
+use wander_lsp::{capabilities, WanderLanguageServer};
+
+fn environment() -> Environment<NoHostType> {
+    let mut environment = common::<NoHostType>();
+    add_print(&mut environment);
+    environment
+}
+
+fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    WanderLanguageServer::new(environment()).run(&connection)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
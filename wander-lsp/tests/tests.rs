@@ -0,0 +1,133 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use lsp_types::{
+    CompletionItemKind, DidOpenTextDocumentParams, HoverContents, Position,
+    PublishDiagnosticsParams, TextDocumentItem, Url,
+};
+use wander::preludes::common;
+use wander::NoHostType;
+use wander_lsp::{offset, position, WanderLanguageServer};
+
+fn server() -> WanderLanguageServer<NoHostType> {
+    WanderLanguageServer::new(common::<NoHostType>())
+}
+
+#[test]
+fn positions_count_utf16_code_units() {
+    let source = "val é = 1;\n\"😀\" x";
+    assert_eq!(position(source, 0), Position::new(0, 0));
+    assert_eq!(position(source, 14), Position::new(1, 1));
+    assert_eq!(position(source, 19), Position::new(1, 5));
+    assert_eq!(offset(source, Position::new(1, 5)), 19);
+    assert_eq!(offset(source, Position::new(0, 100)), 11);
+    assert_eq!(offset(source, Position::new(5, 0)), source.len());
+}
+
+#[test]
+fn report_parse_errors() {
+    let server = server();
+    assert!(server.diagnostics("val x = true; Bool.not x").is_empty());
+    let diagnostics = server.diagnostics("val x = 5;\n(Bool.not x");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start.line, 1);
+    assert_eq!(server.diagnostics("val x = $").len(), 1);
+}
+
+#[test]
+fn report_translation_and_type_errors() {
+    let server = server();
+    let diagnostics = server.diagnostics("val x = 1;\n~x");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start.line, 1);
+    let diagnostics = server.diagnostics("val x = 5;\nBool.not x");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start, Position::new(1, 9));
+    assert_eq!(
+        diagnostics[0].message,
+        "Expected an argument of type Bool, found Int at line 2, column 10."
+    );
+}
+
+#[test]
+fn complete_bound_names() {
+    let server = server();
+    let source = "val total = 5;\n(Bool.n to";
+    let labels = |line, character| -> Vec<(String, Option<CompletionItemKind>)> {
+        server
+            .completions(source, Position::new(line, character))
+            .into_iter()
+            .map(|item| (item.label, item.kind))
+            .collect()
+    };
+    assert_eq!(
+        labels(1, 7),
+        vec![("Bool.not".to_owned(), Some(CompletionItemKind::FUNCTION))]
+    );
+    assert_eq!(
+        labels(1, 10),
+        vec![("total".to_owned(), Some(CompletionItemKind::VARIABLE))]
+    );
+}
+
+#[test]
+fn hover_shows_host_function_docs() {
+    let server = server();
+    let source = "Bool.not true";
+    let hover = server.hover(source, Position::new(0, 3)).unwrap();
+    match hover.contents {
+        HoverContents::Markup(markup) => {
+            assert!(markup.value.starts_with("```\nBool.not"));
+        }
+        contents => panic!("Unexpected hover {contents:?}"),
+    }
+    assert_eq!(hover.range.unwrap().end, Position::new(0, 8));
+    assert_eq!(server.hover(source, Position::new(0, 11)), None);
+}
+
+#[test]
+fn publish_diagnostics_for_opened_documents() {
+    let (server_connection, client) = Connection::memory();
+    let thread = std::thread::spawn(move || server().run(&server_connection).unwrap());
+    let uri = Url::parse("file:///script.wander").unwrap();
+    let params = DidOpenTextDocumentParams {
+        text_document: TextDocumentItem::new(uri.clone(), "wander".to_owned(), 1, "(".to_owned()),
+    };
+    client
+        .sender
+        .send(Message::Notification(Notification::new(
+            "textDocument/didOpen".to_owned(),
+            params,
+        )))
+        .unwrap();
+    match client.receiver.recv().unwrap() {
+        Message::Notification(notification) => {
+            assert_eq!(notification.method, "textDocument/publishDiagnostics");
+            let params: PublishDiagnosticsParams =
+                serde_json::from_value(notification.params).unwrap();
+            assert_eq!(params.uri, uri);
+            assert_eq!(params.diagnostics.len(), 1);
+        }
+        message => panic!("Unexpected message {message:?}"),
+    }
+    let id = RequestId::from(1);
+    client
+        .sender
+        .send(Message::Request(Request::new(
+            id,
+            "shutdown".to_owned(),
+            (),
+        )))
+        .unwrap();
+    assert!(matches!(client.receiver.recv(), Ok(Message::Response(_))));
+    client
+        .sender
+        .send(Message::Notification(Notification::new(
+            "exit".to_owned(),
+            (),
+        )))
+        .unwrap();
+    thread.join().unwrap();
+}
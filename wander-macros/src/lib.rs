@@ -0,0 +1,225 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Macros for writing Wander HostFunctions as ordinary Rust functions.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    spanned::Spanned,
+    Expr, ExprLit, FnArg, Ident, ItemFn, Lit, LitStr, Meta, Pat, ReturnType, Token, Type,
+};
+
+/// Generate a HostFunction from a function, bound with the name given to the attribute.
+///
+/// ```ignore
+/// /// Check if both values are true.
+/// #[wander_fn("Test.and")]
+/// fn and(left: bool, right: bool) -> bool {
+///     left && right
+/// }
+///
+/// environment.bind_host_function(Rc::new(And));
+/// ```
+///
+/// The HostFunction is a unit struct named after the function in UpperCamelCase,
+/// and the function's doc comment is used as its documentation.
+/// Parameters are converted with `wander::FromWanderValue` and the result with `wander::IntoWanderValue`,
+/// a function that returns a `Result` can fail with a `WanderError`.
+///
+/// The HostFunction is impure and not effectful unless the name is followed by `pure`,
+/// for functions that always return the same result for the same arguments,
+/// or `effectful`, for functions with side effects that are skipped during a dry run.
+///
+/// ```ignore
+/// #[wander_fn("Test.and", pure)]
+/// fn and(left: bool, right: bool) -> bool {
+///     left && right
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn wander_fn(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    let attribute = parse_macro_input!(attribute as Attribute);
+    let function = parse_macro_input!(item as ItemFn);
+    match host_function(&attribute, &function) {
+        Ok(host_function) => quote! {
+            #function
+            #host_function
+        }
+        .into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+// The arguments of `#[wander_fn]`, the bound name followed by any of `pure` and `effectful`.
+struct Attribute {
+    name: LitStr,
+    pure: bool,
+    effectful: bool,
+}
+
+impl Parse for Attribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attribute = Attribute {
+            name: input.parse()?,
+            pure: false,
+            effectful: false,
+        };
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            let flag: Ident = input.parse()?;
+            match flag.to_string().as_str() {
+                "pure" => attribute.pure = true,
+                "effectful" => attribute.effectful = true,
+                _ => {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        "expected `pure` or `effectful`",
+                    ))
+                }
+            }
+        }
+        if attribute.pure && attribute.effectful {
+            return Err(syn::Error::new(
+                attribute.name.span(),
+                "a `wander_fn` can't be both `pure` and `effectful`",
+            ));
+        }
+        Ok(attribute)
+    }
+}
+
+fn host_function(attribute: &Attribute, function: &ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &attribute.name;
+    let pure = attribute.pure;
+    let effectful = attribute.effectful;
+    let signature = &function.sig;
+    if !signature.generics.params.is_empty() || signature.asyncness.is_some() {
+        return Err(syn::Error::new(
+            signature.span(),
+            "`wander_fn` functions can't be generic or async",
+        ));
+    }
+    let mut parameters = vec![];
+    let mut types = vec![];
+    for input in &signature.inputs {
+        match input {
+            FnArg::Typed(input) => match input.pat.as_ref() {
+                Pat::Ident(pattern) => {
+                    parameters.push(pattern.ident.clone());
+                    types.push(input.ty.as_ref().clone());
+                }
+                pattern => {
+                    return Err(syn::Error::new(
+                        pattern.span(),
+                        "`wander_fn` parameters must be plain names",
+                    ))
+                }
+            },
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new(
+                    receiver.span(),
+                    "`wander_fn` can't be used on methods",
+                ))
+            }
+        }
+    }
+    let visibility = &function.vis;
+    let function_name = &signature.ident;
+    let struct_name = Ident::new(
+        &upper_camel_case(&function_name.to_string()),
+        function_name.span(),
+    );
+    let call = quote! { #function_name(#(#parameters),*) };
+    let result = if returns_result(&signature.output) {
+        quote! { #call.map(::wander::IntoWanderValue::<T>::into_wander_value) }
+    } else {
+        quote! { Ok(::wander::IntoWanderValue::<T>::into_wander_value(#call)) }
+    };
+    let doc_string = doc_string(function);
+    let parameter_names = parameters.iter().map(|parameter| parameter.to_string());
+    Ok(quote! {
+        #[allow(missing_docs)]
+        #visibility struct #struct_name;
+
+        impl<T: ::wander::HostType> ::wander::HostFunction<T> for #struct_name {
+            fn run(
+                &self,
+                arguments: &[::wander::WanderValue<T>],
+                _bindings: &mut ::wander::environment::Environment<T>,
+            ) -> Result<::wander::WanderValue<T>, ::wander::WanderError> {
+                if let [#(#parameters),*] = arguments {
+                    if let (#(Some(#parameters),)*) = (#(<#types as ::wander::FromWanderValue<T>>::from_wander_value(#parameters),)*) {
+                        return #result;
+                    }
+                }
+                Err(::wander::requires_error(
+                    #name,
+                    &[#(<#types as ::wander::FromWanderValue<T>>::DESCRIPTION),*],
+                ))
+            }
+
+            fn binding(&self) -> ::wander::HostFunctionBinding {
+                ::wander::HostFunctionBinding {
+                    name: #name.to_owned(),
+                    parameters: vec![#((#parameter_names.to_owned(), None)),*],
+                    result: None,
+                    effectful: #effectful,
+                    pure: #pure,
+                    doc_string: #doc_string.to_owned(),
+                    ..::std::default::Default::default()
+                }
+            }
+        }
+    })
+}
+
+// Functions returning a type named Result, like `Result<bool, WanderError>`, can fail.
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, output) => match output.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+// Join the lines of a function's doc comment.
+fn doc_string(function: &ItemFn) -> LitStr {
+    let lines: Vec<String> = function
+        .attrs
+        .iter()
+        .filter_map(|attribute| match &attribute.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(line),
+                    ..
+                }) => Some(line.value().trim().to_owned()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    LitStr::new(&lines.join(" "), Span::call_site())
+}
+
+fn upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut characters = word.chars();
+            match characters.next() {
+                Some(first) => first.to_uppercase().chain(characters).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::rc::Rc;
+
+use wander::{
+    environment::Environment, preludes::common, run, HostFunction, NoHostType, WanderError,
+    WanderValue,
+};
+use wander_macros::wander_fn;
+
+/// Check if both values are true.
+#[wander_fn("Test.and", pure)]
+fn and(left: bool, right: bool) -> bool {
+    left && right
+}
+
+/// Add up a List of Ints.
+#[wander_fn("Test.sum")]
+fn sum_all(values: Vec<i64>) -> i64 {
+    values.iter().sum()
+}
+
+/// Divide two Ints.
+#[wander_fn("Test.div")]
+fn checked_div(left: i64, right: i64) -> Result<i64, WanderError> {
+    left.checked_div(right)
+        .ok_or_else(|| WanderError::new("Cannot divide by zero.".to_owned()))
+}
+
+#[wander_fn("Test.greeting")]
+fn greeting() -> String {
+    "hello".to_owned()
+}
+
+fn bindings() -> Environment<NoHostType> {
+    let mut bindings = common::<NoHostType>();
+    bindings.bind_host_function(Rc::new(And));
+    bindings.bind_host_function(Rc::new(SumAll));
+    bindings.bind_host_function(Rc::new(CheckedDiv));
+    bindings.bind_host_function(Rc::new(Greeting));
+    bindings
+}
+
+#[test]
+fn call_generated_host_functions() {
+    let input = "[(Test.and true false) (Test.sum [1 2 3]) (Test.div 7 2) Test.greeting]";
+    let res = run(input, &mut bindings());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(false),
+        WanderValue::Int(6),
+        WanderValue::Int(3),
+        WanderValue::String("hello".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn generated_errors() {
+    let res = run("Test.and true 5", &mut bindings());
+    let expected = Err(WanderError::new(
+        "`Test.and` function requires a Bool and a Bool.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+    let res = run("Test.div 1 0", &mut bindings());
+    let expected = Err(WanderError::new("Cannot divide by zero.".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn generated_binding() {
+    let binding = HostFunction::<NoHostType>::binding(&SumAll);
+    assert_eq!(binding.name, "Test.sum");
+    assert_eq!(binding.parameters, vec![("values".to_owned(), None)]);
+    assert_eq!(binding.doc_string, "Add up a List of Ints.");
+}
+
+#[test]
+fn generated_functions_are_impure_by_default() {
+    let and = HostFunction::<NoHostType>::binding(&And);
+    assert!(and.pure && !and.effectful);
+    let greeting = HostFunction::<NoHostType>::binding(&Greeting);
+    assert!(!greeting.pure && !greeting.effectful);
+    let res = run("pure Test.and true true end", &mut bindings());
+    assert_eq!(res, Ok(WanderValue::Bool(true)));
+    let res = run("pure Test.greeting end", &mut bindings());
+    assert!(res.is_err());
+}
@@ -42,7 +42,7 @@ impl eframe::App for WanderPad {
                 let script = self.script.clone();
                 self.result = match run(&script, &mut common::<NoHostType>()) {
                     Ok(value) => value.to_string(),
-                    Err(err) => err.0,
+                    Err(err) => err.to_string(),
                 }
             }
             ui.text_edit_multiline(&mut self.script);
@@ -4,43 +4,262 @@
 
 //! This module is the library module for the ligature-repl project.
 
+use rustyline::completion::{Completer, Pair};
 use rustyline::error::ReadlineError;
-use rustyline::{DefaultEditor, Result};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper, Result};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::Write;
+use std::time::{Duration, Instant};
 use tabled::{
     settings::{object::Rows, Modify, Width},
     Table, Tabled,
 };
+use wander::debugger::{DebugCommand, DebugPause, Debugger};
 use wander::environment::Environment;
-use wander::{introspect, run, HostFunctionBinding, HostType};
+use wander::{
+    fmt, format_value, introspect, line_column, run, try_fmt, FormatOptions, HostFunctionBinding,
+    HostType, WanderError, WanderValue,
+};
 
 pub struct REPLState<T: HostType> {
     pub environment: Environment<T>,
+    /// Creates the Environment used when the session starts or is reset.
+    pub create_environment: fn() -> Environment<T>,
+    commands: BTreeMap<String, REPLCommand<T>>,
+    /// Lines of the last result that haven't been shown yet.
+    pending_output: VecDeque<String>,
+    /// How many results have been bound to `r1`, `r2`, ...
+    result_count: usize,
+    /// The file inputs and results are written to while recording.
+    transcript: Option<File>,
+}
+
+/// Handles a meta-command given the rest of the line after the command's name.
+pub type CommandHandler<T> = Box<dyn FnMut(&str, &mut Environment<T>)>;
+
+/// A meta-command added by the application embedding the REPL.
+struct REPLCommand<T: HostType> {
+    help: String,
+    handler: CommandHandler<T>,
+}
+
+impl<T: HostType> REPLState<T> {
+    /// Start a new session with the Environment returned by `create_environment`.
+    pub fn new(create_environment: fn() -> Environment<T>) -> Self {
+        REPLState {
+            environment: create_environment(),
+            create_environment,
+            commands: BTreeMap::new(),
+            pending_output: VecDeque::new(),
+            result_count: 0,
+            transcript: None,
+        }
+    }
+
+    /// Add a meta-command, like `:connect`, that is listed by the help command.
+    /// The handler is called with the rest of the line after the command's name.
+    pub fn add_command(
+        &mut self,
+        name: &str,
+        help: &str,
+        handler: impl FnMut(&str, &mut Environment<T>) + 'static,
+    ) {
+        self.commands.insert(
+            name.to_owned(),
+            REPLCommand {
+                help: help.to_owned(),
+                handler: Box::new(handler),
+            },
+        );
+    }
+
+    /// Run a meta-command added with `add_command`.
+    /// Returns false if there is no command with the given name.
+    pub fn run_command(&mut self, name: &str, input: &str) -> bool {
+        match self.commands.get_mut(name) {
+            Some(command) => {
+                (command.handler)(input, &mut self.environment);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Clear all values bound in this session.
+    pub fn reset(&mut self) {
+        self.environment = (self.create_environment)();
+        self.result_count = 0;
+    }
+
+    /// Run the script in the given file in this session.
+    pub fn load(&mut self, path: &str) -> std::result::Result<WanderValue<T>, WanderError> {
+        match std::fs::read_to_string(path) {
+            Ok(script) => self.run(&script),
+            Err(err) => Err(WanderError::new(format!("Could not read {path}, {err}."))),
+        }
+    }
+
+    /// Return the first `page_size` lines of output and keep the rest to be shown by `more`.
+    pub fn page(&mut self, output: &str, page_size: usize) -> String {
+        self.pending_output = output.lines().map(str::to_owned).collect();
+        self.more(page_size).unwrap_or_default()
+    }
+
+    /// Return the next `page_size` lines of the output passed to `page`,
+    /// or None if all of it has been shown.
+    pub fn more(&mut self, page_size: usize) -> Option<String> {
+        if self.pending_output.is_empty() {
+            return None;
+        }
+        let count = page_size.max(1).min(self.pending_output.len());
+        let page: Vec<String> = self.pending_output.drain(..count).collect();
+        Some(page.join("\n"))
+    }
+
+    /// The number of lines of output left for `more` to show.
+    pub fn remaining_lines(&self) -> usize {
+        self.pending_output.len()
+    }
+
+    /// Run a line of input in this session.
+    /// Top-level `val` and `let` declarations stay bound for the inputs that follow.
+    /// Each result is bound to `it` and to the next of `r1`, `r2`, ... so later inputs can reuse it.
+    pub fn run(&mut self, input: &str) -> std::result::Result<WanderValue<T>, WanderError> {
+        let started = Instant::now();
+        let result = run(input, &mut self.environment);
+        let elapsed = started.elapsed();
+        if let Ok(value) = &result {
+            self.result_count += 1;
+            self.environment
+                .bind(format!("r{}", self.result_count), value.clone());
+            self.environment.bind("it".to_owned(), value.clone());
+        }
+        self.record(input, &result, elapsed);
+        result
+    }
+
+    /// Start writing a transcript of every input, its result or error, and how long it took to the given file.
+    /// Input lines start with `> ` and notes with `# `, so a transcript can be replayed by running its input lines.
+    pub fn start_recording(&mut self, path: &str) -> std::io::Result<()> {
+        let mut transcript = File::create(path)?;
+        writeln!(transcript, "# Wander transcript\n")?;
+        self.transcript = Some(transcript);
+        Ok(())
+    }
+
+    /// Stop writing the transcript started by `start_recording`.
+    /// Returns false if there wasn't a recording.
+    pub fn stop_recording(&mut self) -> bool {
+        self.transcript.take().is_some()
+    }
+
+    /// Check if a transcript is being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.transcript.is_some()
+    }
+
+    // Add an entry to the transcript, recording stops if the transcript can't be written to.
+    fn record(
+        &mut self,
+        input: &str,
+        result: &std::result::Result<WanderValue<T>, WanderError>,
+        elapsed: Duration,
+    ) {
+        let Some(transcript) = &mut self.transcript else {
+            return;
+        };
+        let mut entry = String::new();
+        for line in input.lines() {
+            entry += &format!("> {line}\n");
+        }
+        match (result, self.result_count) {
+            (Ok(value), count) => entry += &format!("r{count} = {value}\n"),
+            (Err(err), _) => entry += &format!("Error: {err}\n"),
+        }
+        entry += &format!("# took {elapsed:?}\n\n");
+        if transcript.write_all(entry.as_bytes()).is_err() {
+            self.transcript = None;
+        }
+    }
+
+    /// The name the last result was bound to, like `r3`, or None if nothing has run yet.
+    pub fn last_result_name(&self) -> Option<String> {
+        match self.result_count {
+            0 => None,
+            count => Some(format!("r{count}")),
+        }
+    }
+}
+
+/// Settings for how the REPL looks and where it keeps its history.
+pub struct REPLConfig {
+    /// Shown before each line of input.
+    pub prompt: String,
+    /// Printed when the REPL starts.
+    pub banner: String,
+    /// The file input history is loaded from and saved to.
+    pub history_path: String,
+    /// Input starting with this prefix is a meta-command, like `:help`.
+    pub meta_command_prefix: String,
+    /// Results longer than this many lines are shown a page at a time, use `:more` to see the next page.
+    /// None prints results in full.
+    pub page_size: Option<usize>,
+}
+
+impl Default for REPLConfig {
+    fn default() -> Self {
+        REPLConfig {
+            prompt: "> ".to_owned(),
+            banner: [
+                "Welcome to Wander's REPL!",
+                "Press Ctrl+C or Ctrl+D or enter `:q` to quit.",
+                "Enter :help or :h for help.",
+                "---",
+            ]
+            .join("\n"),
+            history_path: "history.txt".to_owned(),
+            meta_command_prefix: ":".to_owned(),
+            page_size: Some(50),
+        }
+    }
 }
 
-pub fn start_repl<T: HostType>(state: &mut REPLState<T>) -> Result<()> {
-    //TODO this should accept REPLState not create it
-    println!("Welcome to Wander's REPL!");
-    println!("Press Ctrl+C or Ctrl+D or enter `:q` to quit.");
-    println!("Enter :help or :h for help.");
-    println!("---");
-    let mut rl = DefaultEditor::new()?;
-    if rl.load_history("history.txt").is_err() {
+pub fn start_repl<T: HostType + 'static>(
+    state: &mut REPLState<T>,
+    config: &REPLConfig,
+) -> Result<()> {
+    println!("{}", config.banner);
+    let mut rl: Editor<NameCompleter, DefaultHistory> = Editor::new()?;
+    rl.set_helper(Some(NameCompleter::new(&state.environment)));
+    if rl.load_history(&config.history_path).is_err() {
         println!("No previous history.");
     }
     loop {
-        let readline = rl.readline("> ");
+        let readline = rl.readline(&config.prompt);
         match readline {
             Ok(line) => {
                 rl.add_history_entry(line.as_str())?;
-                if line.trim().starts_with(':') {
-                    if !handle_command(&line, state) {
+                if let Some(command) = line
+                    .trim()
+                    .strip_prefix(config.meta_command_prefix.as_str())
+                {
+                    if !handle_command(command, state, config) {
                         break;
                     }
                 } else {
-                    match run(line.as_str(), &mut state.environment) {
-                        Ok(result) => println!("{result}"),
-                        Err(err) => println!("Error: {err:?}"),
-                    }    
+                    match state.run(line.as_str()) {
+                        Ok(result) => print_result(&result, state, config),
+                        Err(err) => println!("Error: {err}"),
+                    }
+                    for warning in state.environment.take_warnings() {
+                        println!("Warning: {}", warning.locate(&line));
+                    }
+                    rl.set_helper(Some(NameCompleter::new(&state.environment)));
                 }
             }
             Err(ReadlineError::Interrupted) => {
@@ -57,44 +276,173 @@ pub fn start_repl<T: HostType>(state: &mut REPLState<T>) -> Result<()> {
             }
         }
     }
-    rl.save_history("history.txt")
+    rl.save_history(&config.history_path)
+}
+
+/// Format script files in place with `wander::fmt`, or only check them if `check` is set.
+/// Returns the paths of the files that weren't already formatted,
+/// including files that can't be formatted because they don't tokenize or are unbalanced,
+/// which are left as they are.
+pub fn format_files(paths: &[String], check: bool) -> std::io::Result<Vec<String>> {
+    let mut unformatted = vec![];
+    for path in paths {
+        let script = std::fs::read_to_string(path)?;
+        match try_fmt(&script, FormatOptions::default()) {
+            Some(formatted) if formatted == script => {}
+            Some(formatted) => {
+                if !check {
+                    std::fs::write(path, formatted)?;
+                }
+                unformatted.push(path.clone());
+            }
+            None => unformatted.push(path.clone()),
+        }
+    }
+    Ok(unformatted)
+}
+
+// Print a result, broken over lines if it is wide, a page at a time if it is longer than the configured page size.
+fn print_result<T: HostType>(
+    result: &WanderValue<T>,
+    state: &mut REPLState<T>,
+    config: &REPLConfig,
+) {
+    let result = format_value(result, FormatOptions::default());
+    let output = match state.last_result_name() {
+        Some(name) => format!("{name} = {result}"),
+        None => result,
+    };
+    match config.page_size {
+        Some(page_size) => {
+            println!("{}", state.page(&output, page_size));
+            print_more_hint(state, config);
+        }
+        None => println!("{output}"),
+    }
 }
 
-fn handle_command<T: HostType>(input: &str, instance: &mut REPLState<T>) -> bool {
-    let mut parts = input.split_whitespace();
-    match parts.next().unwrap() {
-        //":remote" => todo!(),
-        //":local" => todo!(),
-        ":parse" | ":p" => parse(input, &instance.environment),
-        ":status" | ":s" => status(),
-        ":quit" | ":q" => quit(),
-        ":bindings" | ":b" => bindings(&instance.environment),
-        ":environment" | ":e" => environment(&mut instance.environment),
-        ":help" | ":h" => help(),
-        ":broadcast" => broadcast(input),
+fn print_more_hint<T: HostType>(state: &REPLState<T>, config: &REPLConfig) {
+    let remaining = state.remaining_lines();
+    if remaining > 0 {
+        println!(
+            "-- {remaining} more lines, enter {}more to continue --",
+            config.meta_command_prefix
+        );
+    }
+}
+
+// Run a meta-command, `input` is the line without the meta-command prefix.
+// Returns false when the REPL should quit.
+fn handle_command<T: HostType + 'static>(
+    input: &str,
+    instance: &mut REPLState<T>,
+    config: &REPLConfig,
+) -> bool {
+    let (command, rest) = match input.split_once(char::is_whitespace) {
+        Some((command, rest)) => (command, rest.trim()),
+        None => (input, ""),
+    };
+    let argument = rest.split_whitespace().next();
+    match command {
+        //"remote" => todo!(),
+        //"local" => todo!(),
+        "parse" | "p" => parse(rest, &instance.environment),
+        "fmt" | "f" => format(rest),
+        "debug" => debug(rest, instance, config),
+        "status" | "s" => status(),
+        "quit" | "q" => quit(),
+        "bindings" | "b" => bindings(&instance.environment),
+        "environment" | "e" => environment(&mut instance.environment),
+        "doc" | "d" => doc(argument, &instance.environment, config),
+        "load" | "l" => load(argument, instance, config),
+        "reset" | "r" => reset(instance),
+        "more" | "m" => more(instance, config),
+        "record" => record(argument, instance, config),
+        "stop" => stop(instance),
+        "help" | "h" => help(instance, config),
+        "broadcast" => broadcast(rest),
         s => {
-            println!("Unknown command - {s}");
+            if !instance.run_command(s, rest) {
+                println!("Unknown command - {}{s}", config.meta_command_prefix);
+            }
             true
         }
     }
 }
 
 fn parse<T: HostType>(input: &str, instance: &Environment<T>) -> bool {
-    let input = if input.starts_with(":parse") {
-        input.replacen(":parse", "", 1)
-    } else {
-        input.replacen(":p", "", 1)
-    };
-    let input = input.trim();
-    let introspection = introspect(&input, instance).unwrap();
+    let introspection = introspect(input, instance).unwrap();
     println!("Tokens:\n{:?}\n", introspection.tokens_ws);
     println!("Tokens Filtered:\n{:?}\n", introspection.tokens);
     println!("Transformed:\n{:?}\n", introspection.tokens_transformed);
     println!("Element:\n{:?}\n", introspection.element);
+    for diagnostic in &introspection.diagnostics {
+        println!("Error: {}", diagnostic.error);
+    }
     println!("Expression:\n{:?}\n", introspection.expression);
     true
 }
 
+// Run a script, pausing at its first expression to step through it.
+fn debug<T: HostType + 'static>(
+    script: &str,
+    instance: &mut REPLState<T>,
+    config: &REPLConfig,
+) -> bool {
+    if script.is_empty() {
+        println!("Usage - {}debug <script>", config.meta_command_prefix);
+        return true;
+    }
+    let source = script.to_owned();
+    let mut debugger = Debugger::new(Box::new(move |pause| debug_prompt(&source, pause)));
+    debugger.step();
+    instance.environment.set_eval_observer(Box::new(debugger));
+    let result = instance.run(script);
+    instance.environment.take_eval_observer();
+    match result {
+        Ok(result) => print_result(&result, instance, config),
+        Err(err) => println!("Error: {err}"),
+    }
+    true
+}
+
+// Show where the Debugger paused, and read commands until one resumes the script.
+fn debug_prompt<T: HostType>(script: &str, pause: &DebugPause<T>) -> DebugCommand {
+    let (line, column) = line_column(script, pause.position);
+    let text = script.lines().nth(line - 1).unwrap_or_default();
+    println!("Paused at line {line}, column {column}:");
+    println!("{text}\n{}^", " ".repeat(column - 1));
+    loop {
+        print!("debug> ");
+        let _ = std::io::stdout().flush();
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            return DebugCommand::Stop;
+        }
+        let input = input.trim();
+        let (command, name) = input.split_once(' ').unwrap_or((input, ""));
+        match command {
+            "step" | "s" => return DebugCommand::Step,
+            "next" | "n" => return DebugCommand::StepOver,
+            "out" | "o" => return DebugCommand::StepOut,
+            "continue" | "c" => return DebugCommand::Continue,
+            "quit" | "q" => return DebugCommand::Stop,
+            "print" | "p" => match pause.environment.read(&name.trim().to_owned()) {
+                Some(value) => println!("{} = {value}", name.trim()),
+                None => println!("{} isn't bound.", name.trim()),
+            },
+            _ => println!(
+                "Commands - step (s), next (n), out (o), continue (c), print (p) <name>, quit (q)"
+            ),
+        }
+    }
+}
+
+fn format(input: &str) -> bool {
+    println!("{}", fmt(input).trim_end());
+    true
+}
+
 fn broadcast(_input: &str) -> bool {
     true
 }
@@ -126,7 +474,108 @@ fn environment<T: HostType>(bindings: &mut Environment<T>) -> bool {
     true
 }
 
-fn help() -> bool {
+fn doc<T: HostType>(name: Option<&str>, bindings: &Environment<T>, config: &REPLConfig) -> bool {
+    match name {
+        Some(name) => match bindings.read_host_function(&name.to_owned()) {
+            Some(function) => println!("{}", function.binding().doc_string),
+            None => println!("No HostFunction named {name}."),
+        },
+        None => println!("Usage - {}doc <name>", config.meta_command_prefix),
+    }
+    true
+}
+
+fn load<T: HostType>(path: Option<&str>, instance: &mut REPLState<T>, config: &REPLConfig) -> bool {
+    match path {
+        Some(path) => match instance.load(path) {
+            Ok(result) => print_result(&result, instance, config),
+            Err(err) => println!("Error: {err}"),
+        },
+        None => println!("Usage - {}load <file>", config.meta_command_prefix),
+    }
+    true
+}
+
+fn reset<T: HostType>(instance: &mut REPLState<T>) -> bool {
+    instance.reset();
+    println!("Session reset.");
+    true
+}
+
+fn record<T: HostType>(
+    path: Option<&str>,
+    instance: &mut REPLState<T>,
+    config: &REPLConfig,
+) -> bool {
+    match path {
+        Some(path) => match instance.start_recording(path) {
+            Ok(()) => println!("Recording to {path}."),
+            Err(err) => println!("Error: Could not record to {path}, {err}."),
+        },
+        None => println!("Usage - {}record <file>", config.meta_command_prefix),
+    }
+    true
+}
+
+fn stop<T: HostType>(instance: &mut REPLState<T>) -> bool {
+    if instance.stop_recording() {
+        println!("Recording stopped.");
+    } else {
+        println!("Not recording.");
+    }
+    true
+}
+
+fn more<T: HostType>(instance: &mut REPLState<T>, config: &REPLConfig) -> bool {
+    match instance.more(config.page_size.unwrap_or(usize::MAX)) {
+        Some(page) => {
+            println!("{page}");
+            print_more_hint(instance, config);
+        }
+        None => println!("Nothing more to show."),
+    }
+    true
+}
+
+fn help<T: HostType>(instance: &REPLState<T>, config: &REPLConfig) -> bool {
+    let commands = [
+        ("bindings", "b", "", "List all bound names."),
+        ("environment", "e", "", "Show all HostFunctions."),
+        (
+            "doc",
+            "d",
+            " <name>",
+            "Show the documentation for a HostFunction.",
+        ),
+        ("load", "l", " <file>", "Run a script file in this session."),
+        ("reset", "r", "", "Clear all values bound in this session."),
+        ("more", "m", "", "Show the next page of the last result."),
+        (
+            "record",
+            "",
+            " <file>",
+            "Write a transcript of this session to a file.",
+        ),
+        ("stop", "", "", "Stop writing the transcript."),
+        ("parse", "p", " <script>", "Show how a script is parsed."),
+        ("fmt", "f", " <script>", "Show a script formatted."),
+        ("debug", "", " <script>", "Step through a script."),
+        ("status", "s", "", "Show the status of the REPL."),
+        ("help", "h", "", "Show this message."),
+        ("quit", "q", "", "Quit the REPL."),
+    ];
+    let prefix = &config.meta_command_prefix;
+    for (name, short_name, arguments, description) in commands {
+        let usage = if short_name.is_empty() {
+            format!("{prefix}{name}{arguments}")
+        } else {
+            format!("{prefix}{name}, {prefix}{short_name}{arguments}")
+        };
+        println!("{usage:<24}{description}");
+    }
+    for (name, command) in &instance.commands {
+        println!("{:<24}{}", format!("{prefix}{name}"), command.help);
+    }
     true
 }
 
@@ -157,3 +606,54 @@ impl From<HostFunctionBinding> for EnvironmentDisplay {
         }
     }
 }
+
+/// Completes names bound in an Environment, including namespaced HostFunctions like `Bool.and`.
+pub struct NameCompleter {
+    names: Vec<String>,
+}
+
+impl NameCompleter {
+    /// Create a NameCompleter for the names currently bound in the given Environment.
+    pub fn new<T: HostType>(environment: &Environment<T>) -> Self {
+        let mut names: Vec<String> = environment.bound_names().into_iter().collect();
+        names.sort();
+        NameCompleter { names }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '?'
+}
+
+impl Completer for NameCompleter {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Result<(usize, Vec<Pair>)> {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_name_char(*c))
+            .map_or(0, |(index, c)| index + c.len_utf8());
+        let prefix = &line[start..pos];
+        let candidates = self
+            .names
+            .iter()
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| Pair {
+                display: name.clone(),
+                replacement: name.clone(),
+            })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for NameCompleter {
+    type Hint = String;
+}
+
+impl Highlighter for NameCompleter {}
+
+impl Validator for NameCompleter {}
+
+impl Helper for NameCompleter {}
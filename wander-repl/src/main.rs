@@ -5,14 +5,59 @@
 //! This module is the main module for the ligature-repl project.
 
 pub use rustyline::Result;
+use wander::environment::Environment;
 use wander::preludes::common;
 use wander::NoHostType;
-use wander_repl::{start_repl, REPLState};
+use std::io::Read;
+use wander_repl::{format_files, start_repl, REPLConfig, REPLState};
 use wander::preludes::add_print;
 
-fn main() -> Result<()> {
+fn environment() -> Environment<NoHostType> {
     let mut environment = common::<NoHostType>();
     add_print(&mut environment);
-    let mut state = REPLState { environment };
-    start_repl(&mut state)
+    environment
+}
+
+// Run `wander-repl fmt [--check] [files]`, formatting stdin to stdout when no files are given.
+// Returns the exit code, 1 if checking found unformatted scripts or scripts that can't be formatted.
+fn fmt(arguments: &[String]) -> i32 {
+    let check = arguments.iter().any(|argument| argument == "--check");
+    let paths: Vec<String> = arguments
+        .iter()
+        .filter(|argument| *argument != "--check")
+        .cloned()
+        .collect();
+    if paths.is_empty() {
+        let mut script = String::new();
+        if let Err(err) = std::io::stdin().read_to_string(&mut script) {
+            eprintln!("Error: {err}");
+            return 2;
+        }
+        let formatted = wander::try_fmt(&script, wander::FormatOptions::default());
+        if check {
+            return i32::from(formatted.as_ref() != Some(&script));
+        }
+        print!("{}", formatted.unwrap_or(script));
+        return 0;
+    }
+    match format_files(&paths, check) {
+        Ok(unformatted) if check => {
+            unformatted.iter().for_each(|path| println!("{path}"));
+            i32::from(!unformatted.is_empty())
+        }
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            2
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    if arguments.first().is_some_and(|command| command == "fmt") {
+        std::process::exit(fmt(&arguments[1..]));
+    }
+    let mut state = REPLState::new(environment);
+    start_repl(&mut state, &REPLConfig::default())
 }
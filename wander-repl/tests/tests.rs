@@ -1,3 +1,135 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use rustyline::completion::Completer;
+use rustyline::history::DefaultHistory;
+use rustyline::Context;
+use wander::preludes::common;
+use wander::{NoHostType, WanderValue};
+use wander_repl::{format_files, NameCompleter, REPLState};
+
+#[test]
+fn complete_namespaced_host_functions() {
+    let completer = NameCompleter::new(&common::<NoHostType>());
+    let history = DefaultHistory::new();
+    let (start, candidates) = completer
+        .complete("(Bool.n", 7, &Context::new(&history))
+        .unwrap();
+    assert_eq!(start, 1);
+    let names: Vec<String> = candidates
+        .into_iter()
+        .map(|pair| pair.replacement)
+        .collect();
+    assert_eq!(names, vec!["Bool.not".to_owned()]);
+}
+
+#[test]
+fn load_and_reset_session() {
+    let path = std::env::temp_dir().join("wander_repl_load_test.wander");
+    std::fs::write(&path, "val x = 5").unwrap();
+    let mut state = REPLState::new(common::<NoHostType>);
+    assert_eq!(state.load(path.to_str().unwrap()), Ok(WanderValue::Int(5)));
+    assert_eq!(state.run("x"), Ok(WanderValue::Int(5)));
+    state.reset();
+    assert!(state.run("x").is_err());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn run_custom_command() {
+    let mut state = REPLState::new(common::<NoHostType>);
+    state.add_command(
+        "connect",
+        "Bind the given address.",
+        |input, environment| {
+            environment.bind("address".to_owned(), WanderValue::String(input.to_owned()))
+        },
+    );
+    assert!(state.run_command("connect", "localhost"));
+    assert!(!state.run_command("flush", ""));
+    assert_eq!(
+        state.run("address"),
+        Ok(WanderValue::String("localhost".to_owned()))
+    );
+}
+
+#[test]
+fn page_long_output() {
+    let mut state = REPLState::new(common::<NoHostType>);
+    assert_eq!(state.page("1\n2\n3\n4\n5", 2), "1\n2");
+    assert_eq!(state.remaining_lines(), 3);
+    assert_eq!(state.more(2), Some("3\n4".to_owned()));
+    assert_eq!(state.more(2), Some("5".to_owned()));
+    assert_eq!(state.more(2), None);
+}
+
+#[test]
+fn results_are_bound_in_the_session() {
+    let mut state = REPLState::new(common::<NoHostType>);
+    assert_eq!(state.last_result_name(), None);
+    assert_eq!(state.run("5"), Ok(WanderValue::Int(5)));
+    assert_eq!(state.run("Int.add it 1"), Ok(WanderValue::Int(6)));
+    assert_eq!(state.last_result_name(), Some("r2".to_owned()));
+    assert_eq!(state.run("Int.add r1 r2"), Ok(WanderValue::Int(11)));
+    state.reset();
+    assert_eq!(state.last_result_name(), None);
+    assert!(state.run("r1").is_err());
+}
+
+#[test]
+fn record_transcript() {
+    let path = std::env::temp_dir().join("wander_repl_transcript_test.txt");
+    let mut state = REPLState::new(common::<NoHostType>);
+    state.start_recording(path.to_str().unwrap()).unwrap();
+    assert!(state.is_recording());
+    state.run("Int.add 1 2").unwrap();
+    assert!(state.run("missing").is_err());
+    assert!(state.stop_recording());
+    state.run("5").unwrap();
+    let transcript = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = transcript
+        .lines()
+        .filter(|line| !line.starts_with("# took"))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "# Wander transcript",
+            "",
+            "> Int.add 1 2",
+            "r1 = 3",
+            "",
+            "> missing",
+            "Error: Error looking up missing, missing is not bound.",
+            "",
+        ]
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn format_script_files() {
+    let path = std::env::temp_dir().join("wander_repl_format_test.wander");
+    let path = path.to_str().unwrap().to_owned();
+    std::fs::write(&path, "val   x = [ 1 2 ] ;x").unwrap();
+    let paths = vec![path.clone()];
+    assert_eq!(format_files(&paths, true).unwrap(), paths);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "val   x = [ 1 2 ] ;x");
+    assert_eq!(format_files(&paths, false).unwrap(), paths);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "val x = [1 2];\nx\n");
+    assert!(format_files(&paths, true).unwrap().is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn unbalanced_script_files_fail_checks() {
+    let path = std::env::temp_dir().join("wander_repl_unbalanced_test.wander");
+    let path = path.to_str().unwrap().to_owned();
+    std::fs::write(&path, "[1 2").unwrap();
+    let paths = vec![path.clone()];
+    assert_eq!(format_files(&paths, true).unwrap(), paths);
+    assert_eq!(format_files(&paths, false).unwrap(), paths);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "[1 2");
+    std::fs::remove_file(path).unwrap();
+}
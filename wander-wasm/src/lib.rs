@@ -5,19 +5,19 @@
 //! This project exposes functionality from the Rust implementation of Wander to WASM and JS runtimes thanks to wasm-bindgen and wasm-pack.
 
 mod utils;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
 use serde::Serialize;
-use wander::{HostType, WanderError, WanderValue};
+use wander::{
+    environment::Environment, trace::TraceNode, HostFunction, HostFunctionBinding, HostType,
+    ModuleResolver, NoHostType, WanderError, WanderValue,
+};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
 pub fn run(script: String) -> JsValue {
     let mut bindings = wander::preludes::common::<wander::NoHostType>();
-    let res = wander::run(&script, &mut bindings);
-    let res = RunResult {
-        object: res.clone(),
-        string: res.map(|res| format!("{}", res)),
-    };
-    serde_wasm_bindgen::to_value(&res).unwrap()
+    run_with_bindings(&script, &mut bindings)
 }
 
 #[derive(Serialize)]
@@ -28,6 +28,143 @@ pub struct RunResult<T: HostType> {
 
 #[wasm_bindgen]
 pub fn introspect(script: String) -> JsValue {
+    let bindings = wander::preludes::common::<wander::NoHostType>();
+    serde_wasm_bindgen::to_value(&wander::introspect(&script, &bindings)).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn run_traced(script: String) -> JsValue {
     let mut bindings = wander::preludes::common::<wander::NoHostType>();
-    serde_wasm_bindgen::to_value(&wander::introspect(&script, &mut bindings)).unwrap()
+    run_traced_with_bindings(&script, &mut bindings)
+}
+
+#[derive(Serialize)]
+pub struct TracedRunResult<T: HostType> {
+    result: RunResult<T>,
+    trace: Vec<TraceNode>,
+}
+
+fn run_traced_with_bindings(script: &str, bindings: &mut Environment<NoHostType>) -> JsValue {
+    let (res, trace) = wander::trace::run_traced(script, bindings);
+    let res = TracedRunResult {
+        result: RunResult {
+            object: res.clone(),
+            string: res.map(|res| format!("{}", res)),
+        },
+        trace,
+    };
+    serde_wasm_bindgen::to_value(&res).unwrap()
+}
+
+fn run_with_bindings(script: &str, bindings: &mut Environment<NoHostType>) -> JsValue {
+    let res = wander::run(script, bindings);
+    let res = RunResult {
+        object: res.clone(),
+        string: res.map(|res| format!("{}", res)),
+    };
+    serde_wasm_bindgen::to_value(&res).unwrap()
+}
+
+/// A Wander Environment that keeps its bindings between runs
+/// and can be extended with HostFunctions written in JS.
+#[wasm_bindgen]
+pub struct WanderInstance {
+    bindings: Environment<NoHostType>,
+    modules: Rc<VirtualModules>,
+}
+
+#[wasm_bindgen]
+impl WanderInstance {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WanderInstance {
+        let modules = Rc::new(VirtualModules::default());
+        let mut bindings = wander::preludes::common::<NoHostType>();
+        bindings.set_module_resolver(modules.clone());
+        WanderInstance { bindings, modules }
+    }
+
+    /// Add a file to the virtual filesystem scripts import modules from,
+    /// so `import Util from "util.wander"` loads the given source.
+    pub fn add_module(&mut self, path: String, source: String) {
+        self.modules.0.borrow_mut().insert(path, source);
+    }
+
+    /// Bind a JS function as a HostFunction.
+    /// The number of parameters the HostFunction takes is the JS function's `length`.
+    /// Arguments and results are converted with serde_wasm_bindgen.
+    pub fn bind_host_function(&mut self, name: String, function: js_sys::Function) {
+        self.bindings
+            .bind_host_function(Rc::new(JsHostFunction { name, function }));
+    }
+
+    pub fn run(&mut self, script: String) -> JsValue {
+        run_with_bindings(&script, &mut self.bindings)
+    }
+
+    /// Run a script, returning its result along with a trace of every expression evaluated.
+    pub fn run_traced(&mut self, script: String) -> JsValue {
+        run_traced_with_bindings(&script, &mut self.bindings)
+    }
+
+    pub fn introspect(&self, script: String) -> JsValue {
+        serde_wasm_bindgen::to_value(&wander::introspect(&script, &self.bindings)).unwrap()
+    }
+}
+
+impl Default for WanderInstance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The module sources added to a WanderInstance, keyed by path.
+#[derive(Default)]
+struct VirtualModules(RefCell<HashMap<String, String>>);
+
+impl ModuleResolver for VirtualModules {
+    fn resolve(&self, path: &str) -> Result<String, WanderError> {
+        self.0.borrow().resolve(path)
+    }
+}
+
+struct JsHostFunction {
+    name: String,
+    function: js_sys::Function,
+}
+
+impl HostFunction<NoHostType> for JsHostFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<NoHostType>],
+        _bindings: &mut Environment<NoHostType>,
+    ) -> Result<WanderValue<NoHostType>, WanderError> {
+        let js_arguments = js_sys::Array::new();
+        for argument in arguments {
+            let argument = serde_wasm_bindgen::to_value(argument)
+                .map_err(|err| WanderError::new(err.to_string()))?;
+            js_arguments.push(&argument);
+        }
+        let result = self
+            .function
+            .apply(&JsValue::NULL, &js_arguments)
+            .map_err(|err| {
+                WanderError::new(
+                    err.as_string()
+                        .unwrap_or_else(|| format!("`{}` function threw an error.", self.name)),
+                )
+            })?;
+        serde_wasm_bindgen::from_value(result).map_err(|err| WanderError::new(err.to_string()))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.clone(),
+            parameters: (0..self.function.length())
+                .map(|index| (format!("arg{index}"), None))
+                .collect(),
+            result: None,
+            doc_string: String::new(),
+            ..Default::default()
+        }
+    }
 }
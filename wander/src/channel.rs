@@ -0,0 +1,234 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Contains Channels, so Lambdas run with `Task.par` can pass values to each other.
+//! Only available with the `parallel` feature.
+
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Display},
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Serialize, Serializer};
+
+use crate::{
+    environment::Environment,
+    preludes::{add_table_functions, TableFunction},
+    CancelFlag, ExecutionContext, HostType, HostValue, WanderError, WanderValue,
+};
+
+// How often a waiting receive checks its CancelFlag, which can't wake it up.
+const CANCEL_POLL: Duration = Duration::from_millis(10);
+
+/// A queue of values shared between Lambdas, values are received in the order they were sent.
+/// Channels are equal only to themselves.
+pub struct Channel<T: Clone + PartialEq + Eq> {
+    state: Arc<ChannelState<T>>,
+}
+
+struct ChannelState<T: Clone + PartialEq + Eq> {
+    queue: Mutex<Queue<T>>,
+    ready: Condvar,
+}
+
+struct Queue<T: Clone + PartialEq + Eq> {
+    values: VecDeque<WanderValue<T>>,
+    closed: bool,
+}
+
+impl<T: Clone + PartialEq + Eq> Channel<T> {
+    /// Create an empty, open Channel.
+    pub fn new() -> Channel<T> {
+        Channel {
+            state: Arc::new(ChannelState {
+                queue: Mutex::new(Queue {
+                    values: VecDeque::new(),
+                    closed: false,
+                }),
+                ready: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Add a value to the end of the Channel, returns false if the Channel is closed.
+    pub fn send(&self, value: WanderValue<T>) -> bool {
+        let mut queue = self.state.queue.lock().unwrap();
+        if queue.closed {
+            return false;
+        }
+        queue.values.push_back(value);
+        self.state.ready.notify_one();
+        true
+    }
+
+    /// Take the value at the front of the Channel, waiting until one is sent.
+    /// Returns None once the Channel is closed and empty.
+    /// Waiting fails once the deadline passes or the CancelFlag is set,
+    /// see `Environment::execution_context` and `Environment::cancel_flag`.
+    pub fn receive(
+        &self,
+        deadline: Option<Instant>,
+        cancel: Option<&CancelFlag>,
+    ) -> Result<Option<WanderValue<T>>, WanderError> {
+        let mut queue = self.state.queue.lock().unwrap();
+        loop {
+            if let Some(value) = queue.values.pop_front() {
+                return Ok(Some(value));
+            }
+            if queue.closed {
+                return Ok(None);
+            }
+            ExecutionContext {
+                remaining_steps: None,
+                deadline,
+                cancelled: cancel.is_some_and(CancelFlag::is_cancelled),
+            }
+            .check()?;
+            let wait = match deadline {
+                Some(deadline) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .min(CANCEL_POLL),
+                None => CANCEL_POLL,
+            };
+            queue = self.state.ready.wait_timeout(queue, wait).unwrap().0;
+        }
+    }
+
+    /// Stop any more values from being sent, values already sent can still be received.
+    pub fn close(&self) {
+        self.state.queue.lock().unwrap().closed = true;
+        self.state.ready.notify_all();
+    }
+}
+
+impl<T: Clone + PartialEq + Eq> Default for Channel<T> {
+    fn default() -> Self {
+        Channel::new()
+    }
+}
+
+impl<T: Clone + PartialEq + Eq> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        Channel {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq + Eq> PartialEq for Channel<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl<T: Clone + PartialEq + Eq> Eq for Channel<T> {}
+
+impl<T: Clone + PartialEq + Eq> Display for Channel<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<Channel>")
+    }
+}
+
+impl<T: Clone + PartialEq + Eq> Debug for Channel<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Channel")
+    }
+}
+
+impl<T: Clone + PartialEq + Eq> Serialize for Channel<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("<Channel>")
+    }
+}
+
+/// A HostType that can hold Channels, needed to bind the Channel functions.
+pub trait ChannelHost: HostType {
+    /// Wrap a Channel in the HostType.
+    fn from_channel(channel: Channel<Self>) -> Self;
+    /// Get the Channel held by a value, if it is one.
+    fn as_channel(&self) -> Option<&Channel<Self>>;
+}
+
+fn as_channel<T: ChannelHost>(value: &WanderValue<T>) -> Option<&Channel<T>> {
+    match value {
+        WanderValue::HostValue(HostValue { value }) => value.as_channel(),
+        _ => None,
+    }
+}
+
+/// Bind `Channel.new`, `Channel.send`, `Channel.receive`, and `Channel.close`.
+pub fn add_channel_functions<T: ChannelHost + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 4] = [
+        TableFunction {
+            name: "Channel.new",
+            doc_string: "Create an empty Channel.",
+            parameters: &[],
+            requires: "no arguments",
+            pure: false,
+            run: |arguments, _| match arguments {
+                [] => Some(Ok(WanderValue::HostValue(HostValue {
+                    value: T::from_channel(Channel::new()),
+                }))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Channel.send",
+            doc_string: "Send a value to a Channel.",
+            parameters: &["channel", "value"],
+            requires: "a Channel and a value",
+            pure: false,
+            run: |arguments, _| match arguments {
+                [channel, value] => {
+                    let channel = as_channel(channel)?;
+                    Some(match channel.send(value.clone()) {
+                        true => Ok(WanderValue::Nothing),
+                        false => Err(WanderError::new(
+                            "Cannot send to a closed Channel.".to_owned(),
+                        )),
+                    })
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Channel.receive",
+            doc_string: "Receive the next value from a Channel, waiting until one is sent. \
+                Returns nothing once the Channel is closed and every value has been received. \
+                Fails if the script is cancelled or runs out of time while waiting.",
+            parameters: &["channel"],
+            requires: "a Channel",
+            pure: false,
+            run: |arguments, environment| match arguments {
+                [channel] => {
+                    let deadline = environment.execution_context().deadline;
+                    let cancel = environment.cancel_flag();
+                    Some(
+                        as_channel(channel)?
+                            .receive(deadline, cancel.as_ref())
+                            .map(|value| value.unwrap_or(WanderValue::Nothing)),
+                    )
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Channel.close",
+            doc_string: "Close a Channel so no more values can be sent.",
+            parameters: &["channel"],
+            requires: "a Channel",
+            pure: false,
+            run: |arguments, _| match arguments {
+                [channel] => {
+                    as_channel(channel)?.close();
+                    Some(Ok(WanderValue::Nothing))
+                }
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
@@ -0,0 +1,131 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Contains a Debugger, an EvalObserver that pauses evaluation at breakpoints and while stepping.
+
+use crate::{
+    environment::Environment,
+    interpreter::{EvalObserver, Expression},
+    HostType, Location, Span, WanderError, WanderValue,
+};
+
+/// Where evaluation is paused, passed to a Debugger's pause handler.
+pub struct DebugPause<'a, T: HostType> {
+    /// The offset into the script of the expression about to be evaluated.
+    pub position: usize,
+    /// How deeply the expression is nested, starting at 1.
+    pub depth: usize,
+    /// The Environment the expression will be evaluated in, to look up bound values.
+    pub environment: &'a Environment<T>,
+}
+
+/// What a Debugger does after it pauses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugCommand {
+    /// Run until the next breakpoint.
+    Continue,
+    /// Pause at the next expression.
+    Step,
+    /// Pause at the next expression that isn't nested in this one.
+    StepOver,
+    /// Pause at the next expression outside of the one containing this one.
+    StepOut,
+    /// Stop the script with an error.
+    Stop,
+}
+
+/// Handles each pause of a Debugger, by showing where it is and asking what to do next.
+pub type PauseHandler<T> = Box<dyn FnMut(&DebugPause<T>) -> DebugCommand>;
+
+/// An EvalObserver that pauses before expressions inside of breakpoints and while stepping,
+/// calling its PauseHandler each time. Set it with `Environment::set_eval_observer`.
+pub struct Debugger<T: HostType> {
+    breakpoints: Vec<Span>,
+    command: DebugCommand,
+    // The depth the last pause was at.
+    paused_depth: usize,
+    // The position of the last pause, so expressions starting at the same position only pause once.
+    paused_position: Option<usize>,
+    depth: usize,
+    on_pause: PauseHandler<T>,
+}
+
+impl<T: HostType> Debugger<T> {
+    /// Create a Debugger without breakpoints, that runs until one is added.
+    pub fn new(on_pause: PauseHandler<T>) -> Debugger<T> {
+        Debugger {
+            breakpoints: vec![],
+            command: DebugCommand::Continue,
+            paused_depth: 0,
+            paused_position: None,
+            depth: 0,
+            on_pause,
+        }
+    }
+
+    /// Pause before any expression starting in the span.
+    /// A span whose end is equal to its start only matches expressions starting there.
+    pub fn add_breakpoint(&mut self, span: Span) {
+        self.breakpoints.push(span);
+    }
+
+    /// Pause at the next expression, call before running a script to pause at its start.
+    pub fn step(&mut self) {
+        self.command = DebugCommand::Step;
+    }
+
+    fn at_breakpoint(&self, position: usize) -> bool {
+        self.breakpoints
+            .iter()
+            .any(|span| span.start <= position && position < span.end.max(span.start + 1))
+    }
+}
+
+impl<T: HostType> EvalObserver<T> for Debugger<T> {
+    fn before_eval(
+        &mut self,
+        expression: &Location<Expression>,
+        environment: &Environment<T>,
+    ) -> Result<(), WanderError> {
+        self.depth += 1;
+        let position = expression.1;
+        if self.paused_position == Some(position) {
+            return Ok(());
+        }
+        self.paused_position = None;
+        let stepped = match self.command {
+            DebugCommand::Step => true,
+            DebugCommand::StepOver => self.depth <= self.paused_depth,
+            DebugCommand::StepOut => self.depth < self.paused_depth,
+            DebugCommand::Continue | DebugCommand::Stop => false,
+        };
+        let breakpoint = self.at_breakpoint(position);
+        if !stepped && !breakpoint {
+            return Ok(());
+        }
+        self.paused_depth = self.depth;
+        self.paused_position = Some(position);
+        self.command = (self.on_pause)(&DebugPause {
+            position,
+            depth: self.depth,
+            environment,
+        });
+        match self.command {
+            DebugCommand::Stop => Err(WanderError::at(
+                "Script was stopped by the debugger".to_owned(),
+                position,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn after_eval(
+        &mut self,
+        _expression: &Location<Expression>,
+        _result: &Result<WanderValue<T>, WanderError>,
+        _environment: &Environment<T>,
+    ) {
+        self.depth -= 1;
+    }
+}
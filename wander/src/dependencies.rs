@@ -0,0 +1,187 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Finds what the top-level bindings of a script depend on, see `Introspection::dependencies`.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::{
+    environment::Environment,
+    interpreter::Expression,
+    parser::{Element, Pattern},
+    translation::{express, replace_unquotes},
+    HostType, Location,
+};
+
+/// The HostFunctions and outer names a top-level binding refers to,
+/// so hosts can decide if the binding can be cached, run in parallel, or sandboxed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BindingDependencies {
+    /// The name of the binding.
+    pub name: String,
+    /// HostFunctions used by the binding's value, in the order they first appear.
+    pub host_functions: Vec<String>,
+    /// Names used by the binding's value that are bound outside of it,
+    /// by other bindings in the script or by the Environment, in the order they first appear.
+    /// References a binding makes to itself aren't included.
+    pub names: Vec<String>,
+}
+
+/// Find the dependencies of each top-level binding, both `val` statements and
+/// the declarations of a `let` that makes up a whole statement.
+pub(crate) fn dependencies<T: HostType>(
+    expression: &Location<Expression>,
+    environment: &Environment<T>,
+) -> Vec<BindingDependencies> {
+    let statements = match expression {
+        Location(Expression::Sequence(statements), _) => statements.iter().collect(),
+        expression => vec![expression],
+    };
+    let mut results = vec![];
+    for statement in statements {
+        if let Location(Expression::Let(decls, _), _) = statement {
+            for (name, _, value) in decls {
+                let mut collector = Collector {
+                    environment,
+                    bound: HashSet::from([name.clone()]),
+                    dependencies: BindingDependencies {
+                        name: name.clone(),
+                        host_functions: vec![],
+                        names: vec![],
+                    },
+                };
+                collector.collect(value);
+                results.push(collector.dependencies);
+            }
+        }
+    }
+    results
+}
+
+// Walks an Expression, tracking the names bound inside of it.
+struct Collector<'a, T: HostType> {
+    environment: &'a Environment<T>,
+    bound: HashSet<String>,
+    dependencies: BindingDependencies,
+}
+
+impl<T: HostType> Collector<'_, T> {
+    fn reference(&mut self, name: &String) {
+        if self.bound.contains(name) {
+            return;
+        }
+        let found = match self.environment.read_host_function(name) {
+            Some(_) => &mut self.dependencies.host_functions,
+            None => &mut self.dependencies.names,
+        };
+        if !found.contains(name) {
+            found.push(name.clone());
+        }
+    }
+
+    // Collect from an Expression with extra names bound, restoring the outer names afterwards.
+    fn collect_scoped(&mut self, names: Vec<String>, expression: &Location<Expression>) {
+        let outer = self.bound.clone();
+        self.bound.extend(names);
+        self.collect(expression);
+        self.bound = outer;
+    }
+
+    fn collect(&mut self, expression: &Location<Expression>) {
+        match &expression.0 {
+            Expression::Boolean(_)
+            | Expression::Int(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Char(_)
+            | Expression::Identifier(_)
+            | Expression::Keyword(_)
+            | Expression::Nothing
+            | Expression::CustomLiteral(_) => (),
+            Expression::Name(name) | Expression::TaggedName(name, _) => self.reference(name),
+            Expression::HostFunction(name) => self.reference(name),
+            Expression::Quantity(value, _) => self.collect(value),
+            Expression::Let(decls, body) => {
+                let outer = self.bound.clone();
+                for (name, _, value) in decls {
+                    self.bound.insert(name.clone());
+                    self.collect(value);
+                }
+                self.collect(body);
+                self.bound = outer;
+            }
+            Expression::Lambda(name, _, _, body) => {
+                if let Ok(body) = express(body) {
+                    self.collect_scoped(vec![name.clone()], &body);
+                }
+            }
+            Expression::TailCall(name, arguments) => {
+                self.reference(name);
+                arguments.iter().for_each(|argument| self.collect(argument));
+            }
+            Expression::Conditional(condition, then, otherwise) => {
+                self.collect(condition);
+                self.collect(then);
+                self.collect(otherwise);
+            }
+            Expression::Application(expressions)
+            | Expression::Tuple(expressions)
+            | Expression::List(expressions)
+            | Expression::Sequence(expressions) => {
+                expressions.iter().for_each(|value| self.collect(value))
+            }
+            Expression::Quote(quoted, expressions) => {
+                expressions.iter().for_each(|value| self.collect(value));
+                // The rest of the quote reads names when it's evaluated,
+                // its unquotes were collected above so they're left out.
+                let mut quoted = quoted.as_ref().clone();
+                let replaced = replace_unquotes(&mut quoted, &mut |element| {
+                    Ok(vec![Location(Element::Nothing, element.1)])
+                });
+                if let (Ok(()), Ok(quoted)) = (replaced, express(&quoted)) {
+                    self.collect(&quoted);
+                }
+            }
+            Expression::Set(values) => values.iter().for_each(|value| self.collect(value)),
+            Expression::Record(fields) => fields.values().for_each(|value| self.collect(value)),
+            Expression::RecordUpdate(base, updates) => {
+                self.collect(base);
+                updates.values().for_each(|value| self.collect(value));
+            }
+            Expression::FieldAccess(base, _) => self.collect(base),
+            Expression::Match(value, arms) => {
+                self.collect(value);
+                for (pattern, body) in arms {
+                    let mut names = vec![];
+                    pattern_names(pattern, &mut names);
+                    self.collect_scoped(names, body);
+                }
+            }
+            Expression::Try(body, name, handler) => {
+                self.collect(body);
+                self.collect_scoped(vec![name.clone()], handler);
+            }
+            Expression::TryResult(body) | Expression::Pure(body) => self.collect(body),
+            Expression::Ensure(body, cleanup) => {
+                self.collect(body);
+                self.collect(cleanup);
+            }
+        }
+    }
+}
+
+fn pattern_names(pattern: &Pattern, names: &mut Vec<String>) {
+    match pattern {
+        Pattern::Name(name) => names.push(name.clone()),
+        Pattern::Tuple(patterns) | Pattern::List(patterns) => patterns
+            .iter()
+            .for_each(|pattern| pattern_names(pattern, names)),
+        Pattern::Record(patterns) => patterns
+            .values()
+            .for_each(|pattern| pattern_names(pattern, names)),
+        _ => (),
+    }
+}
@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Contains DynHostValue, a HostType that can hold values of any type.
+
+use serde::{Serialize, Serializer};
+use std::{
+    any::Any,
+    fmt::{Debug, Display},
+    rc::Rc,
+};
+
+/// A host value whose type is erased, for use as the HostType of an Environment
+/// so different host modules can each contribute their own value types
+/// instead of sharing a single enum.
+/// Values are compared, displayed, and serialized with the implementations of their own type,
+/// and values of different types are never equal.
+#[derive(Clone)]
+pub struct DynHostValue {
+    value: Rc<dyn Any>,
+    type_name: &'static str,
+    eq: fn(&dyn Any, &dyn Any) -> bool,
+    fmt: fn(&dyn Any, &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+    to_json: fn(&dyn Any) -> Result<serde_json::Value, serde_json::Error>,
+}
+
+impl DynHostValue {
+    /// Wrap a value of any type that can be compared, displayed, and serialized.
+    pub fn new<V: Any + PartialEq + Display + Serialize>(value: V) -> DynHostValue {
+        DynHostValue {
+            value: Rc::new(value),
+            type_name: std::any::type_name::<V>(),
+            eq: |left, right| match (left.downcast_ref::<V>(), right.downcast_ref::<V>()) {
+                (Some(left), Some(right)) => left == right,
+                _ => false,
+            },
+            fmt: |value, f| match value.downcast_ref::<V>() {
+                Some(value) => Display::fmt(value, f),
+                None => Err(std::fmt::Error),
+            },
+            to_json: |value| match value.downcast_ref::<V>() {
+                Some(value) => serde_json::to_value(value),
+                None => serde_json::to_value(()),
+            },
+        }
+    }
+
+    /// Get the wrapped value if it has the given type.
+    pub fn downcast_ref<V: Any>(&self) -> Option<&V> {
+        self.value.downcast_ref::<V>()
+    }
+
+    /// The name of the wrapped value's type.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl PartialEq for DynHostValue {
+    fn eq(&self, other: &Self) -> bool {
+        (self.eq)(self.value.as_ref(), other.value.as_ref())
+    }
+}
+
+impl Eq for DynHostValue {}
+
+impl Display for DynHostValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (self.fmt)(self.value.as_ref(), f)
+    }
+}
+
+impl Debug for DynHostValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DynHostValue({}: ", self.type_name)?;
+        (self.fmt)(self.value.as_ref(), f)?;
+        write!(f, ")")
+    }
+}
+
+impl Serialize for DynHostValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.to_json)(self.value.as_ref())
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
@@ -3,21 +3,83 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::{
-    parser::Element, EpsilonChecker, HostFunction, HostFunctionBinding, HostType, TokenTransformer,
-    TypeChecker, WanderValue, Location,
+    combine_errors, interpreter::{call_function, EvalObserver}, parser::Element, AuditSink, ModuleResolver, DryRunStub, EpsilonChecker, HostFunctionCall, SkippedCall, HostFunction, HostFunctionBinding, HostType, IntOverflow, LiteralParser,
+    CancelFlag, ExecutionContext, RunConfig, RunHooks, CANCELLED, steps_exceeded, RunStats, TokenTransformer, TypeChecker, WanderError, WanderValue, Location,
 };
 use std::{
+    any::{Any, TypeId},
     cell::RefCell,
     collections::{HashMap, HashSet},
+    ops::{Deref, DerefMut},
     rc::Rc,
+    time::Instant,
 };
 
+/// Tracks how much work the current script has done, to check it against its RunConfig.
+#[derive(Default)]
+pub(crate) struct RunState {
+    config: RunConfig,
+    steps: usize,
+    depth: usize,
+    peak_depth: usize,
+    started: Option<Instant>,
+    // The position of the last expression entered, so limits reached by HostFunctions can be located.
+    position: usize,
+}
+
 /// A structure used to setup the environment a Wander program is executed in.
 pub struct Environment<T: HostType> {
     token_transformers: RefCell<HashMap<String, Rc<TokenTransformer>>>,
-    host_functions: RefCell<HashMap<String, Rc<dyn HostFunction<T>>>>,
-    scopes: Vec<HashMap<String, WanderValue<T>>>,
-    type_checker: Box<dyn TypeChecker<T>>,
+    literal_parsers: RefCell<HashMap<String, Rc<LiteralParser<T>>>>,
+    // Scopes and HostFunctions are shared with forked Environments until either side binds something.
+    host_functions: RefCell<Rc<HashMap<String, Rc<dyn HostFunction<T>>>>>,
+    scopes: Vec<Rc<HashMap<String, WanderValue<T>>>>,
+    type_checker: Rc<dyn TypeChecker<T>>,
+    warnings: RefCell<Vec<WanderError>>,
+    run_state: RunState,
+    contexts: HashMap<TypeId, Box<dyn Any>>,
+    audit_sink: Option<Box<dyn AuditSink>>,
+    run_hooks: Vec<Box<dyn RunHooks<T>>>,
+    eval_observer: Option<Box<dyn EvalObserver<T>>>,
+    granted_capabilities: Option<HashSet<String>>,
+    dry_run: Option<DryRun<T>>,
+    pure: bool,
+    int_overflow: IntOverflow,
+    module_resolver: Option<Rc<dyn ModuleResolver>>,
+    event_handlers: HashMap<String, Vec<WanderValue<T>>>,
+}
+
+/// The parts of a running script's execution context `Task.par` carries to the Environments of its threads:
+/// granted capabilities, purity, Int overflow, and what's left of its limits, sharing its CancelFlag.
+/// HostFunction calls are still audited and skipped during a dry run by the Environment it was forked from.
+#[cfg(feature = "parallel")]
+pub(crate) struct ForkedContext {
+    granted_capabilities: Option<HashSet<String>>,
+    pure: bool,
+    int_overflow: IntOverflow,
+    config: RunConfig,
+    pub(crate) audited: bool,
+    pub(crate) dry_run: bool,
+}
+
+// The stub and skipped calls of a dry run.
+struct DryRun<T: HostType> {
+    stub: DryRunStub<T>,
+    skipped: Vec<SkippedCall<T>>,
+}
+
+// The longest an argument or result summary in a HostFunctionCall or TraceNode can be.
+const SUMMARY_LENGTH: usize = 80;
+
+pub(crate) fn summarize<T: HostType>(value: &WanderValue<T>) -> String {
+    let summary = value.to_string();
+    if summary.chars().count() > SUMMARY_LENGTH {
+        let mut summary: String = summary.chars().take(SUMMARY_LENGTH - 3).collect();
+        summary.push_str("...");
+        summary
+    } else {
+        summary
+    }
 }
 
 ///
@@ -30,15 +92,56 @@ impl<T: HostType> Environment<T> {
     pub fn new() -> Environment<T> {
         Environment {
             token_transformers: RefCell::new(HashMap::new()),
-            host_functions: RefCell::new(HashMap::new()),
-            scopes: vec![HashMap::new()],
-            type_checker: Box::new(EpsilonChecker {}),
+            literal_parsers: RefCell::new(HashMap::new()),
+            host_functions: RefCell::new(Rc::new(HashMap::new())),
+            scopes: vec![Rc::new(HashMap::new())],
+            type_checker: Rc::new(EpsilonChecker {}),
+            warnings: RefCell::new(vec![]),
+            run_state: RunState::default(),
+            contexts: HashMap::new(),
+            audit_sink: None,
+            run_hooks: vec![],
+            eval_observer: None,
+            granted_capabilities: None,
+            dry_run: None,
+            pure: false,
+            int_overflow: IntOverflow::Error,
+            module_resolver: None,
+            event_handlers: HashMap::new(),
+        }
+    }
+
+    /// Create a child Environment that starts with the same bindings as this one.
+    /// Scopes and HostFunctions are shared, and the fork binds into a new empty Scope of its own,
+    /// so forking an Environment that holds a large prelude is cheap and stays cheap after binding.
+    /// Granted capabilities, purity, Int overflow, event handlers, and the ModuleResolver are kept, contexts, warnings, audit sinks, RunHooks, EvalObservers, and dry runs are not.
+    pub fn fork(&self) -> Environment<T> {
+        let mut scopes = self.scopes.clone();
+        scopes.push(Rc::new(HashMap::new()));
+        Environment {
+            token_transformers: self.token_transformers.clone(),
+            literal_parsers: self.literal_parsers.clone(),
+            host_functions: self.host_functions.clone(),
+            scopes,
+            type_checker: self.type_checker.clone(),
+            warnings: RefCell::new(vec![]),
+            run_state: RunState::default(),
+            contexts: HashMap::new(),
+            audit_sink: None,
+            run_hooks: vec![],
+            eval_observer: None,
+            granted_capabilities: self.granted_capabilities.clone(),
+            dry_run: None,
+            pure: self.pure,
+            int_overflow: self.int_overflow,
+            module_resolver: self.module_resolver.clone(),
+            event_handlers: self.event_handlers.clone(),
         }
     }
 
     /// Add a new Scope to these Bindings.
     pub fn add_scope(&mut self) {
-        self.scopes.push(HashMap::new());
+        self.scopes.push(Rc::new(HashMap::new()));
     }
 
     /// Remove the current Scope from these Bindings.
@@ -66,15 +169,48 @@ impl<T: HostType> Environment<T> {
     /// Bind a new Value in this Scope.
     pub fn bind(&mut self, name: String, value: WanderValue<T>) {
         let mut current_scope = self.scopes.pop().unwrap();
-        current_scope.insert(name, value);
+        Rc::make_mut(&mut current_scope).insert(name, value);
         self.scopes.push(current_scope);
     }
 
+    /// Remove the innermost binding of a name and return its Value.
+    /// A binding of the same name in an outer Scope becomes visible again.
+    pub fn remove(&mut self, name: &str) -> Option<WanderValue<T>> {
+        for scope in self.scopes.iter_mut().rev() {
+            if scope.contains_key(name) {
+                return Rc::make_mut(scope).remove(name);
+            }
+        }
+        None
+    }
+
+    /// Check if a name is bound to a Value in any Scope or to a HostFunction.
+    pub fn contains(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains_key(name))
+            || self.host_functions.borrow().contains_key(name)
+    }
+
+    /// Get the names bound in the current Scope.
+    pub fn names_in_current_scope(&self) -> HashSet<String> {
+        self.scopes
+            .last()
+            .map(|scope| scope.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the number of Scopes, a new Environment has one.
+    pub fn scope_depth(&self) -> usize {
+        self.scopes.len()
+    }
+
     /// Add a new HostFunction.
     pub fn bind_host_function(&mut self, function: Rc<dyn HostFunction<T>>) {
-        let full_name = function.binding().name.to_string();
-        self.host_functions
-            .borrow_mut()
+        self.bind_host_function_as(function.binding().name, function);
+    }
+
+    // Bind a HostFunction under the given name instead of the name in its binding.
+    fn bind_host_function_as(&mut self, full_name: String, function: Rc<dyn HostFunction<T>>) {
+        Rc::make_mut(&mut self.host_functions.borrow_mut())
             .insert(full_name.clone(), function.clone());
         let mut parameters = function.binding().parameters.clone();
         let mut result = None;
@@ -107,7 +243,309 @@ impl<T: HostType> Environment<T> {
                 ));
             }
         });
-        self.bind(full_name, result.unwrap());
+        // HostFunctions without parameters aren't bound as Lambdas, reading their name runs them.
+        match result {
+            Some(result) => self.bind(full_name, result),
+            None => self.unbind(&full_name),
+        }
+    }
+
+    // Remove a name from the current Scope.
+    fn unbind(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            Rc::make_mut(scope).remove(name);
+        }
+    }
+
+    /// Replace the HostFunction bound to the given name with a mock, for testing scripts that
+    /// call functions like `Time.now` or `Http.get`.
+    /// The original HostFunction is restored when the returned guard is dropped,
+    /// use the guard in place of this Environment until then.
+    pub fn override_host_function(
+        &mut self,
+        name: &str,
+        mock: Rc<dyn HostFunction<T>>,
+    ) -> HostFunctionOverride<'_, T> {
+        let original = self.read_host_function(&name.to_owned());
+        self.bind_host_function_as(name.to_owned(), mock);
+        HostFunctionOverride {
+            environment: self,
+            name: name.to_owned(),
+            original,
+        }
+    }
+
+    /// Send a record of every HostFunction call to the given AuditSink, replacing any previous sink.
+    pub fn set_audit_sink(&mut self, sink: Box<dyn AuditSink>) {
+        self.audit_sink = Some(sink);
+    }
+
+    /// Stop auditing HostFunction calls, returning the AuditSink that was set.
+    pub fn take_audit_sink(&mut self) -> Option<Box<dyn AuditSink>> {
+        self.audit_sink.take()
+    }
+
+    /// Add RunHooks to be called around every script run in this Environment,
+    /// after any RunHooks already added.
+    pub fn add_run_hooks(&mut self, hooks: Box<dyn RunHooks<T>>) {
+        self.run_hooks.push(hooks);
+    }
+
+    /// Remove every RunHooks that was added, returning them.
+    pub fn take_run_hooks(&mut self) -> Vec<Box<dyn RunHooks<T>>> {
+        std::mem::take(&mut self.run_hooks)
+    }
+
+    // Call each RunHooks, they're taken out while being called so they can't see themselves.
+    pub(crate) fn call_run_hooks(&mut self, mut call: impl FnMut(&mut dyn RunHooks<T>)) {
+        let mut hooks = std::mem::take(&mut self.run_hooks);
+        for hook in hooks.iter_mut() {
+            call(hook.as_mut());
+        }
+        hooks.append(&mut self.run_hooks);
+        self.run_hooks = hooks;
+    }
+
+    /// Call the given EvalObserver around every expression evaluated in this Environment,
+    /// replacing any previous observer.
+    pub fn set_eval_observer(&mut self, observer: Box<dyn EvalObserver<T>>) {
+        self.eval_observer = Some(observer);
+    }
+
+    /// Stop observing evaluation, returning the EvalObserver that was set.
+    pub fn take_eval_observer(&mut self) -> Option<Box<dyn EvalObserver<T>>> {
+        self.eval_observer.take()
+    }
+
+    /// Check if an EvalObserver is set.
+    pub(crate) fn is_observed(&self) -> bool {
+        self.eval_observer.is_some()
+    }
+
+    // Call the EvalObserver, it's taken out while being called so it can be given this Environment.
+    pub(crate) fn observe<R>(
+        &mut self,
+        call: impl FnOnce(&mut dyn EvalObserver<T>, &Environment<T>) -> R,
+    ) -> Option<R> {
+        let mut observer = self.eval_observer.take()?;
+        let result = call(observer.as_mut(), self);
+        if self.eval_observer.is_none() {
+            self.eval_observer = Some(observer);
+        }
+        Some(result)
+    }
+
+    /// Use the given ModuleResolver to load the modules scripts import, replacing any previous resolver.
+    pub fn set_module_resolver(&mut self, resolver: Rc<dyn ModuleResolver>) {
+        self.module_resolver = Some(resolver);
+    }
+
+    pub(crate) fn module_resolver(&self) -> Option<Rc<dyn ModuleResolver>> {
+        self.module_resolver.clone()
+    }
+
+    /// Use the given TypeChecker to check the tags of vals and Lambda parameters, replacing any previous checker.
+    pub fn set_type_checker(&mut self, checker: Rc<dyn TypeChecker<T>>) {
+        self.type_checker = checker;
+    }
+
+    // Check a value bound to a tagged name, tags are passed to the TypeChecker as Strings.
+    pub(crate) fn check_tag(
+        &self,
+        name: &str,
+        tag: &str,
+        value: &WanderValue<T>,
+    ) -> Result<(), WanderError> {
+        if self
+            .type_checker
+            .check(value.clone(), WanderValue::String(tag.to_owned()))?
+        {
+            Ok(())
+        } else {
+            Err(WanderError::new(format!(
+                "`{name}` requires a value tagged {tag}, found {value}."
+            )))
+        }
+    }
+
+    /// Only allow calls to HostFunctions whose capabilities have all been granted.
+    /// None, the default, allows every HostFunction to be called.
+    pub fn set_granted_capabilities(&mut self, capabilities: Option<HashSet<String>>) {
+        self.granted_capabilities = capabilities;
+    }
+
+    /// Grant a capability, only needed after restricting capabilities with `set_granted_capabilities`.
+    pub fn grant_capability(&mut self, capability: &str) {
+        if let Some(granted) = &mut self.granted_capabilities {
+            granted.insert(capability.to_owned());
+        }
+    }
+
+    // Check that every capability the HostFunction requires has been granted.
+    pub(crate) fn check_capabilities(&self, binding: &HostFunctionBinding) -> Result<(), WanderError> {
+        let Some(granted) = &self.granted_capabilities else {
+            return Ok(());
+        };
+        match binding
+            .capabilities
+            .iter()
+            .find(|capability| !granted.contains(*capability))
+        {
+            Some(capability) => Err(WanderError {
+                message: format!(
+                    "Permission denied, `{}` requires the {capability} capability.",
+                    binding.name
+                ),
+                span: None,
+                code: Some("PermissionDenied".to_owned()),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Only allow calls to pure HostFunctions, for running deterministic rules or values that will be memoized.
+    /// `pure ... end` expressions do the same for their body.
+    pub fn set_pure(&mut self, pure: bool) {
+        self.pure = pure;
+    }
+
+    /// If only pure HostFunctions can be called.
+    pub fn is_pure(&self) -> bool {
+        self.pure
+    }
+
+    /// Set what Int arithmetic in the preludes does when a result overflows, an error by default.
+    pub fn set_int_overflow(&mut self, overflow: IntOverflow) {
+        self.int_overflow = overflow;
+    }
+
+    /// What Int arithmetic in the preludes does when a result overflows.
+    pub fn int_overflow(&self) -> IntOverflow {
+        self.int_overflow
+    }
+
+    /// Add a Lambda, or a partially applied HostFunction, to be called each time the named event is emitted.
+    pub fn on_event(&mut self, name: &str, handler: WanderValue<T>) {
+        self.event_handlers
+            .entry(name.to_owned())
+            .or_default()
+            .push(handler);
+    }
+
+    /// Remove every handler of the named event, returning them.
+    pub fn remove_event_handlers(&mut self, name: &str) -> Vec<WanderValue<T>> {
+        self.event_handlers.remove(name).unwrap_or_default()
+    }
+
+    /// Call each handler of the named event with the arguments, or with nothing if there are none,
+    /// in the order they were added, and return their results.
+    /// Every handler is called even if some fail, their errors are combined.
+    pub fn emit(
+        &mut self,
+        name: &str,
+        arguments: &[WanderValue<T>],
+    ) -> Result<Vec<WanderValue<T>>, WanderError> {
+        let handlers = self.event_handlers.get(name).cloned().unwrap_or_default();
+        let arguments = match arguments {
+            [] => &[WanderValue::Nothing],
+            arguments => arguments,
+        };
+        let mut results = vec![];
+        let mut errors = vec![];
+        for handler in handlers {
+            match call_function(&handler, arguments, self) {
+                Ok(result) => results.push(result),
+                Err(err) => errors.push(err),
+            }
+        }
+        if errors.is_empty() {
+            Ok(results)
+        } else {
+            Err(combine_errors(errors))
+        }
+    }
+
+    // Check that the HostFunction is pure if only pure HostFunctions can be called.
+    fn check_purity(&self, binding: &HostFunctionBinding) -> Result<(), WanderError> {
+        if !self.pure || binding.pure {
+            return Ok(());
+        }
+        Err(WanderError {
+            message: format!(
+                "`{}` is impure and can't be called in a pure expression.",
+                binding.name
+            ),
+            span: None,
+            code: Some("Impure".to_owned()),
+        })
+    }
+
+    /// Start a dry run, effectful HostFunctions are skipped and the value returned by `stub` is used instead.
+    pub fn start_dry_run(&mut self, stub: DryRunStub<T>) {
+        self.dry_run = Some(DryRun {
+            stub,
+            skipped: vec![],
+        });
+    }
+
+    /// End a dry run, returning the calls that were skipped in the order they were made.
+    pub fn stop_dry_run(&mut self) -> Vec<SkippedCall<T>> {
+        self.dry_run
+            .take()
+            .map(|dry_run| dry_run.skipped)
+            .unwrap_or_default()
+    }
+
+    // Call a HostFunction, or skip it if it is effectful and there is a dry run.
+    fn run_host_function(
+        &mut self,
+        function: &Rc<dyn HostFunction<T>>,
+        binding: &HostFunctionBinding,
+        arguments: &[WanderValue<T>],
+    ) -> Result<WanderValue<T>, WanderError> {
+        match &mut self.dry_run {
+            Some(dry_run) if binding.effectful => {
+                dry_run.skipped.push(SkippedCall {
+                    name: binding.name.clone(),
+                    arguments: arguments.to_vec(),
+                });
+                Ok((dry_run.stub)(&binding.name, arguments))
+            }
+            _ => function.run(arguments, self),
+        }
+    }
+
+    /// Run a HostFunction, checking its capabilities if they are restricted and its purity if the Environment is pure,
+    /// skipping it during a dry run if it is effectful, and recording the call if an AuditSink is set.
+    pub(crate) fn call_host_function(
+        &mut self,
+        function: &Rc<dyn HostFunction<T>>,
+        arguments: &[WanderValue<T>],
+    ) -> Result<WanderValue<T>, WanderError> {
+        if self.audit_sink.is_none()
+            && self.granted_capabilities.is_none()
+            && self.dry_run.is_none()
+            && !self.pure
+        {
+            return function.run(arguments, self);
+        }
+        let binding = function.binding();
+        let started = self.audit_sink.is_some().then(Instant::now);
+        let result = self
+            .check_capabilities(&binding)
+            .and_then(|_| self.check_purity(&binding))
+            .and_then(|_| self.run_host_function(function, &binding, arguments));
+        let (Some(sink), Some(started)) = (&mut self.audit_sink, started) else {
+            return result;
+        };
+        let call = HostFunctionCall {
+            name: binding.name,
+            arguments: arguments.iter().map(summarize).collect(),
+            duration: started.elapsed(),
+            result: result.as_ref().map(summarize).map_err(Clone::clone),
+        };
+        sink.record(call);
+        result
     }
 
     /// Read a HostFunction.
@@ -136,6 +574,171 @@ impl<T: HostType> Environment<T> {
         self.token_transformers.borrow().get(name).cloned()
     }
 
+    /// Add a LiteralParser for custom literals starting with the given prefix.
+    /// Prefixes start with `@` or `#`, for example `@` for `@2024-01-01` or `#id:` for `#id:foo`.
+    pub fn bind_literal_parser(&mut self, prefix: String, parser: Rc<LiteralParser<T>>) {
+        self.literal_parsers.borrow_mut().insert(prefix, parser);
+    }
+
+    /// Read the LiteralParser with the longest prefix matching a custom literal,
+    /// along with the literal's text after that prefix.
+    pub fn read_literal_parser<'a>(&self, literal: &'a str) -> Option<(Rc<LiteralParser<T>>, &'a str)> {
+        self.literal_parsers
+            .borrow()
+            .iter()
+            .filter(|(prefix, _)| literal.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(prefix, parser)| (parser.clone(), &literal[prefix.len()..]))
+    }
+
+    /// Store a value of application state that HostFunctions can read and update while a script runs.
+    /// There is one slot for each type, setting a context replaces any previous value of the same type.
+    pub fn set_context<C: Any>(&mut self, context: C) {
+        self.contexts.insert(TypeId::of::<C>(), Box::new(context));
+    }
+
+    /// Read the context of the given type, if one has been set.
+    pub fn context<C: Any>(&self) -> Option<&C> {
+        self.contexts
+            .get(&TypeId::of::<C>())
+            .and_then(|context| context.downcast_ref())
+    }
+
+    /// Get a mutable reference to the context of the given type, if one has been set.
+    pub fn context_mut<C: Any>(&mut self) -> Option<&mut C> {
+        self.contexts
+            .get_mut(&TypeId::of::<C>())
+            .and_then(|context| context.downcast_mut())
+    }
+
+    /// Remove and return the context of the given type.
+    pub fn take_context<C: Any>(&mut self) -> Option<C> {
+        self.contexts
+            .remove(&TypeId::of::<C>())
+            .and_then(|context| context.downcast().ok())
+            .map(|context| *context)
+    }
+
+    /// Record a warning found while preparing a script, like a local value shadowing an imported name.
+    pub fn warn(&self, warning: WanderError) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    /// Remove and return all recorded warnings.
+    /// Warnings have spans into the script that caused them, use `WanderError::locate` to add lines and columns.
+    pub fn take_warnings(&self) -> Vec<WanderError> {
+        self.warnings.take()
+    }
+
+    /// Start checking evaluation against the given limits, returning the state of the previous run
+    /// so it can be restored when a script is run from inside another one.
+    pub(crate) fn set_run_config(&mut self, config: RunConfig) -> RunState {
+        // Instant isn't available on every target, so only read the clock when there is a time limit.
+        let started = config.max_time.map(|_| Instant::now());
+        std::mem::replace(
+            &mut self.run_state,
+            RunState {
+                config,
+                started,
+                ..RunState::default()
+            },
+        )
+    }
+
+    /// Restore the state returned by `set_run_config`, returning the stats of the run that finished.
+    pub(crate) fn restore_run_config(&mut self, state: RunState) -> RunStats {
+        let finished = std::mem::replace(&mut self.run_state, state);
+        RunStats {
+            steps: finished.steps,
+            max_depth: finished.peak_depth,
+        }
+    }
+
+    /// Count a step of evaluation at the given position, failing if it goes past a limit.
+    /// Every call must be matched by a call to `exit`, even if it fails.
+    pub(crate) fn enter(&mut self, position: usize) -> Result<(), WanderError> {
+        let state = &mut self.run_state;
+        state.steps += 1;
+        state.depth += 1;
+        state.peak_depth = state.peak_depth.max(state.depth);
+        state.position = position;
+        if let Some(max_steps) = state.config.max_steps {
+            if state.steps > max_steps {
+                return Err(WanderError::at(steps_exceeded(max_steps), position));
+            }
+        }
+        if let Some(max_depth) = state.config.max_depth {
+            if state.depth > max_depth {
+                return Err(WanderError::at(
+                    format!("Script exceeded the maximum evaluation depth of {max_depth}"),
+                    position,
+                ));
+            }
+        }
+        if state
+            .config
+            .cancel
+            .as_ref()
+            .is_some_and(CancelFlag::is_cancelled)
+        {
+            return Err(WanderError::at(CANCELLED.to_owned(), position));
+        }
+        if let (Some(max_time), Some(started)) = (state.config.max_time, state.started) {
+            if started.elapsed() > max_time {
+                return Err(WanderError::at(
+                    format!("Script exceeded the time limit of {max_time:?}"),
+                    position,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get what's left of the limits of the running script, for HostFunctions that do long-running work.
+    /// Outside of a run there are no limits.
+    pub fn execution_context(&self) -> ExecutionContext {
+        let state = &self.run_state;
+        ExecutionContext {
+            remaining_steps: state
+                .config
+                .max_steps
+                .map(|max_steps| max_steps.saturating_sub(state.steps)),
+            deadline: state
+                .config
+                .max_time
+                .zip(state.started)
+                .map(|(max_time, started)| started + max_time),
+            cancelled: state
+                .config
+                .cancel
+                .as_ref()
+                .is_some_and(CancelFlag::is_cancelled),
+        }
+    }
+
+    /// The CancelFlag of the running script, for HostFunctions that wait and need to stop when it's set.
+    pub fn cancel_flag(&self) -> Option<CancelFlag> {
+        self.run_state.config.cancel.clone()
+    }
+
+    /// Count work done by a HostFunction as evaluation steps, failing if it goes past the step limit.
+    /// The error is located at the last expression evaluated, usually the call of the HostFunction.
+    pub fn charge_steps(&mut self, steps: usize) -> Result<(), WanderError> {
+        let state = &mut self.run_state;
+        state.steps = state.steps.saturating_add(steps);
+        match state.config.max_steps {
+            Some(max_steps) if state.steps > max_steps => {
+                Err(WanderError::at(steps_exceeded(max_steps), state.position))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Leave a step of evaluation started with `enter`.
+    pub(crate) fn exit(&mut self) {
+        self.run_state.depth -= 1;
+    }
+
     /// Get a collection of all names.
     pub fn bound_names(&self) -> HashSet<String> {
         let mut names = HashSet::new();
@@ -150,7 +753,181 @@ impl<T: HostType> Environment<T> {
         names
     }
 
+    /// Every value bound in a Scope, from the outermost Scope to the innermost,
+    /// so binding them in order into another Environment shadows names the same way.
+    /// The Lambdas HostFunctions are bound as are left out, an Environment with the same HostFunctions already has them.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn bound_values(&self) -> Vec<(String, WanderValue<T>)> {
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.iter())
+            .filter(|(name, value)| match value {
+                WanderValue::Lambda(_, _, _, body) => {
+                    crate::interpreter::host_function_name(body).as_ref() != Some(*name)
+                }
+                _ => true,
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// The execution context of the running script, for running part of it in an Environment on another thread.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn fork_context(&self) -> ForkedContext {
+        let state = &self.run_state;
+        let context = self.execution_context();
+        ForkedContext {
+            granted_capabilities: self.granted_capabilities.clone(),
+            pure: self.pure,
+            int_overflow: self.int_overflow,
+            config: RunConfig {
+                max_steps: context.remaining_steps,
+                max_depth: state
+                    .config
+                    .max_depth
+                    .map(|max_depth| max_depth.saturating_sub(state.depth)),
+                max_time: context
+                    .deadline
+                    .map(|deadline| deadline.saturating_duration_since(Instant::now())),
+                cancel: state.config.cancel.clone(),
+            },
+            audited: self.audit_sink.is_some(),
+            dry_run: self.dry_run.is_some(),
+        }
+    }
+
+    /// Run with the execution context of another Environment, returning the state to restore
+    /// with `restore_run_config` once finished. The AuditSink and dry run are set up by the caller,
+    /// since they stay with the Environment the context was forked from.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn join_context(&mut self, context: &ForkedContext) -> RunState {
+        self.granted_capabilities = context.granted_capabilities.clone();
+        self.pure = context.pure;
+        self.int_overflow = context.int_overflow;
+        self.set_run_config(context.config.clone())
+    }
+
+    /// Record a HostFunction call made in a forked context with this Environment's AuditSink.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn record_call(&mut self, call: HostFunctionCall) {
+        if let Some(sink) = &mut self.audit_sink {
+            sink.record(call);
+        }
+    }
+
+    /// Skip an effectful HostFunction call made in a forked context as part of this Environment's dry run.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn skip_call(&mut self, name: String, arguments: Vec<WanderValue<T>>) -> WanderValue<T> {
+        match &mut self.dry_run {
+            Some(dry_run) => {
+                let value = (dry_run.stub)(&name, &arguments);
+                dry_run.skipped.push(SkippedCall { name, arguments });
+                value
+            }
+            None => WanderValue::Nothing,
+        }
+    }
+
+    /// Get a HostFunctionBinding for every bound HostFunction and every value bound in a Scope.
+    /// Bound values only have the parameters and result tags of Lambdas, and no documentation.
+    /// Only Lambdas created in a pure expression are marked pure, since any other Lambda
+    /// could call an impure HostFunction.
     pub fn environment(&self) -> Vec<HostFunctionBinding> {
-        todo!()
+        let host_functions = self.host_functions.borrow();
+        let mut environment: Vec<HostFunctionBinding> = host_functions
+            .values()
+            .map(|function| function.binding())
+            .collect();
+        let mut names = HashSet::new();
+        for scope in self.scopes.iter().rev() {
+            for (name, value) in scope.iter() {
+                if host_functions.contains_key(name) || !names.insert(name.clone()) {
+                    continue;
+                }
+                let mut parameters = vec![];
+                let mut result = None;
+                let mut pure = false;
+                if let WanderValue::Lambda(parameter, tag, output, body) = value {
+                    parameters.push((parameter.clone(), tag.clone()));
+                    result = output.clone();
+                    let mut body = body.as_ref();
+                    while let Location(Element::Lambda(parameter, tag, output, inner), _) = body {
+                        parameters.push((parameter.clone(), tag.clone()));
+                        result = output.clone();
+                        body = inner.as_ref();
+                    }
+                    pure = matches!(body, Location(Element::Pure(_), _));
+                }
+                environment.push(HostFunctionBinding {
+                    name: name.clone(),
+                    parameters,
+                    result,
+                    pure,
+                    ..Default::default()
+                });
+            }
+        }
+        environment.sort_by(|left, right| left.name.cmp(&right.name));
+        environment
+    }
+
+    /// Capture every Scope and the bound HostFunctions so they can be restored later.
+    /// The snapshot shares them with this Environment until one of its Scopes changes.
+    pub fn snapshot(&self) -> BindingsSnapshot<T> {
+        BindingsSnapshot {
+            scopes: self.scopes.clone(),
+            host_functions: self.host_functions.borrow().clone(),
+        }
+    }
+
+    /// Return these Bindings to the state captured by `snapshot`,
+    /// dropping anything bound since then.
+    pub fn restore(&mut self, snapshot: BindingsSnapshot<T>) {
+        self.scopes = snapshot.scopes;
+        *self.host_functions.borrow_mut() = snapshot.host_functions;
+    }
+}
+
+/// The Scopes and HostFunctions of an Environment, created by `Environment::snapshot`.
+#[derive(Clone)]
+pub struct BindingsSnapshot<T: HostType> {
+    scopes: Vec<Rc<HashMap<String, WanderValue<T>>>>,
+    host_functions: Rc<HashMap<String, Rc<dyn HostFunction<T>>>>,
+}
+
+/// Restores the HostFunction replaced by `Environment::override_host_function` when dropped.
+/// Derefs to the Environment so scripts can be run while the mock is in place.
+pub struct HostFunctionOverride<'a, T: HostType> {
+    environment: &'a mut Environment<T>,
+    name: String,
+    original: Option<Rc<dyn HostFunction<T>>>,
+}
+
+impl<T: HostType> Deref for HostFunctionOverride<'_, T> {
+    type Target = Environment<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.environment
+    }
+}
+
+impl<T: HostType> DerefMut for HostFunctionOverride<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.environment
+    }
+}
+
+impl<T: HostType> Drop for HostFunctionOverride<'_, T> {
+    fn drop(&mut self) {
+        match self.original.take() {
+            Some(original) => self
+                .environment
+                .bind_host_function_as(self.name.clone(), original),
+            None => {
+                Rc::make_mut(&mut self.environment.host_functions.borrow_mut())
+                    .remove(&self.name);
+                self.environment.unbind(&self.name);
+            }
+        }
     }
 }
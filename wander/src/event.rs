@@ -0,0 +1,58 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Contains `Event.on` and `Event.emit`, so scripts can add handlers for events the host emits
+//! with `Environment::emit`.
+
+use crate::{
+    environment::Environment,
+    preludes::{add_table_functions, TableFunction},
+    HostType, WanderValue,
+};
+
+// Events are named with a String or a Keyword, `:click` names the same event as "click".
+fn event_name<T: HostType>(value: &WanderValue<T>) -> Option<String> {
+    match value {
+        WanderValue::String(name) => Some(name.clone()),
+        WanderValue::Keyword(keyword) => Some(keyword.name().to_owned()),
+        _ => None,
+    }
+}
+
+/// Bind `Event.on` and `Event.emit`.
+pub fn add_event_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 2] = [
+        TableFunction {
+            name: "Event.on",
+            doc_string: "Add a Lambda to be called each time an event is emitted.",
+            parameters: &["name", "handler"],
+            requires: "a String or Keyword and a Lambda",
+            pure: false,
+            run: |arguments, bindings| match arguments {
+                [name, handler @ (WanderValue::Lambda(..) | WanderValue::PartialApplication(_))] => {
+                    bindings.on_event(&event_name(name)?, handler.clone());
+                    Some(Ok(WanderValue::Nothing))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Event.emit",
+            doc_string: "Call the handlers of an event with a List of arguments, \
+                and return a List of their results.",
+            parameters: &["name", "arguments"],
+            requires: "a String or Keyword and a List",
+            pure: false,
+            run: |arguments, bindings| match arguments {
+                [name, WanderValue::List(arguments)] => Some(
+                    bindings
+                        .emit(&event_name(name)?, arguments)
+                        .map(WanderValue::List),
+                ),
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
@@ -0,0 +1,728 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Pretty printing for values and scripts, see `format_value` and `fmt`.
+
+use std::fmt::{Debug, Display};
+
+use crate::{
+    lexer::{tokenize, Token},
+    ordered_set,
+    parser::{Element, Pattern},
+    write_char, write_float, Location, WanderValue,
+};
+
+/// Controls how values and scripts are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// The number of spaces each level of nesting is indented by.
+    pub indent: usize,
+    /// The width lines are kept under when possible.
+    pub width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: 2,
+            width: 80,
+        }
+    }
+}
+
+/// Write a value the way it's displayed, but with any Lists, Tuples, Sets, and Records
+/// that don't fit within the width broken over multiple lines, one value per line.
+pub fn format_value<T: Clone + Display + PartialEq + Eq + Debug>(
+    value: &WanderValue<T>,
+    options: FormatOptions,
+) -> String {
+    let mut printer = Printer::new(options);
+    printer.value(value, 0);
+    printer.out
+}
+
+/// Format a script with the default FormatOptions, see `fmt_with_options`.
+pub fn fmt(source: &str) -> String {
+    fmt_with_options(source, FormatOptions::default())
+}
+
+/// Format a script, normalizing the spacing between tokens and breaking
+/// brackets and `let`, `if`, `match`, `do`, `try`, `ensure`, and `pure` blocks
+/// that don't fit within the width over multiple lines.
+/// Each top-level statement is put on its own line, and comments and blank lines
+/// between statements are kept.
+/// Scripts that can't be tokenized or have unbalanced brackets or blocks are returned unchanged.
+pub fn fmt_with_options(source: &str, options: FormatOptions) -> String {
+    try_fmt(source, options).unwrap_or_else(|| source.to_owned())
+}
+
+/// Format a script like `fmt_with_options`, returning None for scripts that can't be
+/// tokenized or have unbalanced brackets or blocks instead of returning them unchanged.
+pub fn try_fmt(source: &str, options: FormatOptions) -> Option<String> {
+    let tokens = tokenize(source).ok()?;
+    let pieces = pieces(source, tokens);
+    let mut index = 0;
+    let nodes = match parse_nodes(&pieces, &mut index, &[]) {
+        Some(nodes) if index == pieces.len() => nodes,
+        _ => return None,
+    };
+    let mut printer = Printer::new(options);
+    for (i, item) in split_statements(&nodes).into_iter().enumerate() {
+        if i > 0 {
+            printer.newline(0);
+            if first_atom(&item[0]).blank_before {
+                printer.newline(0);
+            }
+        }
+        printer.line(item, 0);
+    }
+    if !printer.out.is_empty() {
+        printer.out.push('\n');
+    }
+    Some(printer.out)
+}
+
+// A token with the text it was written with.
+struct Piece {
+    token: Token,
+    atom: Atom,
+}
+
+#[derive(Clone)]
+struct Atom {
+    text: String,
+    space_before: bool,
+    newline_before: bool,
+    blank_before: bool,
+}
+
+impl Atom {
+    fn is_line_comment(&self) -> bool {
+        self.text.starts_with("--")
+    }
+}
+
+enum Node {
+    Atom(Atom),
+    // A bracketed group, its open Atom includes a leading `'` or `#`.
+    Group(Atom, Vec<Node>, String),
+    // A block, a list of keywords with the nodes that follow each, and if it's closed with `end`.
+    Block(Vec<(Atom, Vec<Node>)>, bool),
+}
+
+// Pair each token with its text, and record the whitespace in front of it.
+fn pieces(source: &str, tokens: Vec<Location<Token>>) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut space = String::new();
+    for (i, Location(token, start)) in tokens.iter().enumerate() {
+        let end = tokens.get(i + 1).map_or(source.len(), |next| next.1);
+        let text = &source[*start..end];
+        if let Token::WS(_) = token {
+            space.push_str(text);
+            continue;
+        }
+        pieces.push(Piece {
+            token: token.clone(),
+            atom: Atom {
+                text: text.trim_end().to_owned(),
+                space_before: (!space.is_empty() || text.starts_with("--"))
+                    && *token != Token::Semicolon,
+                newline_before: space.contains('\n'),
+                blank_before: space.matches('\n').count() > 1,
+            },
+        });
+        space = if text.ends_with('\n') {
+            "\n".to_owned()
+        } else {
+            String::new()
+        };
+    }
+    pieces
+}
+
+fn closer(token: &Token) -> Option<(Token, &'static str)> {
+    match token {
+        Token::OpenParen => Some((Token::CloseParen, ")")),
+        Token::OpenSquare => Some((Token::CloseSquare, "]")),
+        Token::OpenBrace => Some((Token::CloseBrace, "}")),
+        _ => None,
+    }
+}
+
+// The keywords that can continue a block started by a token.
+fn block_keywords(token: &Token) -> Option<&'static [Token]> {
+    match token {
+        Token::Let => Some(&[Token::In]),
+        Token::If => Some(&[Token::Then, Token::Else]),
+        Token::Match => Some(&[Token::With]),
+        Token::Try => Some(&[Token::Handle]),
+        Token::Ensure => Some(&[Token::Finally]),
+        Token::Do | Token::Pure => Some(&[]),
+        _ => None,
+    }
+}
+
+// Parse nodes until one of the stop tokens, which isn't consumed, or the end of the pieces.
+fn parse_nodes(pieces: &[Piece], index: &mut usize, stop: &[Token]) -> Option<Vec<Node>> {
+    let mut nodes = vec![];
+    while let Some(piece) = pieces.get(*index) {
+        if stop.contains(&piece.token) {
+            break;
+        }
+        *index += 1;
+        let mut open = piece.atom.clone();
+        let mut token = &piece.token;
+        if matches!(token, Token::SingleQuote | Token::Hash) {
+            if let Some(next) = pieces.get(*index) {
+                if next.token == Token::OpenParen && !next.atom.space_before {
+                    open.text.push('(');
+                    token = &next.token;
+                    *index += 1;
+                }
+            }
+        }
+        if let Some((close, close_text)) = closer(token) {
+            let children = parse_nodes(pieces, index, std::slice::from_ref(&close))?;
+            pieces.get(*index).filter(|piece| piece.token == close)?;
+            *index += 1;
+            nodes.push(Node::Group(open, children, close_text.to_owned()));
+        } else if let Some(keywords) = block_keywords(token) {
+            nodes.push(parse_block(pieces, index, open, token, keywords)?);
+        } else if matches!(
+            token,
+            Token::CloseParen
+                | Token::CloseSquare
+                | Token::CloseBrace
+                | Token::End
+                | Token::In
+                | Token::Then
+                | Token::Else
+                | Token::With
+                | Token::Handle
+                | Token::Finally
+        ) {
+            return None;
+        } else {
+            nodes.push(Node::Atom(open));
+        }
+    }
+    Some(nodes)
+}
+
+fn parse_block(
+    pieces: &[Piece],
+    index: &mut usize,
+    keyword: Atom,
+    token: &Token,
+    keywords: &[Token],
+) -> Option<Node> {
+    let mut stop = keywords.to_vec();
+    stop.push(Token::End);
+    // A let without `in` runs to the end of its statement.
+    if *token == Token::Let {
+        stop.push(Token::Semicolon);
+    }
+    let mut sections = vec![(keyword, parse_nodes(pieces, index, &stop)?)];
+    loop {
+        match pieces.get(*index) {
+            Some(piece) if piece.token == Token::End => {
+                *index += 1;
+                return Some(Node::Block(sections, true));
+            }
+            Some(piece) if keywords.contains(&piece.token) => {
+                *index += 1;
+                let mut keyword = piece.atom.clone();
+                // `else if` continues the same block, sharing its `end`.
+                if piece.token == Token::Else {
+                    if let Some(next) = pieces.get(*index).filter(|next| next.token == Token::If) {
+                        keyword.text.push(' ');
+                        keyword.text.push_str(&next.atom.text);
+                        *index += 1;
+                    }
+                }
+                let stop: Vec<Token> = stop
+                    .iter()
+                    .filter(|token| **token != Token::Semicolon)
+                    .cloned()
+                    .collect();
+                sections.push((keyword, parse_nodes(pieces, index, &stop)?));
+            }
+            _ if *token == Token::Let && sections.len() == 1 => {
+                return Some(Node::Block(sections, false))
+            }
+            _ => return None,
+        }
+    }
+}
+
+fn first_atom(node: &Node) -> &Atom {
+    match node {
+        Node::Atom(atom) | Node::Group(atom, _, _) => atom,
+        Node::Block(sections, _) => &sections[0].0,
+    }
+}
+
+fn is_atom(node: &Node, text: &str) -> bool {
+    matches!(node, Node::Atom(atom) if atom.text == text)
+}
+
+fn is_comment(node: &Node) -> bool {
+    matches!(node, Node::Atom(atom) if atom.text.starts_with("--") || atom.text.starts_with("{-"))
+}
+
+// Split nodes into the pieces ending with each `;`, with comments on their own lines kept separate.
+fn split_statements(nodes: &[Node]) -> Vec<&[Node]> {
+    let mut items = vec![];
+    let mut start = 0;
+    for (i, node) in nodes.iter().enumerate() {
+        let own_line = is_comment(node) && first_atom(node).newline_before;
+        if own_line && i > start {
+            items.push(&nodes[start..i]);
+            start = i;
+        }
+        // A comment on the same line as a `;` stays with its statement.
+        let trailing_comment = nodes
+            .get(i + 1)
+            .is_some_and(|next| is_comment(next) && !first_atom(next).newline_before);
+        if (is_atom(node, ";") && !trailing_comment)
+            || own_line
+            || (i > start && is_atom(&nodes[i - 1], ";") && is_comment(node))
+        {
+            items.push(&nodes[start..=i]);
+            start = i + 1;
+        }
+    }
+    if start < nodes.len() {
+        items.push(&nodes[start..]);
+    }
+    items
+}
+
+// Split nodes into the lines they're written on when they don't fit on one line.
+// Statements are split by `;`, declarations before each name followed by `=`,
+// and match arms and pipes before each `|`.
+// Otherwise values are only split when `spaced` is set, between values separated by spaces.
+fn split_items(nodes: &[Node], spaced: bool) -> Vec<&[Node]> {
+    if nodes.iter().any(|node| is_atom(node, ";")) {
+        return split_statements(nodes);
+    }
+    let mut starts = vec![0];
+    if nodes.iter().any(|node| is_atom(node, "=")) {
+        for (i, node) in nodes.iter().enumerate() {
+            if is_atom(node, "=") {
+                starts.push(declaration_start(nodes, i));
+            }
+        }
+    } else if nodes.iter().skip(1).any(|node| is_atom(node, "|")) {
+        for (i, node) in nodes.iter().enumerate() {
+            if is_atom(node, "|") {
+                starts.push(i);
+            }
+        }
+    } else if spaced {
+        for i in 1..nodes.len() {
+            let atom = first_atom(&nodes[i]);
+            if atom.space_before
+                && !joins(&nodes[i - 1])
+                && !joins(&nodes[i])
+                && (!is_comment(&nodes[i]) || atom.newline_before)
+            {
+                starts.push(i);
+            }
+        }
+    }
+    starts.sort();
+    starts.dedup();
+    starts.retain(|start| *start < nodes.len());
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, start)| &nodes[*start..*starts.get(i + 1).unwrap_or(&nodes.len())])
+        .collect()
+}
+
+// Find where the declaration whose `=` is at `equals` starts, including a tag like `x: Int =`.
+fn declaration_start(nodes: &[Node], equals: usize) -> usize {
+    let mut start = equals.saturating_sub(1);
+    if start >= 2 && is_atom(&nodes[start - 1], ":") {
+        start -= 2;
+    } else if start >= 1 && matches!(&nodes[start], Node::Atom(atom) if atom.text.starts_with(':'))
+    {
+        start -= 1;
+    }
+    start
+}
+
+// Operators keep the values on either side of them on the same line.
+fn joins(node: &Node) -> bool {
+    matches!(node, Node::Atom(atom) if matches!(
+        atom.text.as_str(),
+        "=" | "->" | "\\" | ":" | "::" | "&&" | "||" | "==" | "!=" | "+" | "++" | "-" | "/" | "*"
+            | "<" | ">" | "<=" | ">=" | "as" | "use" | "val" | "import" | "from" | "fun"
+    ))
+}
+
+struct Printer {
+    options: FormatOptions,
+    out: String,
+    column: usize,
+}
+
+impl Printer {
+    fn new(options: FormatOptions) -> Printer {
+        Printer {
+            options,
+            out: String::new(),
+            column: 0,
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.column += text.chars().count();
+    }
+
+    fn newline(&mut self, depth: usize) {
+        while self.out.ends_with(' ') {
+            self.out.pop();
+        }
+        self.out.push('\n');
+        self.column = 0;
+        self.push(&" ".repeat(depth * self.options.indent));
+    }
+
+    fn fits(&self, text: &str) -> bool {
+        self.column + text.chars().count() <= self.options.width
+    }
+
+    fn value<T: Clone + Display + PartialEq + Eq + Debug>(
+        &mut self,
+        value: &WanderValue<T>,
+        depth: usize,
+    ) {
+        let flat = value.to_string();
+        if self.fits(&flat) {
+            return self.push(&flat);
+        }
+        match value {
+            WanderValue::List(values) => self.values("[", values.iter(), "]", depth),
+            WanderValue::Tuple(values) => self.values("'(", values.iter(), ")", depth),
+            WanderValue::Set(values) => {
+                self.values("#(", ordered_set(values).into_iter(), ")", depth)
+            }
+            WanderValue::Record(fields) if !fields.is_empty() => {
+                self.push("{");
+                for (name, value) in fields {
+                    self.newline(depth + 1);
+                    self.push(&format!("{name} = "));
+                    self.value(value, depth + 1);
+                }
+                self.newline(depth);
+                self.push("}");
+            }
+            _ => self.push(&flat),
+        }
+    }
+
+    fn values<'a, T: Clone + Display + PartialEq + Eq + Debug + 'a>(
+        &mut self,
+        open: &str,
+        values: impl Iterator<Item = &'a WanderValue<T>>,
+        close: &str,
+        depth: usize,
+    ) {
+        self.push(open);
+        for value in values {
+            self.newline(depth + 1);
+            self.value(value, depth + 1);
+        }
+        self.newline(depth);
+        self.push(close);
+    }
+
+    // Write nodes on the current line, breaking any groups or blocks that don't fit.
+    fn line(&mut self, nodes: &[Node], depth: usize) {
+        let mut after_comment = false;
+        for (i, node) in nodes.iter().enumerate() {
+            if after_comment {
+                self.newline(depth);
+            } else if i > 0 && first_atom(node).space_before {
+                self.push(" ");
+            }
+            match flat(node) {
+                Some(flat) if self.fits(&flat) || matches!(node, Node::Atom(_)) => self.push(&flat),
+                _ => self.broken(node, depth),
+            }
+            after_comment = matches!(node, Node::Atom(atom) if atom.is_line_comment());
+        }
+    }
+
+    // Write nodes on their own lines, one item per line.
+    fn lines(&mut self, items: Vec<&[Node]>, depth: usize) {
+        for item in items {
+            self.newline(depth);
+            self.line(item, depth);
+        }
+    }
+
+    fn broken(&mut self, node: &Node, depth: usize) {
+        match node {
+            Node::Atom(atom) => self.push(&atom.text),
+            Node::Group(open, children, close) => {
+                self.push(&open.text);
+                let items = split_items(children, true);
+                if open.text == "(" {
+                    // Applications keep the function on the first line, with each argument below it.
+                    let mut items = items.into_iter();
+                    if let Some(first) = items.next() {
+                        self.line(first, depth + 1);
+                    }
+                    self.lines(items.collect(), depth + 1);
+                    if children.last().is_some_and(
+                        |node| matches!(node, Node::Atom(atom) if atom.is_line_comment()),
+                    ) {
+                        self.newline(depth);
+                    }
+                } else {
+                    self.lines(items, depth + 1);
+                    self.newline(depth);
+                }
+                self.push(close);
+            }
+            Node::Block(sections, end) => {
+                let mut inline = false;
+                for (i, (keyword, content)) in sections.iter().enumerate() {
+                    if i > 0 && !inline {
+                        self.newline(depth);
+                    } else if i > 0 {
+                        self.push(" ");
+                    }
+                    self.push(&keyword.text);
+                    inline = false;
+                    if content.is_empty() {
+                        continue;
+                    }
+                    // The condition of an `if` and the value of a `match` stay on the keyword's line.
+                    let header = matches!(keyword.text.as_str(), "if" | "else if" | "match");
+                    match content.iter().map(flat).collect::<Option<Vec<_>>>() {
+                        Some(_) if header => {
+                            self.push(" ");
+                            self.line(content, depth + 1);
+                            inline = i + 1 < sections.len();
+                        }
+                        _ => self.lines(split_items(content, false), depth + 1),
+                    }
+                }
+                if *end {
+                    self.newline(depth);
+                    self.push("end");
+                }
+            }
+        }
+    }
+}
+
+// Write a node on a single line, or None if it has a line comment.
+fn flat(node: &Node) -> Option<String> {
+    match node {
+        Node::Atom(atom) if atom.is_line_comment() => None,
+        Node::Atom(atom) => Some(atom.text.clone()),
+        Node::Group(open, children, close) => {
+            Some(format!("{}{}{}", open.text, flat_nodes(children)?, close))
+        }
+        Node::Block(sections, end) => {
+            let mut result = String::new();
+            for (i, (keyword, content)) in sections.iter().enumerate() {
+                if i > 0 {
+                    result.push(' ');
+                }
+                result.push_str(&keyword.text);
+                if !content.is_empty() {
+                    result.push(' ');
+                    result.push_str(&flat_nodes(content)?);
+                }
+            }
+            if *end {
+                result.push_str(" end");
+            }
+            Some(result)
+        }
+    }
+}
+
+fn flat_nodes(nodes: &[Node]) -> Option<String> {
+    let mut result = String::new();
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 && first_atom(node).space_before {
+            result.push(' ');
+        }
+        result.push_str(&flat(node)?);
+    }
+    Some(result)
+}
+
+/// Write an Element the way it would be written in a script, used to display Quotes.
+/// Lambdas and infix operators are wrapped in parentheses so the source parses back to the same value.
+pub(crate) fn element_source(element: &Element) -> String {
+    let items = |elements: &[Location<Element>]| {
+        elements
+            .iter()
+            .map(|element| element_source(&element.0))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let fields = |fields: &indexmap::IndexMap<String, Location<Element>>| {
+        fields
+            .iter()
+            .map(|(name, value)| format!("{name} = {}", element_source(&value.0)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match element {
+        Element::Boolean(value) => value.to_string(),
+        Element::Int(value) => value.to_string(),
+        Element::Float(value) => write_float(value),
+        // Strings are kept escaped the way they were written.
+        Element::String(value) => format!("\"{value}\""),
+        Element::Char(value) => write_char(value),
+        Element::Identifier(value) => value.to_string(),
+        Element::Keyword(value) => value.to_string(),
+        Element::Name(name) | Element::TaggedName(name, _) | Element::HostFunction(name) => {
+            name.clone()
+        }
+        Element::CustomLiteral(literal) => literal.clone(),
+        Element::Nothing => "nothing".to_owned(),
+        Element::Pipe => "|".to_owned(),
+        Element::Quantity(value, unit) => format!("{}<{}>", element_source(&value.0), unit.id()),
+        Element::Let(decls, body) => {
+            let mut result = "let".to_owned();
+            for (name, tag, value) in decls {
+                result += &format!(" {name}{} = {}", tag_source(tag), element_source(&value.0));
+            }
+            format!("{result} in {} end", element_source(&body.0))
+        }
+        Element::Grouping(elements) => match &elements[..] {
+            [element] => element_source(&element.0),
+            elements => format!("({})", items(elements)),
+        },
+        Element::Conditional(condition, then, otherwise) => {
+            let otherwise = match &otherwise.0 {
+                // `else if` shares the `end` of the conditional it continues.
+                Element::Conditional(..) => element_source(&otherwise.0),
+                otherwise => format!("{} end", element_source(otherwise)),
+            };
+            format!(
+                "if {} then {} else {otherwise}",
+                element_source(&condition.0),
+                element_source(&then.0)
+            )
+        }
+        Element::Lambda(..) => {
+            let mut parameters = vec![];
+            let mut body = element;
+            while let Element::Lambda(name, tag, _, inner) = body {
+                parameters.push(format!("{name}{}", tag_source(tag)));
+                body = &inner.0;
+            }
+            format!("(\\{} -> {})", parameters.join(" "), element_source(body))
+        }
+        Element::Tuple(elements) => format!("'({})", items(elements)),
+        Element::List(elements) => format!("[{}]", items(elements)),
+        Element::Set(elements) => format!("#({})", items(elements)),
+        Element::Record(values) => format!("{{{}}}", fields(values)),
+        Element::RecordUpdate(base, values) => {
+            format!("{{{} with {}}}", element_source(&base.0), fields(values))
+        }
+        Element::TailCall(name, arguments) => format!("({name} {})", items(arguments)),
+        Element::Infix(operator, left, right) => format!(
+            "({} {operator} {})",
+            element_source(&left.0),
+            element_source(&right.0)
+        ),
+        Element::Prefix(operator, operand) => format!("{operator}{}", element_source(&operand.0)),
+        Element::FieldAccess(value, field) => format!("{}.{field}", element_source(&value.0)),
+        Element::Match(value, arms) => {
+            let mut result = format!("match {} with", element_source(&value.0));
+            for (pattern, body) in arms {
+                result += &format!(
+                    " | {} -> {}",
+                    pattern_source(pattern),
+                    element_source(&body.0)
+                );
+            }
+            result + " end"
+        }
+        Element::Sequence(elements) => {
+            let statements: Vec<_> = elements
+                .iter()
+                .map(|element| element_source(&element.0))
+                .collect();
+            format!("do {} end", statements.join("; "))
+        }
+        Element::Try(body, name, handler) => format!(
+            "try {} handle {name} -> {} end",
+            element_source(&body.0),
+            element_source(&handler.0)
+        ),
+        Element::TryResult(body) => format!("try {} end", element_source(&body.0)),
+        Element::Ensure(body, cleanup) => format!(
+            "ensure {} finally {} end",
+            element_source(&body.0),
+            element_source(&cleanup.0)
+        ),
+        Element::Pure(body) => format!("pure {} end", element_source(&body.0)),
+        Element::Quote(quoted) => format!("`{}", element_source(&quoted.0)),
+        Element::Unquote(unquoted) => format!("~{}", element_source(&unquoted.0)),
+        Element::Splice(spliced) => format!("~@{}", element_source(&spliced.0)),
+        Element::Use(namespace, alias, body) => {
+            let declaration = match alias {
+                Some(alias) => format!("{namespace} as {alias}"),
+                None => format!("{namespace}.*"),
+            };
+            format!("let use {declaration} in {} end", element_source(&body.0))
+        }
+        Element::Import(namespace, path, body) => {
+            format!("import {namespace} from \"{path}\"; {}", element_source(&body.0))
+        }
+    }
+}
+
+fn tag_source(tag: &Option<String>) -> String {
+    match tag {
+        Some(tag) => format!(": {tag}"),
+        None => String::new(),
+    }
+}
+
+fn pattern_source(pattern: &Pattern) -> String {
+    let patterns = |patterns: &[Pattern]| {
+        patterns
+            .iter()
+            .map(pattern_source)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match pattern {
+        Pattern::Wildcard => "_".to_owned(),
+        Pattern::Name(name) => name.clone(),
+        Pattern::Boolean(value) => value.to_string(),
+        Pattern::Int(value) => value.to_string(),
+        Pattern::Float(value) => write_float(value),
+        Pattern::String(value) => format!("\"{value}\""),
+        Pattern::Char(value) => write_char(value),
+        Pattern::Identifier(value) => value.to_string(),
+        Pattern::Keyword(value) => value.to_string(),
+        Pattern::Nothing => "nothing".to_owned(),
+        Pattern::Tuple(values) => format!("'({})", patterns(values)),
+        Pattern::List(values) => format!("[{}]", patterns(values)),
+        Pattern::Record(values) => format!(
+            "{{{}}}",
+            values
+                .iter()
+                .map(|(name, value)| format!("{name} = {}", pattern_source(value)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+    }
+}
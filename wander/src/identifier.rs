@@ -36,7 +36,7 @@ impl Identifier {
         if validate_identifier(name) {
             Ok(Self(name.to_string()))
         } else {
-            Err(WanderError(format!("Invalid Entity id {}", name)))
+            Err(WanderError::new(format!("Invalid Entity id {}", name)))
         }
     }
 
@@ -2,7 +2,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::collections::{HashMap, HashSet};
+use indexmap::IndexMap;
+use std::collections::HashSet;
 use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
@@ -10,17 +11,21 @@ use serde::{Deserialize, Serialize};
 use crate::environment::Environment;
 
 use crate::identifier::Identifier;
-use crate::parser::Element;
-use crate::translation::express;
-use crate::{HostType, WanderError, WanderValue, Location};
+use crate::keyword::Keyword;
+use crate::parser::{Element, Pattern};
+use crate::translation::{express, replace_unquotes};
+use crate::{ordered_set, HostType, Location, PartialApplication, WanderError, WanderValue};
 
 #[doc(hidden)]
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub enum Expression {
     Boolean(bool),
     Int(i64),
+    Float(f64),
     String(String),
+    Char(char),
     Identifier(Identifier),
+    Keyword(Keyword),
     Name(String),
     TaggedName(String, Box<Location<Expression>>),
     HostFunction(String),
@@ -33,11 +38,25 @@ pub enum Expression {
     Lambda(String, Option<String>, Option<String>, Box<Location<Element>>),
     Tuple(Vec<Location<Expression>>),
     List(Vec<Location<Expression>>),
-    Set(HashSet<Location<Expression>>),
-    Record(HashMap<String, Location<Expression>>),
+    Set(Vec<Location<Expression>>),
+    Record(IndexMap<String, Location<Expression>>),
+    RecordUpdate(Box<Location<Expression>>, IndexMap<String, Location<Expression>>),
     Nothing,
+    TailCall(String, Vec<Location<Expression>>),
+    FieldAccess(Box<Location<Expression>>, String),
+    Match(Box<Location<Expression>>, Vec<(Pattern, Location<Expression>)>),
+    Sequence(Vec<Location<Expression>>),
+    Try(Box<Location<Expression>>, String, Box<Location<Expression>>),
+    TryResult(Box<Location<Expression>>),
+    Ensure(Box<Location<Expression>>, Box<Location<Expression>>),
+    Pure(Box<Location<Expression>>),
+    CustomLiteral(String),
+    Quantity(Box<Location<Expression>>, Identifier),
+    Quote(Box<Location<Element>>, Vec<Location<Expression>>),
 }
 
+impl Eq for Expression {}
+
 impl core::hash::Hash for Expression {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
@@ -47,12 +66,66 @@ impl core::hash::Hash for Expression {
 pub fn eval<T: Clone + Display + PartialEq + Eq + std::fmt::Debug + Serialize>(
     expression: &Location<Expression>,
     environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    let result = match environment.enter(expression.1) {
+        Ok(()) if environment.is_observed() => observe(expression, environment),
+        Ok(()) => eval_expression(expression, environment),
+        Err(err) => Err(err),
+    };
+    environment.exit();
+    result
+}
+
+/// Watches each expression as it's evaluated, for building debuggers and profilers,
+/// see `Environment::set_eval_observer`.
+pub trait EvalObserver<T: HostType> {
+    /// Called before an expression is evaluated, returning an error stops evaluation with that error.
+    fn before_eval(
+        &mut self,
+        _expression: &Location<Expression>,
+        _environment: &Environment<T>,
+    ) -> Result<(), WanderError> {
+        Ok(())
+    }
+    /// Called after an expression is evaluated, with its result.
+    fn after_eval(
+        &mut self,
+        _expression: &Location<Expression>,
+        _result: &Result<WanderValue<T>, WanderError>,
+        _environment: &Environment<T>,
+    ) {
+    }
+}
+
+// Evaluate an expression between calls to the EvalObserver.
+fn observe<T: HostType>(
+    expression: &Location<Expression>,
+    environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    let result = match environment.observe(|observer, environment| {
+        observer.before_eval(expression, environment)
+    }) {
+        Some(Err(err)) => Err(err),
+        _ => eval_expression(expression, environment),
+    };
+    environment.observe(|observer, environment| {
+        observer.after_eval(expression, &result, environment)
+    });
+    result
+}
+
+fn eval_expression<T: Clone + Display + PartialEq + Eq + std::fmt::Debug + Serialize>(
+    expression: &Location<Expression>,
+    environment: &mut Environment<T>,
 ) -> Result<WanderValue<T>, WanderError> {
     match expression {
         Location(Expression::Boolean(value), _) => Ok(WanderValue::Bool(*value)),
         Location(Expression::Int(value), _) => Ok(WanderValue::Int(*value)),
+        Location(Expression::Float(value), _) => Ok(WanderValue::Float(*value)),
         Location(Expression::String(value), _) => Ok(WanderValue::String(unescape_string(value.to_string()))),
+        Location(Expression::Char(value), _) => Ok(WanderValue::Char(*value)),
         Location(Expression::Identifier(value), _) => Ok(WanderValue::Identifier(value.clone())),
+        Location(Expression::Keyword(value), _) => Ok(WanderValue::Keyword(*value)),
         Location(Expression::Let(decls, body), _) => handle_let(decls.clone(), *body.clone(), environment),
         Location(Expression::Name(name), _) => read_name(name, environment),
         Location(Expression::TaggedName(name, tag), _) => read_tagged_name(name, tag, environment),
@@ -62,15 +135,186 @@ pub fn eval<T: Clone + Display + PartialEq + Eq + std::fmt::Debug + Serialize>(
         Location(Expression::Nothing, _) => Ok(WanderValue::Nothing),
         Location(Expression::Tuple(values), _) => handle_tuple(values, environment),
         Location(Expression::Record(values), _) => handle_record(values, environment),
+        Location(Expression::RecordUpdate(base, values), _) => {
+            handle_record_update(base, values, environment)
+        }
         Location(Expression::Lambda(name, input, output, body), _) => {
-            handle_lambda(name.clone(), input.clone(), output.clone(), body)
+            handle_lambda(name.clone(), input.clone(), output.clone(), body, environment)
         }
         Location(Expression::Set(values), _) => handle_set(values, environment),
         Location(Expression::HostFunction(name), _) => handle_host_function(name, environment),
+        Location(Expression::Match(value, arms), _) => {
+            let value = eval(value, environment)?;
+            let body = select_arm(value, arms, environment)?;
+            eval(body, environment)
+        }
+        Location(Expression::FieldAccess(value, field), _) => {
+            let path = field_path(value);
+            let value = eval(value, environment)?;
+            let path = path.unwrap_or_else(|| format!("{value}"));
+            access_field(value, field, &path)
+        }
+        Location(Expression::TailCall(name, arguments), position) => {
+            let mut expressions = vec![Location(Expression::Name(name.clone()), *position)];
+            expressions.append(&mut arguments.clone());
+            handle_function_call(&expressions, environment)
+        }
+        Location(Expression::Sequence(expressions), _) => {
+            let mut result = WanderValue::Nothing;
+            for expression in expressions {
+                result = eval(expression, environment)?;
+            }
+            Ok(result)
+        }
+        Location(Expression::Try(body, name, handler), _) => match eval(body, environment) {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                environment.bind(name.clone(), error_record(err));
+                eval(handler, environment)
+            }
+        },
+        // Results are Records with either an `ok` field holding the value
+        // or an `error` field holding the same Record a handler receives.
+        Location(Expression::TryResult(body), _) => match eval(body, environment) {
+            Ok(value) => Ok(WanderValue::Record(IndexMap::from([("ok".to_owned(), value)]))),
+            Err(err) => Ok(WanderValue::Record(IndexMap::from([(
+                "error".to_owned(),
+                error_record(err),
+            )]))),
+        },
+        // The cleanup always runs, an error from the body takes priority over one from the cleanup.
+        Location(Expression::Ensure(body, cleanup), _) => {
+            let result = eval(body, environment);
+            let cleanup = eval(cleanup, environment);
+            match (result, cleanup) {
+                (Err(err), _) => Err(err),
+                (Ok(_), Err(err)) => Err(err),
+                (Ok(value), Ok(_)) => Ok(value),
+            }
+        }
+        Location(Expression::Pure(body), _) => {
+            let outer = environment.is_pure();
+            environment.set_pure(true);
+            let result = eval(body, environment);
+            environment.set_pure(outer);
+            result
+        }
+        Location(Expression::Quantity(value, unit), _) => {
+            Ok(WanderValue::Quantity(Box::new(eval(value, environment)?), unit.clone()))
+        }
+        Location(Expression::CustomLiteral(literal), position) => {
+            match environment.read_literal_parser(literal) {
+                Some((parser, text)) => parser(text),
+                None => Err(WanderError::at(
+                    format!("No LiteralParser bound for {literal}"),
+                    *position,
+                )),
+            }
+        }
+        Location(Expression::Quote(quoted, unquoted), _) => {
+            handle_quote(quoted, unquoted, environment)
+        }
         // Expression::Grouping(expressions) => handle_grouping(expressions.clone(), environment),
     }
 }
 
+// Evaluate the unquoted expressions of a quote and put their values into it.
+fn handle_quote<T: HostType>(
+    quoted: &Location<Element>,
+    unquoted: &[Location<Expression>],
+    environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    let mut unquoted_values = vec![];
+    for expression in unquoted {
+        unquoted_values.push(eval(expression, environment)?);
+    }
+    let mut unquoted_values = unquoted_values.into_iter();
+    let mut quoted = quoted.clone();
+    replace_unquotes(&mut quoted, &mut |element: &Location<Element>| {
+        let value = unquoted_values.next().unwrap_or(WanderValue::Nothing);
+        match (&element.0, value) {
+            (Element::Splice(_), WanderValue::List(values) | WanderValue::Tuple(values)) => values
+                .into_iter()
+                .map(|value| value_element(value, element.1))
+                .collect(),
+            (Element::Splice(_), WanderValue::Set(values)) => ordered_set(&values)
+                .into_iter()
+                .map(|value| value_element(value.clone(), element.1))
+                .collect(),
+            (Element::Splice(_), value) => Err(WanderError::at(
+                format!("`~@` requires a List, Tuple, or Set, found {value}."),
+                element.1,
+            )),
+            (_, value) => Ok(vec![value_element(value, element.1)?]),
+        }
+    })?;
+    Ok(WanderValue::Quote(Box::new(quoted), None))
+}
+
+// The Element that evaluates to a value, used to put unquoted values into a quote
+// and to replace custom literals when a script is translated.
+pub(crate) fn value_element<T: HostType>(
+    value: WanderValue<T>,
+    position: usize,
+) -> Result<Location<Element>, WanderError> {
+    let elements = |values: Vec<WanderValue<T>>| {
+        values
+            .into_iter()
+            .map(|value| value_element(value, position))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let element = match value {
+        WanderValue::Bool(value) => Element::Boolean(value),
+        WanderValue::Int(value) => Element::Int(value),
+        WanderValue::Float(value) => Element::Float(value),
+        WanderValue::String(value) => Element::String(escape_string(&value)),
+        WanderValue::Char(value) => Element::Char(value),
+        WanderValue::Identifier(value) => Element::Identifier(value),
+        WanderValue::Keyword(value) => Element::Keyword(value),
+        WanderValue::Nothing => Element::Nothing,
+        WanderValue::List(values) => Element::List(elements(values)?),
+        WanderValue::Tuple(values) => Element::Tuple(elements(values)?),
+        WanderValue::Set(values) => {
+            Element::Set(elements(ordered_set(&values).into_iter().cloned().collect())?)
+        }
+        WanderValue::Record(values) => Element::Record(
+            values
+                .into_iter()
+                .map(|(name, value)| Ok((name, value_element(value, position)?)))
+                .collect::<Result<_, WanderError>>()?,
+        ),
+        WanderValue::Quantity(value, unit) => {
+            Element::Quantity(Box::new(value_element(*value, position)?), unit)
+        }
+        WanderValue::Lambda(parameter, input, output, body) => {
+            Element::Lambda(parameter, input, output, body)
+        }
+        WanderValue::Quote(quoted, _) => Element::Quote(quoted),
+        value @ (WanderValue::HostValue(_) | WanderValue::PartialApplication(_)) => {
+            return Err(WanderError::at(
+                format!("Cannot unquote {value}, it can't be written in a script."),
+                position,
+            ))
+        }
+    };
+    Ok(Location(element, position))
+}
+
+// The reverse of unescape_string, so a String put into a quote reads back the same.
+fn escape_string(value: &str) -> String {
+    let mut result = String::new();
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
 fn unescape_string(value: String) -> String {
     let mut result = String::new();
     let mut last_char = ' ';
@@ -120,14 +364,14 @@ fn handle_host_function<T: HostType>(
     for (name, wander_type) in params {
         match environment.read(&name) {
             Some(value) => arguments.push(value),
-            None => return Err(WanderError(format!("Could not read {}", name))),
+            None => return Err(WanderError::new(format!("Could not read {}", name))),
         }
     }
-    host_function.run(&arguments, environment)
+    environment.call_host_function(&host_function, &arguments)
 }
 
 fn handle_set<T: HostType + Display>(
-    expressions: &HashSet<Location<Expression>>,
+    expressions: &[Location<Expression>],
     environment: &mut Environment<T>,
 ) -> Result<WanderValue<T>, WanderError> {
     let mut results = HashSet::new();
@@ -155,10 +399,10 @@ fn handle_tuple<T: HostType>(
 }
 
 fn handle_record<T: HostType>(
-    expressions: &HashMap<String, Location<Expression>>,
+    expressions: &IndexMap<String, Location<Expression>>,
     environment: &mut Environment<T>,
 ) -> Result<WanderValue<T>, WanderError> {
-    let mut results = HashMap::new();
+    let mut results = IndexMap::new();
     for (name, expression) in expressions {
         match eval(expression, environment) {
             Ok(value) => results.insert(name.to_owned(), value),
@@ -168,6 +412,25 @@ fn handle_record<T: HostType>(
     Ok(WanderValue::Record(results))
 }
 
+fn handle_record_update<T: HostType>(
+    base: &Location<Expression>,
+    expressions: &IndexMap<String, Location<Expression>>,
+    environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    let mut results = match eval(base, environment)? {
+        WanderValue::Record(values) => values,
+        value => {
+            return Err(WanderError::new(format!(
+                "Could not update {value}, only Records can be updated."
+            )))
+        }
+    };
+    for (name, expression) in expressions {
+        results.insert(name.to_owned(), eval(expression, environment)?);
+    }
+    Ok(WanderValue::Record(results))
+}
+
 fn handle_list<T: HostType>(
     expressions: &Vec<Location<Expression>>,
     environment: &mut Environment<T>,
@@ -182,20 +445,40 @@ fn handle_list<T: HostType>(
     Ok(WanderValue::List(results))
 }
 
-fn handle_lambda<T: Clone + PartialEq + Eq>(
+fn handle_lambda<T: HostType>(
     name: String,
     input: Option<String>,
     output: Option<String>,
     body: &Location<Element>,
+    environment: &Environment<T>,
 ) -> Result<WanderValue<T>, WanderError> {
     Ok(WanderValue::Lambda(
         name,
         input.clone(),
         output.clone(),
-        Box::new(body.clone()),
+        Box::new(lambda_purity(body, environment)),
     ))
 }
 
+// A Lambda created while the Environment is pure stays pure when it's called later,
+// so the body after its last parameter is wrapped in a `pure ... end` expression.
+fn lambda_purity<T: HostType>(body: &Location<Element>, environment: &Environment<T>) -> Location<Element> {
+    match body {
+        _ if !environment.is_pure() => body.clone(),
+        Location(Element::Lambda(name, input, output, body), position) => Location(
+            Element::Lambda(
+                name.clone(),
+                input.clone(),
+                output.clone(),
+                Box::new(lambda_purity(body, environment)),
+            ),
+            *position,
+        ),
+        Location(Element::Pure(_), _) => body.clone(),
+        Location(_, position) => Location(Element::Pure(Box::new(body.clone())), *position),
+    }
+}
+
 fn handle_conditional<T: HostType + Display>(
     cond: &Location<Expression>,
     ife: &Location<Expression>,
@@ -205,66 +488,129 @@ fn handle_conditional<T: HostType + Display>(
     match eval(cond, environment)? {
         WanderValue::Bool(true) => eval(ife, environment),
         WanderValue::Bool(false) => eval(elsee, environment),
-        value => Err(WanderError(format!(
+        value => Err(WanderError::new(format!(
             "Conditionals require a bool value found, {value}"
         ))),
     }
 }
 
+// An argument waiting to be applied in a function call. The results of earlier applications
+// stay values so they never need to be converted back into Expressions.
+enum Argument<T: HostType> {
+    Expression(Location<Expression>),
+    Value(WanderValue<T>),
+}
+
+impl<T: HostType> Argument<T> {
+    fn eval(self, environment: &mut Environment<T>) -> Result<WanderValue<T>, WanderError> {
+        match self {
+            Argument::Expression(expression) => eval(&expression, environment),
+            Argument::Value(value) => Ok(value),
+        }
+    }
+}
+
 fn run_lambda<T: HostType + Display>(
     name: String,
     input: Option<String>,
     output: Option<String>,
     lambda_body: Location<Element>,
-    expressions: &mut Vec<Location<Expression>>,
+    arguments: &mut Vec<Argument<T>>,
     environment: &mut Environment<T>,
 ) -> Option<Result<WanderValue<T>, WanderError>> {
-    if expressions.is_empty() {
-        Some(Ok(WanderValue::Lambda(
+    if arguments.is_empty() {
+        return Some(Ok(WanderValue::Lambda(
             name,
             input,
             output,
             Box::new(lambda_body),
-        )))
-    } else {
-        let argument_expression = expressions.pop().unwrap();
-        let argument_value = match eval(&argument_expression, environment) {
-            Err(e) => return Some(Err(e)),
-            Ok(e) => e,
-        };
-        environment.bind(name, argument_value);
-        let expression = match express(&lambda_body) {
-            Ok(e) => e,
-            Err(e) => return Some(Err(e)),
-        };
-        let function = match eval(&expression, environment) {
-            Ok(e) => e,
-            Err(err) => return Some(Err(err)),
-        };
-        match function {
-            WanderValue::Lambda(_, _, _, b) => {
-                let Ok(expression) = express(&b) else {
-                    return None;
-                };
-                match eval(&expression, environment) {
-                    Ok(value) => {
-                        expressions.push(value_to_expression(value));
-                        None
-                    }
-                    Err(err) => Some(Err(err)),
-                }
-            }
-            _ => {
-                if expressions.is_empty() {
-                    Some(Ok(function))
-                } else {
-                    Some(Err(WanderError(format!(
-                        "Invalid function call, expected expressions {expressions:?}."
-                    ))))
-                }
+        )));
+    }
+    let argument_value = match arguments.pop().unwrap().eval(environment) {
+        Err(e) => return Some(Err(e)),
+        Ok(e) => e,
+    };
+    if let Some(tag) = &input {
+        if let Err(err) = environment.check_tag(&name, tag, &argument_value) {
+            return Some(Err(err));
+        }
+    }
+    environment.bind(name, argument_value);
+    let expression = match express(&lambda_body) {
+        Ok(e) => e,
+        Err(e) => return Some(Err(e)),
+    };
+    match eval(&expression, environment) {
+        Ok(value) if arguments.is_empty() => Some(Ok(value)),
+        Ok(value) => {
+            arguments.push(Argument::Value(value));
+            None
+        }
+        Err(err) => Some(Err(err)),
+    }
+}
+
+// The name of the HostFunction at the end of a chain of Lambdas created by bind_host_function.
+pub(crate) fn host_function_name(body: &Location<Element>) -> Option<String> {
+    match body {
+        Location(Element::HostFunction(name), _) => Some(name.clone()),
+        Location(Element::Lambda(_, _, _, body), _) => host_function_name(body),
+        _ => None,
+    }
+}
+
+fn is_host_function<T: HostType>(value: &WanderValue<T>) -> bool {
+    match value {
+        WanderValue::Lambda(_, _, _, body) => host_function_name(body).is_some(),
+        WanderValue::PartialApplication(_) => true,
+        _ => false,
+    }
+}
+
+// HostFunctions are applied directly instead of through their Lambda bindings so a partially
+// applied HostFunction keeps its arguments instead of reading them back out of scope.
+fn apply_host_function<T: HostType>(
+    value: WanderValue<T>,
+    pending: &mut Vec<Argument<T>>,
+    declared: &mut Option<usize>,
+    environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    let (name, mut arguments) = match value {
+        WanderValue::PartialApplication(application) => (application.name, application.arguments),
+        WanderValue::Lambda(_, _, _, body) => match host_function_name(&body) {
+            Some(name) => (name, vec![]),
+            None => return Err(WanderError::new("Expected a HostFunction.".to_owned())),
+        },
+        value => return Err(WanderError::new(format!("Expected a HostFunction and found {value}."))),
+    };
+    let Some(host_function) = environment.read_host_function(&name) else {
+        return Err(WanderError::new(format!("Function {name} is not defined.")));
+    };
+    let arity = host_function.binding().parameters.len();
+    declared.get_or_insert(arity);
+    while arguments.len() < arity {
+        match pending.pop() {
+            Some(argument) => arguments.push(argument.eval(environment)?),
+            None => {
+                return Ok(WanderValue::PartialApplication(Box::new(
+                    PartialApplication { name, arguments },
+                )))
             }
         }
     }
+    environment.call_host_function(&host_function, &arguments)
+}
+
+fn lambda_arity(body: &Location<Element>) -> usize {
+    1 + lambda_parameters(body).len()
+}
+
+fn arity_error(declared: usize, supplied: usize, position: usize) -> WanderError {
+    let arguments = if declared == 1 { "argument" } else { "arguments" };
+    WanderError::at(
+        format!("Function of {declared} {arguments} applied to {supplied}"),
+        position,
+    )
 }
 
 fn handle_function_call<T: HostType>(
@@ -275,105 +621,259 @@ fn handle_function_call<T: HostType>(
         let expression = expressions.first().unwrap();
         return eval(expression, environment);
     }
-    let mut expressions = expressions.clone();
-    expressions.reverse();
-    while let Some(expression) = expressions.pop() {
-        match expression {
-            Location(Expression::Application(contents), position) => {
-                match handle_function_call(&contents, environment)? {
-                    WanderValue::Lambda(name, input, output, element) => {
-                        if let Some(res) =
-                            run_lambda(name, input, output, *element, &mut expressions, environment)
-                        {
-                            return res;
-                        }
-                    }
-                    e => return Ok(e),
+    let position = expressions.first().unwrap().1;
+    let arguments = expressions.iter().rev().cloned().map(Argument::Expression).collect();
+    apply_arguments(arguments, position, environment)
+}
+
+/// Call a function value, a Lambda or HostFunction, with already evaluated arguments.
+/// The Lambda's parameters are bound in a new scope that is removed once the call finishes.
+pub(crate) fn call_function<T: HostType>(
+    function: &WanderValue<T>,
+    arguments: &[WanderValue<T>],
+    environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    if arguments.is_empty() {
+        return Ok(function.clone());
+    }
+    let mut stack: Vec<Argument<T>> = arguments.iter().rev().cloned().map(Argument::Value).collect();
+    stack.push(Argument::Value(function.clone()));
+    environment.add_scope();
+    let result = apply_arguments(stack, 0, environment);
+    environment.remove_scope();
+    result
+}
+
+// Apply the function on top of the stack to the arguments below it,
+// the first argument to apply is the last in the Vec.
+fn apply_arguments<T: HostType>(
+    mut arguments: Vec<Argument<T>>,
+    position: usize,
+    environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    let supplied = arguments.len() - 1;
+    let mut declared = None;
+    while let Some(argument) = arguments.pop() {
+        let (name, value) = match argument {
+            Argument::Value(value) => (None, value),
+            Argument::Expression(Location(Expression::Application(contents), _)) => {
+                (None, handle_function_call(&contents, environment)?)
+            }
+            Argument::Expression(Location(Expression::Lambda(name, input, output, lambda_body), _)) => {
+                (None, handle_lambda(name, input, output, &lambda_body, environment)?)
+            }
+            Argument::Expression(Location(Expression::Name(name), name_position)) => {
+                let value = eval(&Location(Expression::Name(name.clone()), name_position), environment)?;
+                (Some(name), value)
+            }
+            Argument::Expression(value) => {
+                if arguments.is_empty() {
+                    return eval(&value, environment);
+                } else if let Some(declared) = declared {
+                    return Err(arity_error(declared, supplied, position));
+                } else {
+                    return Err(WanderError::new(format!("Invalid function call {value:?}.")));
                 }
-            },
-            Location(Expression::Lambda(name, input, output, lambda_body), position) => {
-                if let Some(res) = run_lambda(
-                    name,
-                    input,
-                    output,
-                    *lambda_body,
-                    &mut expressions,
-                    environment,
-                ) {
-                    return res;
+            }
+        };
+        match value {
+            value if is_host_function(&value) => {
+                let value = apply_host_function(value, &mut arguments, &mut declared, environment)?;
+                if arguments.is_empty() {
+                    return Ok(value);
                 }
+                arguments.push(Argument::Value(value));
             }
-            Location(Expression::Name(name), position) => match eval(&Location(Expression::Name(name), position), environment) {
-                Ok(value) => match value {
-                    WanderValue::Lambda(p, i, o, b) => {
-                        let argument_expression = expressions.pop().unwrap();
-                        let argument_value = eval(&argument_expression, environment)?;
-                        environment.bind(p, argument_value);
-                        match eval(&express(&b)?, environment) {
-                            Ok(value) => expressions.push(value_to_expression(value)),
-                            Err(err) => return Err(err),
-                        }
-                    }
-                    _ => {
-                        return Err(WanderError(format!(
-                            "Invalid function call, was expecting a lambda and found {value}."
-                        )))
+            // A named Lambda called with all of its arguments runs in its own scope,
+            // so a recursive call can't overwrite the parameters of the call that made it,
+            // and calls to itself in tail position run as a loop.
+            // A partial application is curried like any other named Lambda.
+            WanderValue::Lambda(p, input, _, b)
+                if name.is_some() && arguments.len() >= lambda_arity(&b) =>
+            {
+                let name = name.unwrap();
+                let mut params = vec![(p, input)];
+                params.append(&mut lambda_parameters(&b));
+                declared.get_or_insert(params.len());
+                let mut values = vec![];
+                for _ in 0..params.len() {
+                    values.push(arguments.pop().unwrap().eval(environment)?);
+                }
+                let body = lambda_body(&b);
+                environment.add_scope();
+                let value = if has_tail_call(&name, &body) {
+                    run_tail_recursive(&name, &params, &body, values, position, environment)
+                } else {
+                    bind_parameters(&params, values, position, environment)
+                        .and_then(|()| eval(&express(&body)?, environment))
+                };
+                environment.remove_scope();
+                let value = value?;
+                if arguments.is_empty() {
+                    return Ok(value);
+                }
+                arguments.push(Argument::Value(value));
+            }
+            WanderValue::Lambda(p, input, _, b) if name.is_some() => {
+                declared.get_or_insert(lambda_arity(&b));
+                let argument_value = arguments.pop().unwrap().eval(environment)?;
+                if let Some(tag) = input {
+                    environment.check_tag(&p, &tag, &argument_value)?;
+                }
+                environment.bind(p, argument_value);
+                let value = eval(&express(&b)?, environment)?;
+                arguments.push(Argument::Value(value));
+            }
+            WanderValue::Lambda(name, input, output, element) => {
+                let arity = *declared.get_or_insert(lambda_arity(&element));
+                if let Some(res) = run_lambda(name, input, output, *element, &mut arguments, environment) {
+                    if !arguments.is_empty() {
+                        return Err(arity_error(arity, supplied, position));
                     }
-                },
-                Err(err) => return Err(err),
-            },
+                    return res;
+                }
+            }
             value => {
-                if expressions.is_empty() {
-                    return eval(&value, environment);
+                if arguments.is_empty() {
+                    return Ok(value);
+                } else if let Some(declared) = declared {
+                    return Err(arity_error(declared, supplied, position));
                 } else {
-                    return Err(WanderError(format!("Invalid function call {value:?}.")));
+                    return Err(WanderError::new(format!(
+                        "Invalid function call, was expecting a lambda and found {value}."
+                    )));
                 }
             }
-        };
+        }
     }
     panic!()
 }
 
-fn value_to_expression<T: Clone + Display + PartialEq + Eq>(value: WanderValue<T>) -> Location<Expression> {
-    match value {
-        WanderValue::Bool(value) => Location(Expression::Boolean(value), 0),
-        WanderValue::Int(value) => Location(Expression::Int(value), 0),
-        WanderValue::String(value) => Location(Expression::String(value), 0),
-        WanderValue::Identifier(value) => Location(Expression::Identifier(value), 0),
-        WanderValue::Nothing => Location(Expression::Nothing, 0),
-        WanderValue::Lambda(p, i, o, b) => Location(Expression::Lambda(p, i, o, b), 0),
-        WanderValue::List(values) => {
-            let mut expressions = vec![];
-            for value in values {
-                expressions.push(value_to_expression(value).clone());
-            }
-            Location(Expression::List(expressions), 0)
+fn lambda_parameters(element: &Location<Element>) -> Vec<(String, Option<String>)> {
+    match element {
+        Location(Element::Lambda(p, tag, _, b), _) => {
+            let mut params = vec![(p.clone(), tag.clone())];
+            params.append(&mut lambda_parameters(b));
+            params
         }
-        WanderValue::Tuple(values) => {
-            let mut expressions = vec![];
-            for value in values {
-                expressions.push(value_to_expression(value).clone());
-            }
-            Location(Expression::Tuple(expressions), 0)
+        _ => vec![],
+    }
+}
+
+fn lambda_body(element: &Location<Element>) -> Location<Element> {
+    match element {
+        Location(Element::Lambda(_, _, _, b), _) => lambda_body(b),
+        body => body.clone(),
+    }
+}
+
+fn has_tail_call(name: &str, element: &Location<Element>) -> bool {
+    match element {
+        Location(Element::TailCall(callee, _), _) => callee == name,
+        Location(Element::Grouping(elements), _) => {
+            elements.len() == 1 && has_tail_call(name, &elements[0])
         }
-        WanderValue::Set(values) => {
-            let mut expressions = HashSet::new();
-            for value in values {
-                expressions.insert(value_to_expression(value).clone());
-            }
-            Location(Expression::Set(expressions), 0)
+        Location(Element::Conditional(_, i, e), _) => {
+            has_tail_call(name, i) || has_tail_call(name, e)
         }
-        WanderValue::Record(value_record) => {
-            let mut record = HashMap::new();
-            for (name, value) in value_record {
-                record.insert(name, value_to_expression(value));
+        Location(Element::Let(_, body), _) | Location(Element::Pure(body), _) => has_tail_call(name, body),
+        Location(Element::Match(_, arms), _) => arms.iter().any(|(_, body)| has_tail_call(name, body)),
+        _ => false,
+    }
+}
+
+// Evaluate a self-recursive Lambda, treating calls to itself in tail position as
+// a loop instead of a nested call so the stack doesn't grow.
+// A pure Lambda's body is run as a loop too, with the Environment pure while it runs.
+fn run_tail_recursive<T: HostType>(
+    name: &str,
+    params: &[(String, Option<String>)],
+    body: &Location<Element>,
+    arguments: Vec<WanderValue<T>>,
+    position: usize,
+    environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    match express(body)? {
+        Location(Expression::Pure(body), _) => {
+            let outer = environment.is_pure();
+            environment.set_pure(true);
+            let result = run_tail_loop(name, params, &body, arguments, position, environment);
+            environment.set_pure(outer);
+            result
+        }
+        body => run_tail_loop(name, params, &body, arguments, position, environment),
+    }
+}
+
+fn run_tail_loop<T: HostType>(
+    name: &str,
+    params: &[(String, Option<String>)],
+    body: &Location<Expression>,
+    mut arguments: Vec<WanderValue<T>>,
+    mut position: usize,
+    environment: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    loop {
+        bind_parameters(params, arguments, position, environment)?;
+        let mut current = body;
+        loop {
+            match current {
+                Location(Expression::Conditional(c, i, e), _) => match eval(c, environment)? {
+                    WanderValue::Bool(true) => current = i,
+                    WanderValue::Bool(false) => current = e,
+                    value => {
+                        return Err(WanderError::new(format!(
+                            "Conditionals require a bool value found, {value}"
+                        )))
+                    }
+                },
+                Location(Expression::Match(value, arms), _) => {
+                    let value = eval(value, environment)?;
+                    current = select_arm(value, arms, environment)?;
+                }
+                Location(Expression::Let(decls, let_body), _) => {
+                    for (name, tag, decl_body) in decls.clone() {
+                        handle_decl(name, tag, decl_body, environment)?;
+                    }
+                    current = let_body;
+                }
+                Location(Expression::TailCall(callee, tail_arguments), tail_position)
+                    if callee == name =>
+                {
+                    let mut values = vec![];
+                    for argument in tail_arguments {
+                        values.push(eval(argument, environment)?);
+                    }
+                    arguments = values;
+                    position = *tail_position;
+                    break;
+                }
+                expression => return eval(expression, environment),
             }
-            Location(Expression::Record(record), 0)
         }
-        WanderValue::HostValue(value) => todo!(),
     }
 }
 
+// Bind the arguments of a call to the parameters of a named Lambda,
+// checking that there's one argument for each parameter.
+fn bind_parameters<T: HostType>(
+    params: &[(String, Option<String>)],
+    arguments: Vec<WanderValue<T>>,
+    position: usize,
+    environment: &mut Environment<T>,
+) -> Result<(), WanderError> {
+    if arguments.len() != params.len() {
+        return Err(arity_error(params.len(), arguments.len(), position));
+    }
+    for ((param, tag), argument) in params.iter().zip(arguments) {
+        if let Some(tag) = tag {
+            environment.check_tag(param, tag, &argument)?;
+        }
+        environment.bind(param.clone(), argument);
+    }
+    Ok(())
+}
+
 fn handle_let<T: HostType + Display>(
     decls: Vec<(String, Option<Location<Expression>>, Location<Expression>)>,
     body: Location<Expression>,
@@ -385,20 +885,96 @@ fn handle_let<T: HostType + Display>(
     eval(&body, environment)
 }
 
+// The record bound to the name in a try expression's handler.
+pub(crate) fn error_record<T: HostType>(error: WanderError) -> WanderValue<T> {
+    let span = match error.span {
+        Some(span) => WanderValue::Record(IndexMap::from([
+            ("start".to_owned(), WanderValue::Int(span.start as i64)),
+            ("end".to_owned(), WanderValue::Int(span.end as i64)),
+        ])),
+        None => WanderValue::Nothing,
+    };
+    WanderValue::Record(IndexMap::from([
+        ("message".to_owned(), WanderValue::String(error.message)),
+        (
+            "code".to_owned(),
+            error.code.map_or(WanderValue::Nothing, WanderValue::String),
+        ),
+        ("span".to_owned(), span),
+    ]))
+}
+
+// Find the first arm whose Pattern matches the value, bind the names it captures,
+// and return the arm's body.
+fn select_arm<'a, T: HostType>(
+    value: WanderValue<T>,
+    arms: &'a [(Pattern, Location<Expression>)],
+    environment: &mut Environment<T>,
+) -> Result<&'a Location<Expression>, WanderError> {
+    for (pattern, body) in arms {
+        let mut bindings = vec![];
+        if match_pattern(pattern, &value, &mut bindings) {
+            for (name, value) in bindings {
+                environment.bind(name, value);
+            }
+            return Ok(body);
+        }
+    }
+    Err(WanderError::new(format!("No pattern matched {value}.")))
+}
+
+fn match_pattern<T: HostType>(
+    pattern: &Pattern,
+    value: &WanderValue<T>,
+    bindings: &mut Vec<(String, WanderValue<T>)>,
+) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard, _) => true,
+        (Pattern::Name(name), value) => {
+            bindings.push((name.clone(), value.clone()));
+            true
+        }
+        (Pattern::Boolean(expected), WanderValue::Bool(value)) => expected == value,
+        (Pattern::Int(expected), WanderValue::Int(value)) => expected == value,
+        (Pattern::Float(expected), WanderValue::Float(value)) => expected == value,
+        (Pattern::String(expected), WanderValue::String(value)) => {
+            &unescape_string(expected.clone()) == value
+        }
+        (Pattern::Char(expected), WanderValue::Char(value)) => expected == value,
+        (Pattern::Identifier(expected), WanderValue::Identifier(value)) => expected == value,
+        (Pattern::Keyword(expected), WanderValue::Keyword(value)) => expected == value,
+        (Pattern::Nothing, WanderValue::Nothing) => true,
+        (Pattern::Tuple(patterns), WanderValue::Tuple(values))
+        | (Pattern::List(patterns), WanderValue::List(values)) => {
+            patterns.len() == values.len()
+                && patterns
+                    .iter()
+                    .zip(values)
+                    .all(|(pattern, value)| match_pattern(pattern, value, bindings))
+        }
+        (Pattern::Record(patterns), WanderValue::Record(values)) => {
+            patterns.iter().all(|(name, pattern)| match values.get(name) {
+                Some(value) => match_pattern(pattern, value, bindings),
+                None => false,
+            })
+        }
+        _ => false,
+    }
+}
+
 fn handle_decl<T: HostType + Display>(
     name: String,
     tag: Option<Location<Expression>>,
     body: Location<Expression>,
     environment: &mut Environment<T>,
 ) -> Result<(), WanderError> {
-    //TODO handle tag checking here
-    match eval(&body, environment) {
-        Ok(value) => {
-            environment.bind(name.to_string(), value);
-            Ok(())
-        }
-        Err(err) => Err(err),
+    let value = eval(&body, environment)?;
+    // Tags are translated to Names, see express_optional_name.
+    if let Some(Location(Expression::Name(tag), _)) = tag {
+        environment.check_tag(&name, &tag, &value)?;
     }
+    environment.bind(name, value);
+    Ok(())
 }
 
 fn read_name<T: HostType>(
@@ -409,8 +985,10 @@ fn read_name<T: HostType>(
         Ok(value)
     } else {
         match environment.read_host_function(name) {
-            Some(_) => todo!(), //Ok(WanderValue::HostedFunction(name.to_owned())),
-            None => read_field(name, environment),
+            Some(function) => environment.call_host_function(&function, &[]),
+            None => Err(WanderError::new(format!(
+                "Error looking up {name}, {name} is not bound."
+            ))),
         }
     }
 }
@@ -420,77 +998,59 @@ fn read_tagged_name<T: HostType>(
     tag: &Location<Expression>,
     environment: &mut Environment<T>,
 ) -> Result<WanderValue<T>, WanderError> {
-    if let Some(value) = environment.read(name) {
-        Ok(value)
-    } else {
-        match environment.read_host_function(name) {
-            Some(_) => todo!(), //Ok(WanderValue::HostedFunction(name.to_owned())),
-            None => read_field(name, environment),
-        }
-    }
+    read_name(name, environment)
 }
 
-fn read_field<T: HostType>(
-    name: &str,
-    environment: &mut Environment<T>,
+// Follow a chain of field names through nested Records, `base` describes the starting value for errors.
+pub(crate) fn read_path<'a, T: HostType>(
+    value: WanderValue<T>,
+    base: &str,
+    fields: impl IntoIterator<Item = &'a str>,
 ) -> Result<WanderValue<T>, WanderError> {
-    let t = name
-        .split('.')
-        .map(|e| e.to_string())
-        .collect::<Vec<String>>();
-    let mut result = None;
-    let (name, fields) = t.split_first().unwrap();
-    if let Some(WanderValue::Record(value)) = environment.read(&name.to_string()) {
-        for field in fields {
-            match result {
-                Some(WanderValue::Record(r)) => result = Some(r.get(field).unwrap().clone()),
-                Some(x) => {
-                    return Err(WanderError(format!(
-                        "Could not access field {field} in {x}."
-                    )))
-                }
-                None => match value.get(field) {
-                    Some(r) => result = Some(r.clone()),
-                    None => return Err(WanderError(format!("Could not read field {name}"))),
-                },
-            }
-        }
-        Ok(result.unwrap().clone())
-    } else {
-        Err(WanderError(format!("Error looking up {name}")))
+    let mut result = value;
+    let mut path = base.to_owned();
+    for field in fields {
+        result = access_field(result, field, &path)?;
+        path = format!("{path}.{field}");
     }
+    Ok(result)
 }
 
-fn call_function<T: HostType + Display>(
-    name: &String,
-    arguments: &Vec<Location<Expression>>,
-    environment: &mut Environment<T>,
-) -> Result<WanderValue<T>, WanderError> {
-    let mut argument_values = vec![];
-    for argument in arguments {
-        match eval(argument, environment) {
-            Ok(value) => argument_values.push(value),
-            Err(err) => return Err(err),
+// Describe how a chain of field accesses starting at a name was reached, like `x.a`, for errors.
+fn field_path(expression: &Location<Expression>) -> Option<String> {
+    match expression {
+        Location(Expression::Name(name), _) => Some(name.clone()),
+        Location(Expression::FieldAccess(value, field), _) => {
+            field_path(value).map(|path| format!("{path}.{field}"))
         }
+        _ => None,
     }
-    match environment.read(name) {
-        //found other value (err), will evntually handle lambdas here
-        Some(_) => Err(WanderError(format!("Function {} is not defined.", &name))),
-        None => match environment.read_host_function(name) {
-            None => Err(WanderError(format!("Function {} is not defined.", name))),
-            Some(function) => {
-                if argument_values.len() == function.binding().parameters.len() {
-                    function.run(&argument_values, environment)
-                } else {
-                    // Ok(WanderValue::PartialApplication(Box::new(
-                    //     PartialApplication {
-                    //         arguments: argument_values,
-                    //         callee: WanderValue::HostedFunction(name.clone()),
-                    //     },
-                    // )))
-                    todo!()
-                }
+}
+
+// Read a single field from a Record, `path` describes how the value was reached for errors.
+fn access_field<T: HostType>(
+    value: WanderValue<T>,
+    field: &str,
+    path: &str,
+) -> Result<WanderValue<T>, WanderError> {
+    match value {
+        WanderValue::Record(record) => match record.get(field) {
+            Some(value) => Ok(value.clone()),
+            None => {
+                let mut keys: Vec<&String> = record.keys().collect();
+                keys.sort();
+                let keys = keys
+                    .iter()
+                    .map(|key| key.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ");
+                Err(WanderError::new(format!(
+                    "Could not read field {field} in {path}, available fields are [{keys}]."
+                )))
             }
         },
+        value => Err(WanderError::new(format!(
+            "Could not access field {field} in {path}, {path} is {value} not a Record."
+        ))),
     }
 }
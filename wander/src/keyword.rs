@@ -0,0 +1,70 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Contains the definition of Keywords.
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::HashSet, fmt::Display, sync::Mutex};
+
+lazy_static! {
+    static ref KEYWORDS: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
+}
+
+/// An interned symbol written as `:name` in Wander scripts.
+/// Each name is only stored once, so Keywords are compared by identity instead of by content.
+#[derive(Debug, Clone, Copy)]
+pub struct Keyword(&'static str);
+
+impl Keyword {
+    /// Returns the Keyword for the given name, interning the name the first time it is seen.
+    pub fn new(name: &str) -> Self {
+        let mut keywords = KEYWORDS.lock().unwrap();
+        match keywords.get(name) {
+            Some(name) => Self(name),
+            None => {
+                let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+                keywords.insert(name);
+                Self(name)
+            }
+        }
+    }
+
+    /// Returns the name of the given Keyword, without the leading colon.
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl PartialEq for Keyword {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Keyword {}
+
+impl std::hash::Hash for Keyword {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.0, state)
+    }
+}
+
+impl Display for Keyword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, ":{}", self.0)
+    }
+}
+
+impl Serialize for Keyword {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Keyword {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|name| Keyword::new(&name))
+    }
+}
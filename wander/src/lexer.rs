@@ -5,9 +5,9 @@
 use logos::{Lexer, Logos};
 use serde::Serialize;
 
-use crate::{environment::Environment, identifier::Identifier, HostType, WanderError, Location};
+use crate::{environment::Environment, identifier::Identifier, keyword::Keyword, HostType, Location, Span, WanderError};
 
-#[derive(Logos, Debug, PartialEq, Eq, Clone, Serialize)]
+#[derive(Logos, Debug, PartialEq, Clone, Serialize)]
 #[logos()]
 pub enum Token {
     #[regex("[ \t\n\r]+", ws)]
@@ -49,12 +49,32 @@ pub enum Token {
     #[regex("-?[0-9]+", int, priority = 2)]
     Int(i64),
 
+    #[regex("-?[0-9]+\\.[0-9]+", float)]
+    Float(f64),
+
+    // A number with its unit written directly after it, like `5<minutes>`,
+    // with a space between them it's a number and an Identifier.
+    #[regex("-?[0-9]+<[^>\\s]+>", int_quantity)]
+    IntQuantity((i64, Identifier)),
+
+    #[regex("-?[0-9]+\\.[0-9]+<[^>\\s]+>", float_quantity)]
+    FloatQuantity((f64, Identifier)),
+
     #[regex(r#""(([^\x00-\x1F"\\]|\\["\\/bfnrt]|\\u[0-9a-fA-F]{4})*)""#, string)]
     String(String),
 
+    // An unescaped `(` isn't allowed so `'('a')` is still a Tuple, `'\('` is the open paren Char.
+    #[regex(r"'([^\x00-\x1F'\\(]+|\\['\\(nrt])'", character)]
+    Char(char),
+
     #[regex("[_a-zA-Z]+[_a-zA-Z0-9.?]*", name, priority = 2)]
     Name(String),
 
+    // Custom literals are handled by a LiteralParser bound in the Environment,
+    // they end at whitespace, a bracket, or a separator so `val d = @2024-01-01;` splits as expected.
+    #[regex(r#"[@#][^\s()\[\]{};,"]+"#, custom_literal)]
+    CustomLiteral(String),
+
     #[regex("(true)|(false)", bool)]
     Boolean(bool),
 
@@ -85,9 +105,45 @@ pub enum Token {
     #[regex("<[a-zA-Z0-9-._~:/?#\\[\\]@!$&'()*+,;%=\\x{00A0}-\\x{D7FF}\\x{F900}-\\x{FDCF}\\x{FDF0}-\\x{FFEF}\\x{10000}-\\x{1FFFD}\\x{20000}-\\x{2FFFD}\\x{30000}-\\x{3FFFD}\\x{40000}-\\x{4FFFD}\\x{50000}-\\x{5FFFD}\\x{60000}-\\x{6FFFD}\\x{70000}-\\x{7FFFD}\\x{80000}-\\x{8FFFD}\\x{90000}-\\x{9FFFD}\\x{A0000}-\\x{AFFFD}\\x{B0000}-\\x{BFFFD}\\x{C0000}-\\x{CFFFD}\\x{D0000}-\\x{DFFFD}\\x{E1000}-\\x{EFFFD}]+>", identifier)]
     Identifier(Identifier),
 
+    #[regex(":[_a-zA-Z][_a-zA-Z0-9]*", keyword)]
+    Keyword(Keyword),
+
     #[token("|")]
     Pipe,
 
+    #[token("&&")]
+    And,
+
+    #[token("||")]
+    Or,
+
+    #[token("==")]
+    Equal,
+
+    #[token("!=")]
+    NotEqual,
+
+    #[token("!")]
+    Bang,
+
+    #[token("+")]
+    Plus,
+
+    #[token("++")]
+    PlusPlus,
+
+    #[token("/")]
+    Slash,
+
+    #[token("<=")]
+    LessEqual,
+
+    #[token(">=")]
+    GreaterEqual,
+
+    #[token("-")]
+    Minus,
+
     #[token("->")]
     Arrow,
 
@@ -97,13 +153,25 @@ pub enum Token {
     #[token("?")]
     QuestionMark,
 
+    #[token(";")]
+    Semicolon,
+
     #[token("`")]
     Backtick,
 
+    #[token("~")]
+    Tilde,
+
+    #[token("~@")]
+    TildeAt,
+
     #[token("#")]
     Hash,
 
+    // Line comments start with `--`, block comments are wrapped in `{-` and `-}`.
+    // Since `{-` always opens a block comment, a brace followed by a negative number needs a space.
     #[regex("--.*\n?", comment)]
+    #[regex(r"\{-[^-]*-+([^-}][^-]*-+)*\}", comment)]
     Comment(String),
 
     #[token("\\")]
@@ -111,8 +179,53 @@ pub enum Token {
 
     #[token("fun")]
     Fun,
+
+    #[token("match")]
+    Match,
+
+    #[token("with")]
+    With,
+
+    #[token("do")]
+    Do,
+
+    #[token("val")]
+    Val,
+
+    #[token("try")]
+    Try,
+
+    #[token("handle")]
+    Handle,
+
+    #[token("ensure")]
+    Ensure,
+
+    #[token("finally")]
+    Finally,
+
+    #[token("pure")]
+    Pure,
+
+    #[token("use")]
+    Use,
+
+    #[token("as")]
+    As,
+
+    #[token("import")]
+    Import,
+
+    #[token("from")]
+    From,
+
+    #[token("*")]
+    Star,
 }
 
+// Float tokens are only lexed from digits, so they are never NaN and equality stays reflexive.
+impl Eq for Token {}
+
 fn bool(lex: &mut Lexer<Token>) -> Option<bool> {
     let slice = lex.slice();
     match slice {
@@ -130,6 +243,24 @@ fn int(lex: &mut Lexer<Token>) -> Option<i64> {
     }
 }
 
+fn float(lex: &mut Lexer<Token>) -> Option<f64> {
+    let slice = lex.slice();
+    match slice.parse::<f64>() {
+        Ok(value) => Some(value),
+        _ => None,
+    }
+}
+
+fn int_quantity(lex: &mut Lexer<Token>) -> Option<(i64, Identifier)> {
+    let (value, unit) = lex.slice().split_once('<')?;
+    Some((value.parse::<i64>().ok()?, Identifier::new(unit.trim_end_matches('>')).ok()?))
+}
+
+fn float_quantity(lex: &mut Lexer<Token>) -> Option<(f64, Identifier)> {
+    let (value, unit) = lex.slice().split_once('<')?;
+    Some((value.parse::<f64>().ok()?, Identifier::new(unit.trim_end_matches('>')).ok()?))
+}
+
 fn trim_string(value: &str) -> &str {
     let mut chars = value.chars();
     assert_eq!(chars.next().unwrap(), '"');
@@ -141,6 +272,25 @@ fn string(lex: &mut Lexer<Token>) -> Option<String> {
     Some(trim_string(lex.slice()).to_string())
 }
 
+fn character(lex: &mut Lexer<Token>) -> Option<char> {
+    let slice = lex.slice();
+    let mut chars = slice[1..slice.len() - 1].chars();
+    let value = match chars.next()? {
+        '\\' => match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            escaped => escaped,
+        },
+        value => value,
+    };
+    // The regex matches runs of characters, since logos can't match a single multi-byte one.
+    match chars.next() {
+        Some(_) => None,
+        None => Some(value),
+    }
+}
+
 fn name(lex: &mut Lexer<Token>) -> Option<String> {
     Some(lex.slice().to_string())
 }
@@ -152,6 +302,14 @@ fn identifier(lex: &mut Lexer<Token>) -> Option<Identifier> {
     }
 }
 
+fn keyword(lex: &mut Lexer<Token>) -> Option<Keyword> {
+    Some(Keyword::new(lex.slice().trim_start_matches(':')))
+}
+
+fn custom_literal(lex: &mut Lexer<Token>) -> Option<String> {
+    Some(lex.slice().to_string())
+}
+
 fn comment(lex: &mut Lexer<Token>) -> Option<String> {
     Some(lex.slice().to_string())
 }
@@ -166,7 +324,16 @@ pub fn tokenize(script: &str) -> Result<Vec<Location<Token>>, WanderError> {
     for (token, range) in lexer {
         match token {
             Ok(token) => results.push(Location(token, range.start)),
-            Err(_) => return Err(WanderError(String::from("Error tokenizing input."))),
+            Err(_) => {
+                return Err(WanderError {
+                    message: format!("Unexpected input {:?}", &script[range.clone()]),
+                    span: Some(Span {
+                        start: range.start,
+                        end: range.end,
+                    }),
+                    code: None,
+                })
+            }
         }
     }
     Ok(results)
@@ -188,18 +355,25 @@ pub fn transform<T: HostType>(
     let mut index = 0;
     let mut results: Vec<Location<Token>> = vec![];
     while let Some(Location(token, position)) = input.get(index) {
-        if token == &Token::Backtick {
+        // A backtick directly after a name starts a Token Transform, any other backtick quotes an expression.
+        let transformer_name = match index.checked_sub(1).and_then(|previous| input.get(previous)) {
+            Some(Location(Token::Name(name), name_position))
+                if token == &Token::Backtick && name_position + name.len() == *position =>
+            {
+                Some((name, name_position))
+            }
+            _ => None,
+        };
+        if let Some((name, name_position)) = transformer_name {
             let mut internal_results: Vec<Location<Token>> = vec![];
-            let transformer = match input.get(index - 1) {
-                Some(Location(Token::Name(name), _)) => match bindings.read_token_transformer(name) {
-                    Some(transformer) => transformer,
-                    None => {
-                        return Err(WanderError(format!(
-                            "{name} Token Transformer doesn't exist."
-                        )))
-                    }
-                },
-                _ => return Err(WanderError("Token Transforms require a name.".to_owned())),
+            let transformer = match bindings.read_token_transformer(name) {
+                Some(transformer) => transformer,
+                None => {
+                    return Err(WanderError::at(
+                        format!("{name} Token Transformer doesn't exist"),
+                        *name_position,
+                    ))
+                }
             };
             results.pop(); //remove transformer's name token
             index += 1; //skip first `
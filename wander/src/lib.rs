@@ -7,21 +7,40 @@
 #![deny(missing_docs)]
 
 use std::{
+    cmp::Ordering,
     collections::{HashMap, HashSet},
     fmt::{Debug, Display, Write}, ops::Range,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
+use dependencies::{dependencies, BindingDependencies};
 use environment::Environment;
+use indexmap::IndexMap;
 use identifier::Identifier;
-use interpreter::{eval, Expression};
+use keyword::Keyword;
+use interpreter::{call_function, eval, read_path, Expression};
 use lexer::{tokenize, tokenize_and_filter, transform, Token};
-use parser::{parse, Element};
+use parser::{parse, parse_with_recovery, Element};
 use serde::{Deserialize, Serialize};
 use translation::translate;
+use typecheck::{Inference, TypeDiagnostic, WanderType};
 
+#[cfg(feature = "parallel")]
+pub mod channel;
+pub mod debugger;
+pub mod dependencies;
+pub mod dyn_host_value;
 #[doc(hidden)]
 pub mod environment;
+pub mod event;
+mod format;
 pub mod identifier;
+pub mod keyword;
 #[doc(hidden)]
 pub mod interpreter;
 #[doc(hidden)]
@@ -30,21 +49,145 @@ pub mod lexer;
 pub mod parser;
 #[doc(hidden)]
 pub mod preludes;
+pub mod reference;
+pub mod simple;
+#[cfg(feature = "parallel")]
+pub mod task;
+pub mod trace;
 #[doc(hidden)]
 pub mod translation;
+pub mod typecheck;
+
+pub use format::{fmt, fmt_with_options, format_value, try_fmt, FormatOptions};
 
 /// An error that occurs while running a Wander script.
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone)]
-pub struct WanderError(pub String);
+pub struct WanderError {
+    /// A human-readable description of the error.
+    pub message: String,
+    /// The part of the script that caused the error, if known.
+    pub span: Option<Span>,
+    /// A code identifying the kind of error, set by scripts using `Core.errorWith`.
+    pub code: Option<String>,
+}
+
+/// A range of byte offsets into a script.
+/// When only the starting position is known `end` is equal to `start`.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy)]
+pub struct Span {
+    /// Offset of the first byte of the span.
+    pub start: usize,
+    /// Offset one past the last byte of the span.
+    pub end: usize,
+}
+
+impl WanderError {
+    /// Create a WanderError without any location information.
+    pub fn new(message: String) -> WanderError {
+        WanderError {
+            message,
+            span: None,
+            code: None,
+        }
+    }
+
+    /// Create a WanderError for the given position in a script.
+    pub fn at(message: String, position: usize) -> WanderError {
+        WanderError {
+            message,
+            span: Some(Span {
+                start: position,
+                end: position,
+            }),
+            code: None,
+        }
+    }
+
+    /// Add the line and column of this error's span to its message.
+    pub fn locate(self, script: &str) -> WanderError {
+        match self.span {
+            Some(span) => {
+                let (line, column) = line_column(script, span.start);
+                WanderError {
+                    message: format!("{} at line {line}, column {column}.", self.message),
+                    ..self
+                }
+            }
+            None => self,
+        }
+    }
+}
+
+impl Display for WanderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A problem found while parsing a script with recovery, see `parser::parse_with_recovery`.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone)]
+pub struct ParseDiagnostic {
+    /// The error, with the span of the token that couldn't be parsed.
+    pub error: WanderError,
+    /// The closing token the statement seems to be missing, like `End` or `CloseParen`.
+    pub expected: Option<String>,
+    /// The token that couldn't be parsed, or None at the end of the script.
+    pub found: Option<String>,
+}
+
+/// Find the line and column, both starting at 1, of a byte offset into a script.
+pub fn line_column(script: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(script.len());
+    let before = &script[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = match before.rfind('\n') {
+        Some(newline) => before[newline + 1..].chars().count() + 1,
+        None => before.chars().count() + 1,
+    };
+    (line, column)
+}
 
 /// A combination of all the traits needed to implement a HostType.
 pub trait HostType: Debug + PartialEq + Eq + Serialize + Clone + Display + Serialize {}
 impl<T> HostType for T where T: Debug + PartialEq + Eq + Serialize + Clone + Display + Serialize {}
 
 /// A trait for the pluggable type checker used by Wander.
+/// The default implementation checks the tags of Wander's own values, `Any`, `Nothing`, `Bool`, `Int`,
+/// `Float`, `String`, `Char`, `Keyword`, `Identifier`, `Quantity`, `List`, `Tuple`, `Set`, `Record`, and `Lambda`,
+/// and optional tags ending with `?`, like `Int?`, that are also satisfied by nothing.
+/// Host applications can implement `check_host_tag` to add their own tags, for HostValues.
 pub trait TypeChecker<T: HostType> {
-    /// Called when a value is assigned to a TaggedNamed.
-    fn check(&self, value: WanderValue<T>, tag: WanderValue<T>) -> Result<bool, WanderError>;
+    /// Called when a value is bound to a tagged val or Lambda parameter, like `val x: Int = 5`,
+    /// with the tag as a String. Returning false reports that the value doesn't satisfy the tag.
+    fn check(&self, value: WanderValue<T>, tag: WanderValue<T>) -> Result<bool, WanderError> {
+        match tag {
+            WanderValue::String(tag) => check_core_tag(self, &value, &tag),
+            tag => Err(WanderError::new(format!("Invalid tag {tag}."))),
+        }
+    }
+
+    /// Called by the default `check` for tags that aren't one of Wander's own.
+    /// Unknown tags are errors by default.
+    fn check_host_tag(&self, _value: &WanderValue<T>, tag: &str) -> Result<bool, WanderError> {
+        Err(WanderError::new(format!("Unknown tag {tag}.")))
+    }
+}
+
+fn check_core_tag<T: HostType, C: TypeChecker<T> + ?Sized>(
+    checker: &C,
+    value: &WanderValue<T>,
+    tag: &str,
+) -> Result<bool, WanderError> {
+    if let Some(tag) = tag.strip_suffix('?') {
+        return match value {
+            WanderValue::Nothing => Ok(true),
+            value => check_core_tag(checker, value, tag),
+        };
+    }
+    match WanderType::from_tag(tag) {
+        Some(expected) => Ok(expected.accepts(&WanderType::of(value))),
+        None => checker.check_host_tag(value, tag),
+    }
 }
 
 /// A TypeChecker that does nothing, everything passes.
@@ -56,17 +199,26 @@ impl<T: HostType> TypeChecker<T> for EpsilonChecker {
     }
 }
 
+/// A TypeChecker that only knows the tags of Wander's own values, using the default implementation.
+pub struct CoreTypeChecker {}
+
+impl<T: HostType> TypeChecker<T> for CoreTypeChecker {}
+
 /// This is a dummy type you can use when you don't need a HostType.
+/// It has no values, so a HostValue<NoHostType> can never be created.
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone)]
-pub struct NoHostType {}
+pub enum NoHostType {}
 
 impl Display for NoHostType {
-    fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        panic!("NoHostType should never be displayed.")
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<NoHostType>")
     }
 }
 
 /// struct describing a HostFunction.
+/// The default binding has no capabilities and is neither effectful nor pure,
+/// so bindings only need to set the fields that differ with `..Default::default()`.
+#[derive(Default)]
 pub struct HostFunctionBinding {
     /// Name used to bind this HostFunction including Namespaces.
     pub name: String,
@@ -74,6 +226,14 @@ pub struct HostFunctionBinding {
     pub parameters: Vec<(String, Option<String>)>,
     /// The type of the result of this HostFunction.
     pub result: Option<String>,
+    /// Capabilities, like `net` or `fs`, that must be granted before this HostFunction can be called.
+    pub capabilities: Vec<String>,
+    /// If this HostFunction has side effects, like writing a file.
+    /// Effectful HostFunctions aren't called during a dry run.
+    pub effectful: bool,
+    /// If this HostFunction has no side effects and always returns the same result for the same arguments.
+    /// Only pure HostFunctions can be called inside `pure ... end` or while an Environment is pure.
+    pub pure: bool,
     /// The documentation for this HostFunction.
     /// Can be text or Markdown.
     pub doc_string: String,
@@ -86,15 +246,289 @@ pub trait HostFunction<T: HostType> {
     fn run(
         &self,
         arguments: &[WanderValue<T>],
-        bindings: &Environment<T>,
+        bindings: &mut Environment<T>,
     ) -> Result<WanderValue<T>, WanderError>;
     /// Get the binding information for this HostFunction.
     fn binding(&self) -> HostFunctionBinding;
 }
 
+/// Converts a WanderValue into a Rust value, so HostFunctions can check their arguments
+/// without matching on WanderValues. Also used by `#[wander_fn]` from the wander-macros crate.
+pub trait FromWanderValue<T: HostType>: Sized {
+    /// Describes the values that can be converted, like "an Int", for error messages.
+    const DESCRIPTION: &'static str;
+    /// Convert the value, or None if it can't be converted.
+    fn from_wander_value(value: &WanderValue<T>) -> Option<Self>;
+}
+
+/// Converts a Rust value into a WanderValue, used for the results of HostFunctions.
+/// Also used by `#[wander_fn]` from the wander-macros crate.
+pub trait IntoWanderValue<T: HostType> {
+    /// Convert the value.
+    fn into_wander_value(self) -> WanderValue<T>;
+}
+
+macro_rules! convert_wander_value {
+    ($type:ty, $variant:ident, $description:literal) => {
+        impl<T: HostType> FromWanderValue<T> for $type {
+            const DESCRIPTION: &'static str = $description;
+            fn from_wander_value(value: &WanderValue<T>) -> Option<Self> {
+                match value {
+                    WanderValue::$variant(value) => Some(value.clone()),
+                    _ => None,
+                }
+            }
+        }
+
+        impl<T: HostType> IntoWanderValue<T> for $type {
+            fn into_wander_value(self) -> WanderValue<T> {
+                WanderValue::$variant(self)
+            }
+        }
+    };
+}
+
+convert_wander_value!(bool, Bool, "a Bool");
+convert_wander_value!(i64, Int, "an Int");
+convert_wander_value!(f64, Float, "a Float");
+convert_wander_value!(String, String, "a String");
+convert_wander_value!(char, Char, "a Char");
+
+impl<T: HostType> FromWanderValue<T> for WanderValue<T> {
+    const DESCRIPTION: &'static str = "a value";
+    fn from_wander_value(value: &WanderValue<T>) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl<T: HostType> IntoWanderValue<T> for WanderValue<T> {
+    fn into_wander_value(self) -> WanderValue<T> {
+        self
+    }
+}
+
+impl<T: HostType, V: FromWanderValue<T>> FromWanderValue<T> for Vec<V> {
+    const DESCRIPTION: &'static str = "a List";
+    fn from_wander_value(value: &WanderValue<T>) -> Option<Self> {
+        match value {
+            WanderValue::List(values) => values.iter().map(V::from_wander_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: HostType, V: IntoWanderValue<T>> IntoWanderValue<T> for Vec<V> {
+    fn into_wander_value(self) -> WanderValue<T> {
+        WanderValue::List(self.into_iter().map(V::into_wander_value).collect())
+    }
+}
+
+impl<T: HostType, V: IntoWanderValue<T>> IntoWanderValue<T> for Option<V> {
+    fn into_wander_value(self) -> WanderValue<T> {
+        self.map_or(WanderValue::Nothing, V::into_wander_value)
+    }
+}
+
+impl<T: HostType> IntoWanderValue<T> for () {
+    fn into_wander_value(self) -> WanderValue<T> {
+        WanderValue::Nothing
+    }
+}
+
+impl<T: HostType> IntoWanderValue<T> for &str {
+    fn into_wander_value(self) -> WanderValue<T> {
+        WanderValue::String(self.to_owned())
+    }
+}
+
+macro_rules! convert_int {
+    ($type:ty) => {
+        impl<T: HostType> FromWanderValue<T> for $type {
+            const DESCRIPTION: &'static str = "an Int";
+            fn from_wander_value(value: &WanderValue<T>) -> Option<Self> {
+                match value {
+                    WanderValue::Int(value) => (*value).try_into().ok(),
+                    _ => None,
+                }
+            }
+        }
+
+        impl<T: HostType> IntoWanderValue<T> for $type {
+            fn into_wander_value(self) -> WanderValue<T> {
+                WanderValue::Int(i64::from(self))
+            }
+        }
+    };
+}
+
+convert_int!(i32);
+convert_int!(u32);
+
+// Nothing converts to None.
+impl<T: HostType, V: FromWanderValue<T>> FromWanderValue<T> for Option<V> {
+    const DESCRIPTION: &'static str = V::DESCRIPTION;
+    fn from_wander_value(value: &WanderValue<T>) -> Option<Self> {
+        match value {
+            WanderValue::Nothing => Some(None),
+            value => V::from_wander_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: HostType, V: FromWanderValue<T>> FromWanderValue<T> for IndexMap<String, V> {
+    const DESCRIPTION: &'static str = "a Record";
+    fn from_wander_value(value: &WanderValue<T>) -> Option<Self> {
+        match value {
+            WanderValue::Record(values) => values
+                .iter()
+                .map(|(name, value)| V::from_wander_value(value).map(|value| (name.clone(), value)))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: HostType, V: IntoWanderValue<T>> IntoWanderValue<T> for IndexMap<String, V> {
+    fn into_wander_value(self) -> WanderValue<T> {
+        WanderValue::Record(
+            self.into_iter()
+                .map(|(name, value)| (name, value.into_wander_value()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: HostType, V: FromWanderValue<T>> FromWanderValue<T> for HashMap<String, V> {
+    const DESCRIPTION: &'static str = "a Record";
+    fn from_wander_value(value: &WanderValue<T>) -> Option<Self> {
+        IndexMap::<String, V>::from_wander_value(value).map(|values| values.into_iter().collect())
+    }
+}
+
+// Fields are sorted by name, since a HashMap has no order.
+impl<T: HostType, V: IntoWanderValue<T>> IntoWanderValue<T> for HashMap<String, V> {
+    fn into_wander_value(self) -> WanderValue<T> {
+        let mut values: Vec<(String, V)> = self.into_iter().collect();
+        values.sort_by(|left, right| left.0.cmp(&right.0));
+        values.into_iter().collect::<IndexMap<String, V>>().into_wander_value()
+    }
+}
+
+macro_rules! convert_tuple {
+    ($($type:ident $value:ident),+) => {
+        impl<T: HostType, $($type: FromWanderValue<T>),+> FromWanderValue<T> for ($($type,)+) {
+            const DESCRIPTION: &'static str = "a Tuple";
+            fn from_wander_value(value: &WanderValue<T>) -> Option<Self> {
+                match value {
+                    WanderValue::Tuple(values) => match values.as_slice() {
+                        [$($value),+] => Some(($($type::from_wander_value($value)?,)+)),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+        }
+
+        impl<T: HostType, $($type: IntoWanderValue<T>),+> IntoWanderValue<T> for ($($type,)+) {
+            fn into_wander_value(self) -> WanderValue<T> {
+                let ($($value,)+) = self;
+                WanderValue::Tuple(vec![$($value.into_wander_value()),+])
+            }
+        }
+    };
+}
+
+convert_tuple!(A a);
+convert_tuple!(A a, B b);
+convert_tuple!(A a, B b, C c);
+convert_tuple!(A a, B b, C c, D d);
+
+/// The error returned by a `#[wander_fn]` HostFunction when its arguments can't be converted,
+/// given the descriptions of its parameters.
+#[doc(hidden)]
+pub fn requires_error(name: &str, descriptions: &[&str]) -> WanderError {
+    let requires = match descriptions {
+        [] => "no arguments".to_owned(),
+        [description] => description.to_string(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    };
+    WanderError::new(format!("`{name}` function requires {requires}."))
+}
+
+/// A record of a single call to a HostFunction, sent to an AuditSink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostFunctionCall {
+    /// The name of the HostFunction including Namespaces.
+    pub name: String,
+    /// A summary of each argument, long values are shortened.
+    pub arguments: Vec<String>,
+    /// How long the call took.
+    pub duration: Duration,
+    /// A summary of the value returned, or the error.
+    pub result: Result<String, WanderError>,
+}
+
+/// Receives a HostFunctionCall for every call a script makes to a HostFunction,
+/// so applications can trace what a script did.
+pub trait AuditSink {
+    /// Called after each HostFunction call finishes.
+    fn record(&mut self, call: HostFunctionCall);
+}
+
+impl<F: FnMut(HostFunctionCall)> AuditSink for F {
+    fn record(&mut self, call: HostFunctionCall) {
+        self(call)
+    }
+}
+
+/// Returns the value used in place of calling an effectful HostFunction during a dry run,
+/// given the HostFunction's name and arguments.
+pub type DryRunStub<T> = Box<dyn FnMut(&str, &[WanderValue<T>]) -> WanderValue<T>>;
+
+/// A call to an effectful HostFunction that was skipped during a dry run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedCall<T: HostType> {
+    /// The name of the HostFunction including Namespaces.
+    pub name: String,
+    /// The arguments the HostFunction would have been called with.
+    pub arguments: Vec<WanderValue<T>>,
+}
+
+/// Loads the source of the modules a script imports with `import Namespace from "path"`.
+pub trait ModuleResolver {
+    /// Get the Wander source of the module at the given path.
+    fn resolve(&self, path: &str) -> Result<String, WanderError>;
+}
+
+/// Module sources registered by the host application, keyed by path.
+impl ModuleResolver for HashMap<String, String> {
+    fn resolve(&self, path: &str) -> Result<String, WanderError> {
+        self.get(path)
+            .cloned()
+            .ok_or_else(|| WanderError::new("No module is registered with that path.".to_owned()))
+    }
+}
+
+/// A ModuleResolver that reads module files relative to a directory.
+pub struct FileModuleResolver {
+    /// The directory module paths are relative to.
+    pub root: PathBuf,
+}
+
+impl ModuleResolver for FileModuleResolver {
+    fn resolve(&self, path: &str) -> Result<String, WanderError> {
+        std::fs::read_to_string(self.root.join(path))
+            .map_err(|err| WanderError::new(format!("{err}.")))
+    }
+}
+
 /// Type alias used for TokenTransformers.
 pub type TokenTransformer = fn(&[Location<Token>]) -> Result<Vec<Location<Token>>, WanderError>;
 
+/// Type alias used for LiteralParsers, which convert the text of a custom literal
+/// after its prefix into a value.
+pub type LiteralParser<T> = fn(&str) -> Result<WanderValue<T>, WanderError>;
+
 /// A value of a type provided by the host application that can be accessed via Wander.
 /// Note it cannot be accessed by Wander directly, only through HostFunctions.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
@@ -106,16 +540,25 @@ pub struct HostValue<T> {
 
 /// Values in Wander programs used for Wander's implementation and interfacing between
 /// Wander and the host application.
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+/// Floats are equal when Core.compare orders them as equal, so NaN is equal to itself
+/// and `-0.0` is not equal to `0.0`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(bound(serialize = "T: Serialize + Display + Debug"))]
 pub enum WanderValue<T: Clone + PartialEq + Eq> {
     /// A Bool value.
     Bool(bool),
     /// A 64-bit signed integer value.
     Int(i64),
+    /// A 64-bit floating point value.
+    Float(f64),
     /// A String value.
     String(String),
+    /// A single Unicode character, written as `'c'`.
+    Char(char),
     /// An Identifier.
     Identifier(Identifier),
+    /// A Keyword.
+    Keyword(Keyword),
     /// The nothing value.
     Nothing,
     /// A Lambda
@@ -124,26 +567,88 @@ pub enum WanderValue<T: Clone + PartialEq + Eq> {
     List(Vec<WanderValue<T>>),
     /// A Tuple.
     Tuple(Vec<WanderValue<T>>),
-    /// A Set.
-    Set(HashSet<WanderValue<T>>),
-    /// A Record.
-    Record(HashMap<String, WanderValue<T>>),
+    /// A Set, written and serialized in a stable order.
+    Set(#[serde(serialize_with = "serialize_set")] HashSet<WanderValue<T>>),
+    /// A Record, its fields are kept in the order they were written.
+    Record(IndexMap<String, WanderValue<T>>),
     /// A HostValue.
     HostValue(HostValue<T>),
+    /// An Int or Float tagged with an Identifier naming its unit.
+    Quantity(Box<WanderValue<T>>, Identifier),
+    /// A HostFunction that has been applied to fewer arguments than it requires.
+    PartialApplication(Box<PartialApplication<T>>),
+    /// An expression that hasn't been evaluated, written as `` `expr `` or made with `Core.parse`,
+    /// and evaluated with `Core.eval`.
+    /// Quotes made with `Core.parse` keep the String they were parsed from, so errors
+    /// from evaluating them are located in it instead of in the script.
+    Quote(Box<Location<Element>>, Option<String>),
+}
+
+impl<T: Clone + PartialEq + Eq> PartialEq for WanderValue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WanderValue::Bool(left), WanderValue::Bool(right)) => left == right,
+            (WanderValue::Int(left), WanderValue::Int(right)) => left == right,
+            (WanderValue::Float(left), WanderValue::Float(right)) => {
+                compare_floats(*left, *right) == Ordering::Equal
+            }
+            (WanderValue::String(left), WanderValue::String(right)) => left == right,
+            (WanderValue::Char(left), WanderValue::Char(right)) => left == right,
+            (WanderValue::Identifier(left), WanderValue::Identifier(right)) => left == right,
+            (WanderValue::Keyword(left), WanderValue::Keyword(right)) => left == right,
+            (WanderValue::Nothing, WanderValue::Nothing) => true,
+            (
+                WanderValue::Lambda(left_name, left_input, left_output, left_body),
+                WanderValue::Lambda(right_name, right_input, right_output, right_body),
+            ) => {
+                left_name == right_name
+                    && left_input == right_input
+                    && left_output == right_output
+                    && left_body == right_body
+            }
+            (WanderValue::List(left), WanderValue::List(right)) => left == right,
+            (WanderValue::Tuple(left), WanderValue::Tuple(right)) => left == right,
+            (WanderValue::Set(left), WanderValue::Set(right)) => left == right,
+            (WanderValue::Record(left), WanderValue::Record(right)) => left == right,
+            (WanderValue::HostValue(left), WanderValue::HostValue(right)) => left == right,
+            (WanderValue::Quantity(left, left_unit), WanderValue::Quantity(right, right_unit)) => {
+                left == right && left_unit == right_unit
+            }
+            (WanderValue::PartialApplication(left), WanderValue::PartialApplication(right)) => {
+                left == right
+            }
+            (WanderValue::Quote(left, left_source), WanderValue::Quote(right, right_source)) => {
+                left == right && left_source == right_source
+            }
+            _ => false,
+        }
+    }
 }
 
+// Equality on Floats is total, see the PartialEq impl above.
+impl<T: Clone + PartialEq + Eq> Eq for WanderValue<T> {}
+
 impl<T: Clone + PartialEq + Eq> core::hash::Hash for WanderValue<T> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
     }
 }
 
-/// A struct represting a partially applied function.
-/// The function can be a Lambda or a HostFunction.
+impl<T: HostType> WanderValue<T> {
+    /// Read a value nested in Records by following a dotted path of field names like `a.b.c`.
+    pub fn get_path(&self, path: &str) -> Result<WanderValue<T>, WanderError> {
+        read_path(self.clone(), &format!("{self}"), path.split('.'))
+    }
+}
+
+/// A struct represting a partially applied HostFunction.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(bound(serialize = "T: Serialize + Display + Debug"))]
 pub struct PartialApplication<T: Clone + PartialEq + Eq> {
-    arguments: Vec<WanderValue<T>>,
-    callee: WanderValue<T>,
+    /// The name of the HostFunction being applied.
+    pub name: String,
+    /// The arguments supplied so far.
+    pub arguments: Vec<WanderValue<T>>,
 }
 
 /// Write integer.
@@ -180,6 +685,17 @@ pub fn write_string(string: &str) -> String {
     format!("\"{}\"", escaped_string)
 }
 
+/// Escape a Char value.
+pub fn write_char(character: &char) -> String {
+    match character {
+        '\'' | '\\' | '(' => format!("'\\{character}'"),
+        '\n' => "'\\n'".to_owned(),
+        '\r' => "'\\r'".to_owned(),
+        '\t' => "'\\t'".to_owned(),
+        character => format!("'{character}'"),
+    }
+}
+
 fn write_list_or_tuple_wander_value<T: Clone + Display + PartialEq + Eq + Debug>(
     open: &str,
     close: char,
@@ -198,13 +714,34 @@ fn write_list_or_tuple_wander_value<T: Clone + Display + PartialEq + Eq + Debug>
     write!(f, "{close}")
 }
 
+// Order the values of a Set by how they're written, so a Set always prints the same way.
+pub(crate) fn ordered_set<T: Clone + Display + PartialEq + Eq + Debug>(
+    contents: &HashSet<WanderValue<T>>,
+) -> Vec<&WanderValue<T>> {
+    let mut values: Vec<(String, &WanderValue<T>)> =
+        contents.iter().map(|value| (value.to_string(), value)).collect();
+    values.sort_by(|left, right| left.0.cmp(&right.0));
+    values.into_iter().map(|(_, value)| value).collect()
+}
+
+// Serialize the values of a Set in the order they're written.
+fn serialize_set<T: Clone + Display + PartialEq + Eq + Debug, S: serde::Serializer>(
+    contents: &HashSet<WanderValue<T>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    WanderValue<T>: Serialize,
+{
+    serializer.collect_seq(ordered_set(contents))
+}
+
 fn write_set<T: Clone + Display + PartialEq + Eq + Debug>(
     contents: &HashSet<WanderValue<T>>,
     f: &mut std::fmt::Formatter<'_>,
 ) -> std::fmt::Result {
     f.write_str("#(").unwrap();
     let mut i = 0;
-    for value in contents {
+    for value in ordered_set(contents) {
         write!(f, "{value}").unwrap();
         i += 1;
         if i < contents.len() {
@@ -221,8 +758,19 @@ fn write_host_value<T: Display + PartialEq + Eq>(
     write!(f, "{}", value.value)
 }
 
+fn write_partial_application<T: Clone + Display + PartialEq + Eq + Debug>(
+    application: &PartialApplication<T>,
+    f: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    write!(f, "({}", application.name)?;
+    for argument in &application.arguments {
+        write!(f, " {argument}")?;
+    }
+    write!(f, ")")
+}
+
 fn write_record<T: Clone + Display + PartialEq + Eq + Debug>(
-    contents: &HashMap<String, WanderValue<T>>,
+    contents: &IndexMap<String, WanderValue<T>>,
     f: &mut std::fmt::Formatter<'_>,
 ) -> std::fmt::Result {
     write!(f, "{{").unwrap();
@@ -242,11 +790,16 @@ impl<T: Clone + Display + PartialEq + Eq + std::fmt::Debug> Display for WanderVa
         match self {
             WanderValue::Bool(value) => write!(f, "{}", value),
             WanderValue::Int(value) => write!(f, "{}", value),
+            WanderValue::Float(value) => f.write_str(&write_float(value)),
             WanderValue::String(value) => f.write_str(&write_string(value)),
+            WanderValue::Char(value) => f.write_str(&write_char(value)),
             WanderValue::Identifier(value) => write!(f, "<{}>", value.id()),
+            WanderValue::Keyword(value) => write!(f, "{value}"),
             WanderValue::Nothing => write!(f, "nothing"),
             WanderValue::List(contents) => write_list_or_tuple_wander_value("[", ']', contents, f),
             WanderValue::HostValue(value) => write_host_value(value, f),
+            WanderValue::PartialApplication(application) => write_partial_application(application, f),
+            WanderValue::Quantity(value, unit) => write!(f, "{value}{unit}"),
             WanderValue::Tuple(contents) => {
                 write_list_or_tuple_wander_value("'(", ')', contents, f)
             }
@@ -257,32 +810,489 @@ impl<T: Clone + Display + PartialEq + Eq + std::fmt::Debug> Display for WanderVa
                 WanderValue::Lambda::<T>(p.clone(), i.clone(), o.clone(), b.clone())
             ),
             WanderValue::Set(contents) => write_set(contents, f),
+            WanderValue::Quote(quoted, _) => write!(f, "`{}", format::element_source(&quoted.0)),
         }
     }
 }
 
+/// Compare two values using Wander's total ordering, used by `Core.compare` and `List.sort`.
+///
+/// Values of different kinds are ordered by kind: nothing, Bools, Ints, Floats, Strings, Chars,
+/// Keywords, Identifiers, Quantities, Lists, Tuples, Sets, Records, and then everything else.
+/// Within a kind, `false` is before `true`, numbers are ordered by value with `-0.0` before
+/// `0.0` and NaN last, and Strings, Chars, Keywords, and Identifiers are ordered by their Unicode code points
+/// so the order never depends on the locale.
+/// Lists and Tuples are compared value by value, Sets by their sorted values,
+/// and Records by their fields sorted by name. Quantities are compared by unit and then value.
+/// Lambdas, Quotes, HostValues, and partially applied functions are ordered by how they're written.
+pub fn compare_values<T: Clone + Display + PartialEq + Eq + Debug>(
+    left: &WanderValue<T>,
+    right: &WanderValue<T>,
+) -> Ordering {
+    match (left, right) {
+        (WanderValue::Bool(left), WanderValue::Bool(right)) => left.cmp(right),
+        (WanderValue::Int(left), WanderValue::Int(right)) => left.cmp(right),
+        (WanderValue::Float(left), WanderValue::Float(right)) => compare_floats(*left, *right),
+        (WanderValue::String(left), WanderValue::String(right)) => left.cmp(right),
+        (WanderValue::Char(left), WanderValue::Char(right)) => left.cmp(right),
+        (WanderValue::Keyword(left), WanderValue::Keyword(right)) => left.name().cmp(right.name()),
+        (WanderValue::Identifier(left), WanderValue::Identifier(right)) => left.cmp(right),
+        (WanderValue::Quantity(left, left_unit), WanderValue::Quantity(right, right_unit)) => {
+            left_unit.cmp(right_unit).then_with(|| compare_values(left, right))
+        }
+        (WanderValue::List(left), WanderValue::List(right))
+        | (WanderValue::Tuple(left), WanderValue::Tuple(right)) => compare_sequences(left, right),
+        (WanderValue::Set(left), WanderValue::Set(right)) => {
+            compare_sequences(&sorted_set(left), &sorted_set(right))
+        }
+        (WanderValue::Record(left), WanderValue::Record(right)) => {
+            let left = sorted_fields(left);
+            let right = sorted_fields(right);
+            for ((left_name, left), (right_name, right)) in left.iter().zip(right.iter()) {
+                let ordering = left_name
+                    .cmp(right_name)
+                    .then_with(|| compare_values(left, right));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            left.len().cmp(&right.len())
+        }
+        _ => kind_rank(left)
+            .cmp(&kind_rank(right))
+            .then_with(|| left.to_string().cmp(&right.to_string())),
+    }
+}
+
+// -0.0 and 0.0 are distinct in total_cmp, and every NaN is placed after every number.
+fn compare_floats(left: f64, right: f64) -> Ordering {
+    match (left.is_nan(), right.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => left.total_cmp(&right),
+    }
+}
+
+fn compare_sequences<T: Clone + Display + PartialEq + Eq + Debug>(
+    left: &[WanderValue<T>],
+    right: &[WanderValue<T>],
+) -> Ordering {
+    for (left, right) in left.iter().zip(right.iter()) {
+        let ordering = compare_values(left, right);
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    left.len().cmp(&right.len())
+}
+
+fn sorted_set<T: Clone + Display + PartialEq + Eq + Debug>(
+    contents: &HashSet<WanderValue<T>>,
+) -> Vec<WanderValue<T>> {
+    let mut values: Vec<WanderValue<T>> = contents.iter().cloned().collect();
+    values.sort_by(compare_values);
+    values
+}
+
+fn sorted_fields<T: Clone + Display + PartialEq + Eq + Debug>(
+    contents: &IndexMap<String, WanderValue<T>>,
+) -> Vec<(&String, &WanderValue<T>)> {
+    let mut fields: Vec<(&String, &WanderValue<T>)> = contents.iter().collect();
+    fields.sort_by(|left, right| left.0.cmp(right.0));
+    fields
+}
+
+fn kind_rank<T: Clone + PartialEq + Eq>(value: &WanderValue<T>) -> u8 {
+    match value {
+        WanderValue::Nothing => 0,
+        WanderValue::Bool(_) => 1,
+        WanderValue::Int(_) => 2,
+        WanderValue::Float(_) => 3,
+        WanderValue::String(_) => 4,
+        WanderValue::Char(_) => 5,
+        WanderValue::Keyword(_) => 6,
+        WanderValue::Identifier(_) => 7,
+        WanderValue::Quantity(_, _) => 8,
+        WanderValue::List(_) => 9,
+        WanderValue::Tuple(_) => 10,
+        WanderValue::Set(_) => 11,
+        WanderValue::Record(_) => 12,
+        WanderValue::Lambda(_, _, _, _) => 13,
+        WanderValue::Quote(..) => 14,
+        WanderValue::PartialApplication(_) => 15,
+        WanderValue::HostValue(_) => 16,
+    }
+}
+
 /// Run a Wander script with the given Bindings.
 pub fn run<T: HostType + Display>(
     script: &str,
     bindings: &mut Environment<T>,
 ) -> Result<WanderValue<T>, WanderError> {
+    run_with_config(script, bindings, &RunConfig::default())
+}
+
+/// What Int arithmetic in the preludes does when a result doesn't fit in an Int,
+/// set for an Environment with `Environment::set_int_overflow`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IntOverflow {
+    /// Fail with an error describing the overflow, the default.
+    #[default]
+    Error,
+    /// Wrap around at the bounds of an Int.
+    Wrap,
+    /// Stop at the largest or smallest Int.
+    Saturate,
+}
+
+/// Limits on how much work a script can do, used when running untrusted scripts.
+/// Every limit is off by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunConfig {
+    /// The most expressions that can be evaluated.
+    pub max_steps: Option<usize>,
+    /// How deeply expressions can be nested while evaluating, including through function calls.
+    pub max_depth: Option<usize>,
+    /// The longest a script can run for.
+    pub max_time: Option<Duration>,
+    /// Stops the script once it's set, so a host can cancel a script from another thread.
+    pub cancel: Option<CancelFlag>,
+}
+
+// The message of the error a script fails with once its CancelFlag is set.
+pub(crate) const CANCELLED: &str = "Script was cancelled";
+
+// The message of the error a script fails with once it goes past its step limit.
+pub(crate) fn steps_exceeded(max_steps: usize) -> String {
+    format!("Script exceeded the limit of {max_steps} evaluation steps")
+}
+
+/// A flag that cancels the scripts run with it, see `RunConfig::cancel`.
+/// Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    /// Create a flag that hasn't been set.
+    pub fn new() -> CancelFlag {
+        CancelFlag::default()
+    }
+
+    /// Set the flag, scripts stop at their next evaluation step.
+    pub fn cancel(&self) {
+        self.0.store(true, AtomicOrdering::Relaxed);
+    }
+
+    /// Check if the flag has been set.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
+    }
+}
+
+impl PartialEq for CancelFlag {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancelFlag {}
+
+/// What's left of the limits of the running script, so HostFunctions doing long-running work
+/// can stop early instead of going past them, see `Environment::execution_context`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionContext {
+    /// How many more evaluation steps the script can take, if it has a step limit.
+    pub remaining_steps: Option<usize>,
+    /// When the script has to finish by, if it has a time limit.
+    pub deadline: Option<Instant>,
+    /// If the script's CancelFlag has been set.
+    pub cancelled: bool,
+}
+
+impl ExecutionContext {
+    /// Fail if the script has been cancelled, has no steps left, or is past its deadline.
+    pub fn check(&self) -> Result<(), WanderError> {
+        if self.cancelled {
+            Err(WanderError::new(CANCELLED.to_owned()))
+        } else if self.remaining_steps == Some(0) {
+            Err(WanderError::new(
+                "Script has no evaluation steps left.".to_owned(),
+            ))
+        } else if self.deadline.is_some_and(|deadline| Instant::now() > deadline) {
+            Err(WanderError::new(
+                "Script is past its time limit.".to_owned(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Run a Wander script, stopping with an error if it goes past any of the given limits.
+pub fn run_with_config<T: HostType + Display>(
+    script: &str,
+    bindings: &mut Environment<T>,
+    config: &RunConfig,
+) -> Result<WanderValue<T>, WanderError> {
+    bindings.call_run_hooks(|hooks| hooks.before_run(script));
+    let (result, stats) = match compile(script, bindings) {
+        Ok(compiled) => execute_compiled(&compiled, bindings, config),
+        Err(err) => (Err(err), RunStats::default()),
+    };
+    finish_run(script, bindings, result, stats)
+}
+
+/// Statistics about a script that finished running, passed to RunHooks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RunStats {
+    /// The number of expressions that were evaluated.
+    pub steps: usize,
+    /// How deeply expressions were nested while evaluating.
+    pub max_depth: usize,
+}
+
+/// Called around every script run in an Environment with `run`, `run_with_config`, `execute`,
+/// or `execute_with_config`, for metrics, caching, or auditing, see `Environment::add_run_hooks`.
+/// Scripts that fail to compile call the hooks with empty RunStats.
+pub trait RunHooks<T: HostType> {
+    /// Called before a script is compiled or run.
+    fn before_run(&mut self, _script: &str) {}
+    /// Called after a script finishes, with its result.
+    fn after_run(
+        &mut self,
+        _script: &str,
+        _result: &Result<WanderValue<T>, WanderError>,
+        _stats: &RunStats,
+    ) {
+    }
+    /// Called when a script fails, before `after_run`.
+    fn on_error(&mut self, _script: &str, _error: &WanderError, _stats: &RunStats) {}
+}
+
+// Call the RunHooks for a finished script.
+fn finish_run<T: HostType>(
+    script: &str,
+    bindings: &mut Environment<T>,
+    result: Result<WanderValue<T>, WanderError>,
+    stats: RunStats,
+) -> Result<WanderValue<T>, WanderError> {
+    if let Err(err) = &result {
+        bindings.call_run_hooks(|hooks| hooks.on_error(script, err, &stats));
+    }
+    bindings.call_run_hooks(|hooks| hooks.after_run(script, &result, &stats));
+    result
+}
+
+/// A script that has already been parsed and translated, so it can be executed many times.
+/// CompiledScripts can be serialized to skip compiling them again later.
+/// Dotted names that aren't bound in the script, like `Bool.not` or `record.a`, are resolved
+/// against the names bound when the script was compiled, so it should be executed with Bindings
+/// that bind the same names.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompiledScript {
+    script: String,
+    expression: Location<Expression>,
+}
+
+impl CompiledScript {
+    /// The source of the compiled script.
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+}
+
+/// Parse and translate a Wander script without running it.
+/// Token Transformers and `use` declarations are applied using the given Bindings,
+/// so the CompiledScript should be executed with Bindings that provide the same modules.
+pub fn compile<T: HostType>(
+    script: &str,
+    bindings: &Environment<T>,
+) -> Result<CompiledScript, WanderError> {
     let tokens = match tokenize_and_filter(script) {
         Ok(v) => v,
-        Err(err) => return Err(err),
+        Err(err) => return Err(err.locate(script)),
     };
     let tokens = match transform(&tokens, bindings) {
         Ok(v) => v,
-        Err(err) => return Err(err),
+        Err(err) => return Err(err.locate(script)),
     };
     let elements = match parse(tokens) {
         Ok(v) => v,
-        Err(err) => return Err(err),
+        Err(err) => return Err(err.locate(script)),
     };
-    let expression = match translate(elements) {
+    let expression = match translate(elements, bindings) {
         Ok(v) => v,
-        Err(err) => return Err(err),
+        Err(errors) => {
+            return Err(combine_errors(
+                errors.into_iter().map(|err| err.locate(script)).collect(),
+            ))
+        }
+    };
+    Ok(CompiledScript {
+        script: script.to_owned(),
+        expression,
+    })
+}
+
+/// Infer the types of a script without running it, for editors and for validating scripts ahead of time.
+/// Tagged vals and Lambda parameters, the result tags of HostFunctions, and values in the Bindings
+/// are used to find values that can't have the type they need, like a String passed to a Lambda tagged `\x: Int`.
+/// A script that can't be compiled has a single diagnostic with its error.
+pub fn check<T: HostType>(script: &str, bindings: &Environment<T>) -> Vec<TypeDiagnostic> {
+    let compiled = match compile(script, bindings) {
+        Ok(compiled) => compiled,
+        Err(error) => {
+            return vec![TypeDiagnostic {
+                error,
+                expected: None,
+                found: None,
+            }]
+        }
     };
-    eval(&expression, bindings)
+    let mut inference = Inference::new(bindings);
+    inference.infer(&compiled.expression);
+    inference
+        .diagnostics()
+        .into_iter()
+        .map(|diagnostic| TypeDiagnostic {
+            error: diagnostic.error.locate(script),
+            ..diagnostic
+        })
+        .collect()
+}
+
+/// Run a CompiledScript with the given Bindings.
+pub fn execute<T: HostType>(
+    compiled: &CompiledScript,
+    bindings: &mut Environment<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    execute_with_config(compiled, bindings, &RunConfig::default())
+}
+
+/// Run a CompiledScript, stopping with an error if it goes past any of the given limits.
+pub fn execute_with_config<T: HostType>(
+    compiled: &CompiledScript,
+    bindings: &mut Environment<T>,
+    config: &RunConfig,
+) -> Result<WanderValue<T>, WanderError> {
+    bindings.call_run_hooks(|hooks| hooks.before_run(&compiled.script));
+    let (result, stats) = execute_compiled(compiled, bindings, config);
+    finish_run(&compiled.script, bindings, result, stats)
+}
+
+fn execute_compiled<T: HostType>(
+    compiled: &CompiledScript,
+    bindings: &mut Environment<T>,
+    config: &RunConfig,
+) -> (Result<WanderValue<T>, WanderError>, RunStats) {
+    let previous = bindings.set_run_config(config.clone());
+    let result = eval(&compiled.expression, bindings);
+    let stats = bindings.restore_run_config(previous);
+    (result.map_err(|err| err.locate(&compiled.script)), stats)
+}
+
+/// Call a Lambda, HostFunction, or PartialApplication from host code with the given arguments.
+/// This lets HostFunctions accept functions as arguments, for example to implement map or filter.
+pub fn call_lambda<T: HostType>(
+    environment: &mut Environment<T>,
+    function: &WanderValue<T>,
+    arguments: &[WanderValue<T>],
+) -> Result<WanderValue<T>, WanderError> {
+    call_function(function, arguments, environment)
+}
+
+/// Convert a WanderValue into JSON.
+/// Records become objects and Lists, Tuples, and Sets become arrays.
+/// Chars become Strings, Identifiers and Keywords become Strings of their names, and Nothing becomes null.
+/// Lambdas, Quotes, HostValues, Quantities, and partially applied functions can't be converted.
+pub fn to_json<T: HostType>(value: &WanderValue<T>) -> Result<serde_json::Value, WanderError> {
+    match value {
+        WanderValue::Bool(value) => Ok(serde_json::Value::Bool(*value)),
+        WanderValue::Int(value) => Ok(serde_json::Value::from(*value)),
+        WanderValue::Float(value) => serde_json::Number::from_f64(*value)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| WanderError::new(format!("Cannot convert {value} to JSON."))),
+        WanderValue::String(value) => Ok(serde_json::Value::String(value.clone())),
+        WanderValue::Char(value) => Ok(serde_json::Value::String(value.to_string())),
+        WanderValue::Identifier(value) => Ok(serde_json::Value::String(value.id().to_owned())),
+        WanderValue::Keyword(value) => Ok(serde_json::Value::String(value.name().to_owned())),
+        WanderValue::Nothing => Ok(serde_json::Value::Null),
+        WanderValue::List(values) | WanderValue::Tuple(values) => values
+            .iter()
+            .map(to_json)
+            .collect::<Result<_, _>>()
+            .map(serde_json::Value::Array),
+        WanderValue::Set(values) => ordered_set(values)
+            .into_iter()
+            .map(to_json)
+            .collect::<Result<_, _>>()
+            .map(serde_json::Value::Array),
+        WanderValue::Record(values) => values
+            .iter()
+            .map(|(name, value)| to_json(value).map(|value| (name.clone(), value)))
+            .collect::<Result<_, _>>()
+            .map(serde_json::Value::Object),
+        WanderValue::Lambda(..)
+        | WanderValue::Quote(..)
+        | WanderValue::HostValue(_)
+        | WanderValue::Quantity(..)
+        | WanderValue::PartialApplication(_) => Err(WanderError::new(format!(
+            "Cannot convert {value} to JSON."
+        ))),
+    }
+}
+
+/// Convert JSON into a WanderValue.
+/// Objects become Records and arrays become Lists.
+/// Numbers that fit in an i64 become Ints, all other numbers become Floats.
+pub fn from_json<T: HostType>(value: &serde_json::Value) -> WanderValue<T> {
+    match value {
+        serde_json::Value::Null => WanderValue::Nothing,
+        serde_json::Value::Bool(value) => WanderValue::Bool(*value),
+        serde_json::Value::Number(value) => match value.as_i64() {
+            Some(value) => WanderValue::Int(value),
+            None => WanderValue::Float(value.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(value) => WanderValue::String(value.clone()),
+        serde_json::Value::Array(values) => WanderValue::List(values.iter().map(from_json).collect()),
+        serde_json::Value::Object(values) => WanderValue::Record(
+            values
+                .iter()
+                .map(|(name, value)| (name.clone(), from_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+impl<T: HostType> TryFrom<&WanderValue<T>> for serde_json::Value {
+    type Error = WanderError;
+
+    fn try_from(value: &WanderValue<T>) -> Result<Self, Self::Error> {
+        to_json(value)
+    }
+}
+
+impl<T: HostType> From<&serde_json::Value> for WanderValue<T> {
+    fn from(value: &serde_json::Value) -> Self {
+        from_json(value)
+    }
+}
+
+/// Combine a list of WanderErrors into a single WanderError, one message per line.
+/// The combined error keeps the span and code of the first error.
+pub fn combine_errors(errors: Vec<WanderError>) -> WanderError {
+    let span = errors.first().and_then(|err| err.span);
+    let code = errors.first().and_then(|err| err.code.clone());
+    WanderError {
+        message: errors
+            .into_iter()
+            .map(|err| err.message)
+            .collect::<Vec<String>>()
+            .join("\n"),
+        span,
+        code,
+    }
 }
 
 #[derive(Debug, Serialize, Clone, PartialEq, Eq, Deserialize, Hash)]
@@ -298,10 +1308,18 @@ pub struct Introspection {
     pub tokens: Vec<Location<Token>>,
     /// A list of all Tokens after macro transformations.
     pub tokens_transformed: Vec<Location<Token>>,
-    /// Element representation.
+    /// Element representation, only the statements that could be parsed are included.
     pub element: Location<Element>,
-    /// Expression representation.
+    /// Problems found while parsing.
+    pub diagnostics: Vec<ParseDiagnostic>,
+    /// Expression representation, nothing when the statements couldn't be translated.
     pub expression: Location<Expression>,
+    /// Errors translating the statements that could be parsed.
+    pub translation_errors: Vec<WanderError>,
+    /// Problems found by inferring the types of the Expression, see `check`.
+    pub type_diagnostics: Vec<TypeDiagnostic>,
+    /// The HostFunctions and outer names each top-level binding depends on.
+    pub dependencies: Vec<BindingDependencies>,
 }
 
 /// Run a Wander script with the given Bindings.
@@ -312,13 +1330,41 @@ pub fn introspect<T: HostType>(
     let tokens_ws = tokenize(script).or(Ok(vec![]))?;
     let tokens = tokenize_and_filter(script).or(Ok(vec![]))?;
     let tokens_transformed = transform(&tokens.clone(), bindings).or(Ok(vec![]))?;
-    let element = parse(tokens_transformed.clone()).or(Ok(Location(Element::Nothing, 0)))?; //TODO handle errors better
-    let expression = translate(element.clone()).or(Ok(Location(Expression::Nothing, 0)))?; //TODO handle errors better
+    let (element, diagnostics) = parse_with_recovery(tokens_transformed.clone());
+    let diagnostics = diagnostics
+        .into_iter()
+        .map(|diagnostic| ParseDiagnostic {
+            error: diagnostic.error.locate(script),
+            ..diagnostic
+        })
+        .collect();
+    let (expression, translation_errors) = match translate(element.clone(), bindings) {
+        Ok(expression) => (expression, vec![]),
+        Err(errors) => (
+            Location(Expression::Nothing, 0),
+            errors.into_iter().map(|error| error.locate(script)).collect(),
+        ),
+    };
+    let mut inference = Inference::new(bindings);
+    inference.infer(&expression);
+    let type_diagnostics = inference
+        .diagnostics()
+        .into_iter()
+        .map(|diagnostic| TypeDiagnostic {
+            error: diagnostic.error.locate(script),
+            ..diagnostic
+        })
+        .collect();
+    let dependencies = dependencies(&expression, bindings);
     Ok(Introspection {
         tokens_ws,
         tokens,
         tokens_transformed,
         element,
+        diagnostics,
         expression,
+        translation_errors,
+        type_diagnostics,
+        dependencies,
     })
 }
@@ -2,18 +2,23 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{identifier::Identifier, lexer::Token, WanderError, Location};
+use crate::{
+    identifier::Identifier, keyword::Keyword, lexer::Token, Location, ParseDiagnostic, WanderError,
+};
 use gaze::Gaze;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use indexmap::IndexMap;
 
 #[doc(hidden)]
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub enum Element {
     Boolean(bool),
     Int(i64),
+    Float(f64),
     String(String),
+    Char(char),
     Identifier(Identifier),
+    Keyword(Keyword),
     Name(String),
     TaggedName(String, Box<Location<Element>>),
     HostFunction(String),
@@ -23,12 +28,52 @@ pub enum Element {
     Lambda(String, Option<String>, Option<String>, Box<Location<Element>>),
     Tuple(Vec<Location<Element>>),
     List(Vec<Location<Element>>),
-    Set(HashSet<Location<Element>>),
-    Record(HashMap<String, Location<Element>>),
+    Set(Vec<Location<Element>>),
+    Record(IndexMap<String, Location<Element>>),
+    RecordUpdate(Box<Location<Element>>, IndexMap<String, Location<Element>>),
     Nothing,
     Pipe,
+    TailCall(String, Vec<Location<Element>>),
+    Infix(String, Box<Location<Element>>, Box<Location<Element>>),
+    Prefix(String, Box<Location<Element>>),
+    FieldAccess(Box<Location<Element>>, String),
+    Match(Box<Location<Element>>, Vec<(Pattern, Location<Element>)>),
+    Sequence(Vec<Location<Element>>),
+    Try(Box<Location<Element>>, String, Box<Location<Element>>),
+    TryResult(Box<Location<Element>>),
+    Ensure(Box<Location<Element>>, Box<Location<Element>>),
+    Pure(Box<Location<Element>>),
+    CustomLiteral(String),
+    Quantity(Box<Location<Element>>, Identifier),
+    Quote(Box<Location<Element>>),
+    Unquote(Box<Location<Element>>),
+    Splice(Box<Location<Element>>),
+    Use(String, Option<String>, Box<Location<Element>>),
+    Import(String, String, Box<Location<Element>>),
 }
 
+#[doc(hidden)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum Pattern {
+    Wildcard,
+    Name(String),
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Char(char),
+    Identifier(Identifier),
+    Keyword(Keyword),
+    Nothing,
+    Tuple(Vec<Pattern>),
+    List(Vec<Pattern>),
+    Record(IndexMap<String, Pattern>),
+}
+
+impl Eq for Pattern {}
+
+impl Eq for Element {}
+
 impl core::hash::Hash for Element {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
@@ -49,6 +94,23 @@ fn int(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     }
 }
 
+fn float(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    match gaze.next() {
+        Some(Location(Token::Float(value), position)) => Some(Location(Element::Float(value), position)),
+        _ => None,
+    }
+}
+
+// A number with the Identifier naming its unit written directly after it, like `5<minutes>`.
+fn quantity(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let (value, unit, position) = match gaze.next() {
+        Some(Location(Token::IntQuantity((value, unit)), position)) => (Element::Int(value), unit, position),
+        Some(Location(Token::FloatQuantity((value, unit)), position)) => (Element::Float(value), unit, position),
+        _ => return None,
+    };
+    Some(Location(Element::Quantity(Box::new(Location(value, position)), unit), position))
+}
+
 fn identifier(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     match gaze.next() {
         Some(Location(Token::Identifier(value), position)) => Some(Location(Element::Identifier(value), position)),
@@ -56,6 +118,22 @@ fn identifier(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     }
 }
 
+fn keyword(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    match gaze.next() {
+        Some(Location(Token::Keyword(value), position)) => Some(Location(Element::Keyword(value), position)),
+        _ => None,
+    }
+}
+
+fn custom_literal(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    match gaze.next() {
+        Some(Location(Token::CustomLiteral(value), position)) => {
+            Some(Location(Element::CustomLiteral(value), position))
+        }
+        _ => None,
+    }
+}
+
 fn string(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     match gaze.next() {
         Some(Location(Token::String(value), position)) => Some(Location(Element::String(value), position)),
@@ -63,6 +141,13 @@ fn string(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     }
 }
 
+fn character(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    match gaze.next() {
+        Some(Location(Token::Char(value), position)) => Some(Location(Element::Char(value), position)),
+        _ => None,
+    }
+}
+
 fn nothing(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     match gaze.next() {
         Some(Location(Token::Nothing, position)) | Some(Location(Token::QuestionMark, position)) => Some(Location(Element::Nothing, position)),
@@ -84,6 +169,223 @@ fn name(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     }
 }
 
+// A single step in a do block, either an expression or `val name = expression`.
+enum DoStep {
+    Expression(Location<Element>),
+    Bind(String, Option<String>, Location<Element>),
+}
+
+// Parse `do e1; val x = e2; e3 end`, desugared into nested Lets and Sequences.
+fn do_block(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let position = match gaze.next() {
+        Some(Location(Token::Do, position)) => position,
+        _ => return None,
+    };
+    let mut steps = vec![];
+    loop {
+        match gaze.peek() {
+            Some(Location(Token::End, _)) => {
+                gaze.next();
+                break;
+            }
+            Some(Location(Token::Val, _)) => {
+                gaze.next();
+                let (name, tag, value) = gaze.attemptf(&mut val_binding)?;
+                steps.push(DoStep::Bind(name, tag, value));
+            }
+            _ => steps.push(DoStep::Expression(gaze.attemptf(&mut element)?)),
+        }
+        match gaze.peek() {
+            Some(Location(Token::Semicolon, _)) => {
+                gaze.next();
+            }
+            Some(Location(Token::End, _)) => (),
+            _ => return None,
+        }
+    }
+    let mut result = Location(Element::Nothing, position);
+    let mut last = true;
+    while let Some(step) = steps.pop() {
+        result = match step {
+            DoStep::Expression(value) if last => value,
+            DoStep::Expression(value) => {
+                let position = value.1;
+                Location(Element::Sequence(vec![value, result]), position)
+            }
+            DoStep::Bind(name, tag, value) => {
+                let position = value.1;
+                let body = if last {
+                    Location(Element::Name(name.clone()), position)
+                } else {
+                    result
+                };
+                Location(Element::Let(vec![(name, tag, value)], Box::new(body)), position)
+            }
+        };
+        last = false;
+    }
+    Some(result)
+}
+
+// Parse `try expr handle err -> fallback end` or `try expr end` which evaluates to a Result.
+fn try_expression(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let position = match gaze.next() {
+        Some(Location(Token::Try, position)) => position,
+        _ => return None,
+    };
+    let body = gaze.attemptf(&mut element)?;
+    match gaze.next() {
+        Some(Location(Token::Handle, _)) => (),
+        Some(Location(Token::End, _)) => {
+            return Some(Location(Element::TryResult(Box::new(body)), position))
+        }
+        _ => return None,
+    }
+    let name = match gaze.next() {
+        Some(Location(Token::Name(name), _)) => name,
+        _ => return None,
+    };
+    match gaze.next() {
+        Some(Location(Token::Arrow, _)) => (),
+        _ => return None,
+    }
+    let handler = gaze.attemptf(&mut element)?;
+    match gaze.next() {
+        Some(Location(Token::End, _)) => {
+            Some(Location(Element::Try(Box::new(body), name, Box::new(handler)), position))
+        }
+        _ => None,
+    }
+}
+
+// Parse `ensure expr finally cleanup end`.
+fn ensure_expression(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let position = match gaze.next() {
+        Some(Location(Token::Ensure, position)) => position,
+        _ => return None,
+    };
+    let body = gaze.attemptf(&mut element)?;
+    match gaze.next() {
+        Some(Location(Token::Finally, _)) => (),
+        _ => return None,
+    }
+    let cleanup = gaze.attemptf(&mut element)?;
+    match gaze.next() {
+        Some(Location(Token::End, _)) => {
+            Some(Location(Element::Ensure(Box::new(body), Box::new(cleanup)), position))
+        }
+        _ => None,
+    }
+}
+
+// Parse `pure expr end`, where only pure HostFunctions can be called.
+fn pure_expression(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let position = match gaze.next() {
+        Some(Location(Token::Pure, position)) => position,
+        _ => return None,
+    };
+    let body = gaze.attemptf(&mut element)?;
+    match gaze.next() {
+        Some(Location(Token::End, _)) => Some(Location(Element::Pure(Box::new(body)), position)),
+        _ => None,
+    }
+}
+
+fn match_expression(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let position = match gaze.next() {
+        Some(Location(Token::Match, position)) => position,
+        _ => return None,
+    };
+    let value = gaze.attemptf(&mut element)?;
+    match gaze.next() {
+        Some(Location(Token::With, _)) => (),
+        _ => return None,
+    }
+    let mut arms = vec![];
+    while let Some(arm) = gaze.attemptf(&mut match_arm) {
+        arms.push(arm);
+    }
+    match gaze.next() {
+        Some(Location(Token::End, _)) if !arms.is_empty() => {
+            Some(Location(Element::Match(Box::new(value), arms), position))
+        }
+        _ => None,
+    }
+}
+
+fn match_arm(gaze: &mut Gaze<Location<Token>>) -> Option<(Pattern, Location<Element>)> {
+    match gaze.next() {
+        Some(Location(Token::Pipe, _)) => (),
+        _ => return None,
+    }
+    let pattern = gaze.attemptf(&mut pattern)?;
+    match gaze.next() {
+        Some(Location(Token::Arrow, _)) => (),
+        _ => return None,
+    }
+    gaze.attemptf(&mut element).map(|body| (pattern, body))
+}
+
+fn pattern(gaze: &mut Gaze<Location<Token>>) -> Option<Pattern> {
+    match gaze.next() {
+        Some(Location(Token::Name(name), _)) => {
+            if name == "_" {
+                Some(Pattern::Wildcard)
+            } else {
+                Some(Pattern::Name(name))
+            }
+        }
+        Some(Location(Token::Boolean(value), _)) => Some(Pattern::Boolean(value)),
+        Some(Location(Token::Int(value), _)) => Some(Pattern::Int(value)),
+        Some(Location(Token::Float(value), _)) => Some(Pattern::Float(value)),
+        Some(Location(Token::String(value), _)) => Some(Pattern::String(value)),
+        Some(Location(Token::Char(value), _)) => Some(Pattern::Char(value)),
+        Some(Location(Token::Identifier(value), _)) => Some(Pattern::Identifier(value)),
+        Some(Location(Token::Keyword(value), _)) => Some(Pattern::Keyword(value)),
+        Some(Location(Token::Nothing, _)) | Some(Location(Token::QuestionMark, _)) => Some(Pattern::Nothing),
+        Some(Location(Token::OpenSquare, _)) => {
+            let mut contents = vec![];
+            while let Some(p) = gaze.attemptf(&mut pattern) {
+                contents.push(p);
+            }
+            match gaze.next() {
+                Some(Location(Token::CloseSquare, _)) => Some(Pattern::List(contents)),
+                _ => None,
+            }
+        }
+        Some(Location(Token::SingleQuote, _)) => {
+            match gaze.next() {
+                Some(Location(Token::OpenParen, _)) => (),
+                _ => return None,
+            }
+            let mut contents = vec![];
+            while let Some(p) = gaze.attemptf(&mut pattern) {
+                contents.push(p);
+            }
+            match gaze.next() {
+                Some(Location(Token::CloseParen, _)) => Some(Pattern::Tuple(contents)),
+                _ => None,
+            }
+        }
+        Some(Location(Token::OpenBrace, _)) => {
+            let mut contents = IndexMap::new();
+            while let Some(Location(Token::Name(name), _)) = gaze.peek() {
+                gaze.next();
+                match gaze.next() {
+                    Some(Location(Token::EqualSign, _)) => (),
+                    _ => return None,
+                }
+                contents.insert(name, gaze.attemptf(&mut pattern)?);
+            }
+            match gaze.next() {
+                Some(Location(Token::CloseBrace, _)) => Some(Pattern::Record(contents)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 fn let_scope(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     let position = match gaze.next() {
         Some(Location(Token::Let, position)) => position,
@@ -91,8 +393,15 @@ fn let_scope(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     };
 
     let mut decls = vec![];
-    while let Some(element) = gaze.attemptf(&mut val_binding) {
-        decls.push(element);
+    let mut uses = vec![];
+    loop {
+        if let Some(element) = gaze.attemptf(&mut val_binding) {
+            decls.push(element);
+        } else if let Some(declaration) = gaze.attemptf(&mut use_declaration) {
+            uses.push((decls.len(), declaration));
+        } else {
+            break;
+        }
     }
 
     match gaze.next() {
@@ -104,23 +413,128 @@ fn let_scope(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
             };
 
             match gaze.next() {
-                Some(Location(Token::End, position)) => Some(Location(Element::Let(decls, Box::new(body)), position)),
+                Some(Location(Token::End, position)) => Some(let_with_uses(decls, uses, body, position)),
                 _ => None,
             }
         }
-        _ => Some(Location(Element::Let(decls, Box::new(Location(Element::Nothing, 0))), position)),
+        _ => Some(let_with_uses(decls, uses, Location(Element::Nothing, 0), position)),
+    }
+}
+
+// The namespace, alias, and position of a `use` declaration.
+type UseDeclaration = (String, Option<String>, usize);
+
+// A `use` inside of a let block applies to the declarations after it and the body,
+// so the declarations are split into nested Lets around each `use`.
+fn let_with_uses(
+    mut decls: Vec<(String, Option<String>, Location<Element>)>,
+    mut uses: Vec<(usize, UseDeclaration)>,
+    body: Location<Element>,
+    position: usize,
+) -> Location<Element> {
+    let mut body = body;
+    while let Some((index, (namespace, alias, use_position))) = uses.pop() {
+        let inner = decls.split_off(index);
+        body = Location(Element::Let(inner, Box::new(body)), position);
+        body = Location(Element::Use(namespace, alias, Box::new(body)), use_position);
     }
+    Location(Element::Let(decls, Box::new(body)), position)
 }
 
 fn grouping(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    operator_expression(gaze, 0)
+}
+
+// The binding power of each infix operator, higher binds tighter.
+fn infix_operator(token: &Token) -> Option<(&'static str, u8)> {
+    match token {
+        Token::Or => Some(("||", 1)),
+        Token::And => Some(("&&", 2)),
+        Token::Equal => Some(("==", 3)),
+        Token::NotEqual => Some(("!=", 3)),
+        Token::OpenAngle => Some(("<", 4)),
+        Token::CloseAngle => Some((">", 4)),
+        Token::LessEqual => Some(("<=", 4)),
+        Token::GreaterEqual => Some((">=", 4)),
+        Token::PlusPlus => Some(("++", 5)),
+        Token::Plus => Some(("+", 6)),
+        Token::Minus => Some(("-", 6)),
+        Token::Star => Some(("*", 7)),
+        Token::Slash => Some(("/", 7)),
+        _ => None,
+    }
+}
+
+// Parse applications separated by infix operators using precedence climbing.
+// All operators are left associative.
+fn operator_expression(gaze: &mut Gaze<Location<Token>>, min_precedence: u8) -> Option<Location<Element>> {
+    let mut left = gaze.attemptf(&mut application)?;
+    while let Some(Location(token, position)) = gaze.peek() {
+        let (operator, precedence) = match infix_operator(&token) {
+            Some((operator, precedence)) if precedence >= min_precedence => (operator, precedence),
+            _ => break,
+        };
+        gaze.next();
+        let right = gaze.attemptf(&mut |gaze: &mut Gaze<Location<Token>>| {
+            operator_expression(gaze, precedence + 1)
+        })?;
+        left = Location(
+            Element::Infix(operator.to_owned(), Box::new(left), Box::new(right)),
+            position,
+        );
+    }
+    Some(left)
+}
+
+// A quote, written with a backtick, applies to the single element that follows it.
+fn quote(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let position = match gaze.next() {
+        Some(Location(Token::Backtick, position)) => position,
+        _ => return None,
+    };
+    gaze.attemptf(&mut element_inner)
+        .map(|quoted| Location(Element::Quote(Box::new(quoted)), position))
+}
+
+// Inside a quote, `~` unquotes the single element that follows it
+// and `~@` splices the values of the List that follows it.
+fn unquote(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let (splice, position) = match gaze.next() {
+        Some(Location(Token::Tilde, position)) => (false, position),
+        Some(Location(Token::TildeAt, position)) => (true, position),
+        _ => return None,
+    };
+    let unquoted = Box::new(gaze.attemptf(&mut element_inner)?);
+    match splice {
+        true => Some(Location(Element::Splice(unquoted), position)),
+        false => Some(Location(Element::Unquote(unquoted), position)),
+    }
+}
+
+// A prefix operator applies to the single element that follows it.
+fn prefix(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let (operator, position) = match gaze.next() {
+        Some(Location(Token::Bang, position)) => ("!", position),
+        Some(Location(Token::Minus, position)) => ("-", position),
+        _ => return None,
+    };
+    gaze.attemptf(&mut element_inner)
+        .map(|operand| Location(Element::Prefix(operator.to_owned(), Box::new(operand)), position))
+}
+
+fn application(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     let mut expressions: Vec<Location<Element>> = vec![];
     let position = match gaze.peek() {
         Some(Location(_, p)) => p,
         None => return None,
     };
 
-    while let Some(e) = gaze.attemptf(&mut element_inner) {
-        expressions.push(e);
+    // Only the first element can be negated with `-`, after that it is subtraction.
+    while expressions.is_empty() || !matches!(gaze.peek(), Some(Location(Token::Minus, _))) {
+        match gaze.attemptf(&mut element_inner) {
+            Some(e) => expressions.push(e),
+            None => break,
+        }
     }
 
     match &expressions[..] {
@@ -137,8 +551,10 @@ fn grouped_application(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Elem
         _ => return None,
     };
 
-    while let Some(e) = gaze.attemptf(&mut element_inner) {
-        elements.push(e);
+    match gaze.attemptf(&mut grouping) {
+        Some(Location(Element::Grouping(mut contents), _)) => elements.append(&mut contents),
+        Some(element) => elements.push(element),
+        None => (),
     }
 
     match gaze.next() {
@@ -166,20 +582,23 @@ fn conditional(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
         Some(d) => d,
         None => return None,
     };
-    if let Some(Location(Token::Else, position)) = gaze.next() {
+    if let Some(Location(Token::Else, _)) = gaze.next() {
         //do nothing
     } else {
         return None;
     }
-    let elsee = match gaze.attemptf(&mut element) {
-        Some(d) => d,
-        None => return None,
-    };
-    if let Some(Location(Token::End, _)) = gaze.next() {
-        //do nothing
+    // `else if` continues the chain and shares its `end`,
+    // a nested conditional in the else branch has to be grouped.
+    let elsee = if let Some(Location(Token::If, _)) = gaze.peek() {
+        gaze.attemptf(&mut conditional)?
     } else {
-        return None;
-    }
+        let elsee = gaze.attemptf(&mut element)?;
+        if let Some(Location(Token::End, _)) = gaze.next() {
+            elsee
+        } else {
+            return None;
+        }
+    };
     Some(Location(Element::Conditional(
         Box::new(cond),
         Box::new(ife),
@@ -196,14 +615,20 @@ fn lambda(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     let mut params: Vec<(String, Option<String>)> = vec![];
 
     while let Some(Location(Element::Name(name), _)) = gaze.attemptf(&mut name) {
-        let tag = if let Some(Location(Token::Colon, _)) = gaze.peek() {
-            gaze.next();
-            match gaze.next() {
-                Some(Location(Token::Name(name), _)) => Some(name),
-                _ => return None, //no match
+        let tag = match gaze.peek() {
+            Some(Location(Token::Colon, _)) => {
+                gaze.next();
+                match gaze.next() {
+                    Some(Location(Token::Name(name), _)) => Some(name),
+                    _ => return None, //no match
+                }
             }
-        } else {
-            None
+            // `x:Int` is lexed as a name followed by a Keyword.
+            Some(Location(Token::Keyword(tag), _)) => {
+                gaze.next();
+                Some(tag.name().to_owned())
+            }
+            _ => None,
         };
         params.push((name, tag));
     }
@@ -263,7 +688,16 @@ fn record(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
         _ => return None,
     };
 
-    let mut contents = HashMap::new();
+    // `{ base with field = value }` copies base with the given fields replaced.
+    let base = gaze.attemptf(&mut |gaze: &mut Gaze<Location<Token>>| {
+        let base = element_inner(gaze)?;
+        match gaze.next() {
+            Some(Location(Token::With, _)) => Some(base),
+            _ => None,
+        }
+    });
+
+    let mut contents = IndexMap::new();
     while let Some(Location(Element::Name(name), _)) = gaze.attemptf(&mut name) {
         match gaze.next() {
             Some(Location(Token::EqualSign, _)) => (),
@@ -276,7 +710,10 @@ fn record(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     }
 
     match gaze.next() {
-        Some(Location(Token::CloseBrace, _)) => Some(Location(Element::Record(contents), position)),
+        Some(Location(Token::CloseBrace, _)) => match base {
+            Some(base) => Some(Location(Element::RecordUpdate(Box::new(base), contents), position)),
+            None => Some(Location(Element::Record(contents), position)),
+        },
         _ => None,
     }
 }
@@ -292,9 +729,9 @@ fn set(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
         _ => return None,
     }
 
-    let mut contents = HashSet::new();
+    let mut contents = vec![];
     while let Some(e) = gaze.attemptf(&mut element_inner) {
-        contents.insert(e);
+        contents.push(e);
     }
 
     match gaze.next() {
@@ -339,6 +776,10 @@ fn val_binding(gaze: &mut Gaze<Location<Token>>) -> Option<(String, Option<Strin
                 return None;
             }
         }
+        Some(Location(Token::Keyword(tag), _)) => {
+            gaze.next();
+            Some(tag.name().to_owned())
+        }
         _ => None,
     };
 
@@ -350,8 +791,31 @@ fn val_binding(gaze: &mut Gaze<Location<Token>>) -> Option<(String, Option<Strin
     gaze.attemptf(&mut element).map(|body| (name, tag, body))
 }
 
-//this function is basically the same as element inner but it matches name instead of application
+// Parse a primary element followed by any number of `.field` accesses.
 fn element_inner(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    let mut result = gaze.attemptf(&mut primary)?;
+    while let Some(Location(Token::Period, position)) = gaze.peek() {
+        let fields = gaze.attemptf(&mut |gaze: &mut Gaze<Location<Token>>| {
+            gaze.next();
+            match gaze.next() {
+                Some(Location(Token::Name(fields), _)) => Some(fields),
+                _ => None,
+            }
+        });
+        match fields {
+            Some(fields) => {
+                for field in fields.split('.') {
+                    result = Location(Element::FieldAccess(Box::new(result), field.to_owned()), position);
+                }
+            }
+            None => break,
+        }
+    }
+    Some(result)
+}
+
+//this function is basically the same as element inner but it matches name instead of application
+fn primary(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     let mut parsers = vec![
         tuple,
         set,
@@ -359,14 +823,27 @@ fn element_inner(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>>
         name,
         boolean,
         nothing,
+        quantity,
         int,
+        float,
         identifier,
+        keyword,
+        custom_literal,
         string,
+        character,
         let_scope,
         grouped_application,
         conditional,
+        match_expression,
+        do_block,
+        try_expression,
+        ensure_expression,
+        pure_expression,
         lambda,
         list,
+        prefix,
+        quote,
+        unquote,
     ];
     for &mut mut parser in parsers.iter_mut() {
         if let Some(element) = gaze.attemptf(&mut parser) {
@@ -377,7 +854,7 @@ fn element_inner(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>>
 }
 
 fn element(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
-    let mut parsers = vec![pipe, let_scope, grouping, grouped_application, conditional];
+    let mut parsers = vec![pipe, let_scope, grouping, grouped_application, conditional, match_expression, do_block, try_expression, ensure_expression, pure_expression];
     for &mut mut parser in parsers.iter_mut() {
         if let Some(element) = gaze.attemptf(&mut parser) {
             return Some(element);
@@ -386,29 +863,223 @@ fn element(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
     None
 }
 
-fn elements(gaze: &mut Gaze<Location<Token>>) -> Option<Vec<Location<Element>>> {
-    let mut results = vec![];
-    while !gaze.is_complete() {
-        if let Some(element) = gaze.attemptf(&mut element) {
-            results.push(element);
-        } else {
-            return None;
-        }
+// Parse `use Namespace as Alias` or `use Namespace.*`, returning the namespace, alias, and position.
+// The alias is None for `use Namespace.*`.
+fn use_declaration(gaze: &mut Gaze<Location<Token>>) -> Option<UseDeclaration> {
+    let position = match gaze.next() {
+        Some(Location(Token::Use, position)) => position,
+        _ => return None,
+    };
+    let namespace = match gaze.next() {
+        Some(Location(Token::Name(namespace), _)) => namespace,
+        _ => return None,
+    };
+    if let Some(namespace) = namespace.strip_suffix('.') {
+        return match gaze.next() {
+            Some(Location(Token::Star, _)) => Some((namespace.to_owned(), None, position)),
+            _ => None,
+        };
+    }
+    match gaze.next() {
+        Some(Location(Token::As, _)) => (),
+        _ => return None,
+    }
+    match gaze.next() {
+        Some(Location(Token::Name(alias), _)) => Some((namespace, Some(alias), position)),
+        _ => None,
+    }
+}
+
+// The namespace, path, and position of an `import Namespace from "path"` declaration.
+type ImportDeclaration = (String, String, usize);
+
+fn import_declaration(gaze: &mut Gaze<Location<Token>>) -> Option<ImportDeclaration> {
+    let position = match gaze.next() {
+        Some(Location(Token::Import, position)) => position,
+        _ => return None,
+    };
+    let namespace = match gaze.next() {
+        Some(Location(Token::Name(namespace), _)) => namespace,
+        _ => return None,
+    };
+    match gaze.next() {
+        Some(Location(Token::From, _)) => (),
+        _ => return None,
+    }
+    match gaze.next() {
+        Some(Location(Token::String(path), _)) => Some((namespace, path, position)),
+        _ => None,
+    }
+}
+
+// A top-level `val name = value` binds the name in the current Environment and evaluates to the value,
+// so it stays bound for later scripts run with the same Environment, like inputs to the REPL.
+fn top_level_val(gaze: &mut Gaze<Location<Token>>) -> Option<Location<Element>> {
+    match gaze.next() {
+        Some(Location(Token::Val, _)) => (),
+        _ => return None,
     }
-    Some(results)
+    let (name, tag, value) = gaze.attemptf(&mut val_binding)?;
+    let position = value.1;
+    let body = Location(Element::Name(name.clone()), position);
+    Some(Location(Element::Let(vec![(name, tag, value)], Box::new(body)), position))
 }
 
 /// Parse a sequence of Tokens into a sequence of ASTs.
+/// Top-level expressions separated by `;` are evaluated in order.
+/// Top-level `use` and `import` declarations apply to the whole script.
 pub fn parse(tokens: Vec<Location<Token>>) -> Result<Location<Element>, WanderError> {
+    let (element, mut diagnostics) = parse_with_recovery(tokens);
+    if diagnostics.is_empty() {
+        Ok(element)
+    } else {
+        Err(diagnostics.remove(0).error)
+    }
+}
+
+/// Parse as much of a script as possible, for editors that show every error at once.
+/// When a statement can't be parsed it is reported and skipped up to the next `;`,
+/// so the returned Element only contains the statements that could be parsed.
+pub fn parse_with_recovery(tokens: Vec<Location<Token>>) -> (Location<Element>, Vec<ParseDiagnostic>) {
     let mut gaze = Gaze::from_vec(tokens);
-    match gaze.attemptf(&mut elements) {
-        Some(values) => {
-            if values.len() == 1 {
-                Ok(values.first().unwrap().clone())
-            } else {
-                Ok(Location(Element::Grouping(values), 0))
+    let mut diagnostics = vec![];
+    let mut uses = vec![];
+    let mut imports = vec![];
+    loop {
+        match gaze.peek() {
+            Some(Location(Token::Use, _)) => match gaze.attemptf(&mut use_declaration) {
+                Some(declaration) => uses.push(declaration),
+                None => diagnostics.push(skip_statement(&mut gaze)),
+            },
+            Some(Location(Token::Import, _)) => match gaze.attemptf(&mut import_declaration) {
+                Some(declaration) => imports.push(declaration),
+                None => diagnostics.push(skip_statement(&mut gaze)),
+            },
+            _ => break,
+        }
+        if let Some(Location(Token::Semicolon, _)) = gaze.peek() {
+            gaze.next();
+        }
+    }
+    let mut result = parse_sequence(gaze, &mut diagnostics);
+    while let Some((namespace, alias, position)) = uses.pop() {
+        result = Location(Element::Use(namespace, alias, Box::new(result)), position);
+    }
+    // Imports go around the uses, so `use` declarations don't apply to the imported modules.
+    while let Some((namespace, path, position)) = imports.pop() {
+        result = Location(Element::Import(namespace, path, Box::new(result)), position);
+    }
+    (result, diagnostics)
+}
+
+fn parse_sequence(
+    mut gaze: Gaze<Location<Token>>,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> Location<Element> {
+    let mut sequence = vec![];
+    let mut values = vec![];
+    while !gaze.is_complete() {
+        if let Some(Location(Token::Semicolon, _)) = gaze.peek() {
+            if values.is_empty() {
+                diagnostics.push(skip_statement(&mut gaze));
+                continue;
+            }
+            gaze.next();
+            sequence.push(group_elements(values));
+            values = vec![];
+            continue;
+        }
+        match gaze.attemptf(&mut top_level_val).or_else(|| gaze.attemptf(&mut element)) {
+            Some(value) => values.push(value),
+            None => {
+                diagnostics.push(skip_statement(&mut gaze));
+                values = vec![];
+            }
+        }
+    }
+    if sequence.is_empty() {
+        return match values.len() {
+            1 => values.remove(0),
+            _ => Location(Element::Grouping(values), 0),
+        };
+    }
+    if !values.is_empty() {
+        sequence.push(group_elements(values));
+    }
+    let position = sequence.first().map(|element| element.1).unwrap_or(0);
+    Location(Element::Sequence(sequence), position)
+}
+
+// Report the token that couldn't be parsed and skip past the next `;`.
+// A stray `;` is only skipped itself, so the statement after it is still parsed.
+fn skip_statement(gaze: &mut Gaze<Location<Token>>) -> ParseDiagnostic {
+    let found = gaze.peek();
+    let mut skipped = vec![];
+    if let Some(token) = gaze.next().filter(|token| token.0 != Token::Semicolon) {
+        skipped.push(token);
+        while let Some(token) = gaze.next() {
+            if let Location(Token::Semicolon, _) = token {
+                break;
             }
+            skipped.push(token);
+        }
+    }
+    ParseDiagnostic {
+        error: unexpected_token(found.clone()),
+        expected: missing_closer(&skipped).map(|token| format!("{token:?}")),
+        found: found.map(|Location(token, _)| format!("{token:?}")),
+    }
+}
+
+// The token that closes the innermost bracket or block left open in the given tokens.
+// An `if` right after `else` can share the `end` of the conditional it continues, so it doesn't open a block.
+fn missing_closer(tokens: &[Location<Token>]) -> Option<Token> {
+    let mut open = vec![];
+    let mut previous = None;
+    for Location(token, _) in tokens {
+        let closer = match token {
+            Token::OpenParen => Some(Token::CloseParen),
+            Token::OpenSquare => Some(Token::CloseSquare),
+            Token::OpenBrace => Some(Token::CloseBrace),
+            Token::If if previous == Some(&Token::Else) => None,
+            Token::Let | Token::If | Token::Match | Token::Try | Token::Ensure | Token::Do | Token::Pure => {
+                Some(Token::End)
+            }
+            _ => None,
+        };
+        match closer {
+            Some(closer) => open.push(closer),
+            None => {
+                if matches!(
+                    token,
+                    Token::CloseParen | Token::CloseSquare | Token::CloseBrace | Token::End
+                ) {
+                    match open.pop() {
+                        Some(expected) if &expected != token => return Some(expected),
+                        _ => (),
+                    }
+                }
+            }
+        }
+        previous = Some(token);
+    }
+    open.pop()
+}
+
+fn group_elements(mut elements: Vec<Location<Element>>) -> Location<Element> {
+    if elements.len() == 1 {
+        elements.remove(0)
+    } else {
+        let position = elements[0].1;
+        Location(Element::Grouping(elements), position)
+    }
+}
+
+fn unexpected_token(token: Option<Location<Token>>) -> WanderError {
+    match token {
+        Some(Location(token, position)) => {
+            WanderError::at(format!("Unexpected token {token:?}"), position)
         }
-        None => Err(WanderError(format!("Error parsing {:?}", gaze.peek()))),
+        None => WanderError::new("Unexpected end of script.".to_owned()),
     }
 }
@@ -3,21 +3,86 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::{
-    environment::Environment, HostFunction, HostFunctionBinding, HostType, WanderError, WanderValue,
+    environment::Environment,
+    identifier::Identifier,
+    interpreter::{call_function, error_record, eval, read_path},
+    lexer::{tokenize_and_filter, transform},
+    parser::parse,
+    translation::translate,
+    combine_errors, compare_values, ordered_set, CoreTypeChecker, HostFunction, HostFunctionBinding, HostType, IntOverflow, WanderError, WanderValue,
 };
-use std::rc::Rc;
+use indexmap::IndexMap;
+use std::{cmp::Ordering, rc::Rc};
+
+/// The function run for a TableFunction, returns None when the arguments aren't valid.
+pub(crate) type TableRun<T> =
+    fn(&[WanderValue<T>], &mut Environment<T>) -> Option<Result<WanderValue<T>, WanderError>>;
+
+/// A HostFunction written as a row in a table of functions, like the functions in a Namespace.
+/// When `run` returns None the error says what the function `requires`.
+pub(crate) struct TableFunction<T: HostType> {
+    pub(crate) name: &'static str,
+    pub(crate) doc_string: &'static str,
+    // Parameter names, with a tag like `value: String` for parameters that only take one type.
+    pub(crate) parameters: &'static [&'static str],
+    pub(crate) requires: &'static str,
+    pub(crate) pure: bool,
+    pub(crate) run: TableRun<T>,
+}
+impl<T: HostType> HostFunction<T> for TableFunction<T> {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        match (self.run)(arguments, bindings) {
+            Some(result) => result,
+            None => Err(WanderError::new(format!(
+                "`{}` function requires {}.",
+                self.name, self.requires
+            ))),
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.to_owned(),
+            parameters: self
+                .parameters
+                .iter()
+                .map(|parameter| match parameter.split_once(": ") {
+                    Some((name, tag)) => (name.to_owned(), Some(tag.to_owned())),
+                    None => (parameter.to_string(), None),
+                })
+                .collect(),
+            result: None,
+            pure: self.pure,
+            doc_string: self.doc_string.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+pub(crate) fn add_table_functions<T: HostType + 'static>(
+    bindings: &mut Environment<T>,
+    functions: impl IntoIterator<Item = TableFunction<T>>,
+) {
+    for function in functions {
+        bindings.bind_host_function(Rc::new(function));
+    }
+}
 
 struct EqFunction {}
 impl<T: HostType> HostFunction<T> for EqFunction {
     fn run(
         &self,
         arguments: &[WanderValue<T>],
-        _bindings: &Environment<T>,
+        _bindings: &mut Environment<T>,
     ) -> Result<WanderValue<T>, WanderError> {
         if let [left, right] = arguments {
             Ok(crate::WanderValue::Bool(left == right))
         } else {
-            Err(WanderError(
+            Err(WanderError::new(
                 "`eq` function requires two parameters.".to_owned(),
             ))
         }
@@ -27,8 +92,249 @@ impl<T: HostType> HostFunction<T> for EqFunction {
         HostFunctionBinding {
             name: "Core.eq".to_owned(),
             parameters: vec![("left".to_owned(), None), ("right".to_owned(), None)],
-            result: None,
+            result: Some("Bool".to_owned()),
+            pure: true,
             doc_string: "Check if two values are equal.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct CompareFunction {}
+impl<T: HostType> HostFunction<T> for CompareFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        if let [left, right] = arguments {
+            Ok(WanderValue::Int(compare_values(left, right) as i64))
+        } else {
+            Err(WanderError::new(
+                "`Core.compare` function requires two parameters.".to_owned(),
+            ))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Core.compare".to_owned(),
+            parameters: vec![("left".to_owned(), None), ("right".to_owned(), None)],
+            result: Some("Int".to_owned()),
+            pure: true,
+            doc_string: "Compare two values, returning -1, 0, or 1 if the first is before, the same as, or after the second.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct ErrorFunction {}
+impl<T: HostType> HostFunction<T> for ErrorFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        if let [WanderValue::String(message)] = arguments {
+            Err(WanderError::new(message.clone()))
+        } else {
+            Err(WanderError::new(
+                "`error` function requires a String message.".to_owned(),
+            ))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Core.error".to_owned(),
+            parameters: vec![("message".to_owned(), Some("String".to_owned()))],
+            result: None,
+            pure: true,
+            doc_string: "Fail with the given message.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct ErrorWithFunction {}
+impl<T: HostType> HostFunction<T> for ErrorWithFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        let requirement = || {
+            WanderError::new(
+                "`errorWith` function requires a Record with a String message and an optional String code."
+                    .to_owned(),
+            )
+        };
+        let [WanderValue::Record(record)] = arguments else {
+            return Err(requirement());
+        };
+        let Some(WanderValue::String(message)) = record.get("message") else {
+            return Err(requirement());
+        };
+        let code = match record.get("code") {
+            Some(WanderValue::String(code)) => Some(code.clone()),
+            None | Some(WanderValue::Nothing) => None,
+            Some(_) => return Err(requirement()),
+        };
+        Err(WanderError {
+            message: message.clone(),
+            span: None,
+            code,
+        })
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Core.errorWith".to_owned(),
+            parameters: vec![("error".to_owned(), Some("Record".to_owned()))],
+            result: None,
+            pure: true,
+            doc_string: "Fail with a Record containing a message and an optional code.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct VersionFunction {}
+impl<T: HostType> HostFunction<T> for VersionFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        if arguments.is_empty() {
+            Ok(WanderValue::String(env!("CARGO_PKG_VERSION").to_owned()))
+        } else {
+            Err(WanderError::new(
+                "`Core.version` function doesn't take any parameters.".to_owned(),
+            ))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Core.version".to_owned(),
+            parameters: vec![],
+            result: Some("String".to_owned()),
+            pure: true,
+            doc_string: "The version of Wander running the script, like \"0.6.0\".".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct HasBindingFunction {}
+impl<T: HostType> HostFunction<T> for HasBindingFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        if let [WanderValue::String(name)] = arguments {
+            // A HostFunction that needs capabilities that weren't granted can't be called, so it isn't bound.
+            let bound = match bindings.read_host_function(name) {
+                Some(function) => bindings.check_capabilities(&function.binding()).is_ok(),
+                None => bindings.read(name).is_some(),
+            };
+            Ok(WanderValue::Bool(bound))
+        } else {
+            Err(WanderError::new(
+                "`Core.hasBinding` function requires a String name.".to_owned(),
+            ))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Core.hasBinding".to_owned(),
+            parameters: vec![("name".to_owned(), Some("String".to_owned()))],
+            result: Some("Bool".to_owned()),
+            doc_string: "Check if a name, like \"Http.get\", is bound in the Environment, \
+                so scripts can check for the functions a host provides before using them."
+                .to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct EvalFunction {}
+impl<T: HostType> HostFunction<T> for EvalFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        let [WanderValue::Quote(quoted, source)] = arguments else {
+            return Err(WanderError::new(
+                "`Core.eval` function requires a Quote.".to_owned(),
+            ));
+        };
+        let result = translate(*quoted.clone(), bindings)
+            .map_err(combine_errors)
+            .and_then(|expression| {
+                bindings.add_scope();
+                let result = eval(&expression, bindings);
+                bindings.remove_scope();
+                result
+            });
+        // Errors from a parsed String are located in it, like parse errors, not in the calling script.
+        match source {
+            Some(source) => result.map_err(|err| WanderError {
+                span: None,
+                ..err.locate(source)
+            }),
+            None => result,
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Core.eval".to_owned(),
+            parameters: vec![("quote".to_owned(), Some("Quote".to_owned()))],
+            result: None,
+            doc_string: "Evaluate a Quote in the current Environment, \
+                values it binds are only bound while it's evaluated."
+                .to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct ParseFunction {}
+impl<T: HostType> HostFunction<T> for ParseFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        let [WanderValue::String(script)] = arguments else {
+            return Err(WanderError::new(
+                "`Core.parse` function requires a String.".to_owned(),
+            ));
+        };
+        tokenize_and_filter(script)
+            .and_then(|tokens| transform(&tokens, bindings))
+            .and_then(parse)
+            .map(|element| WanderValue::Quote(Box::new(element), Some(script.clone())))
+            // The error is located in the parsed String, not the script calling `Core.parse`.
+            .map_err(|err| WanderError {
+                span: None,
+                ..err.locate(script)
+            })
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Core.parse".to_owned(),
+            parameters: vec![("script".to_owned(), Some("String".to_owned()))],
+            result: Some("Quote".to_owned()),
+            pure: true,
+            doc_string: "Parse a String into a Quote without evaluating it.".to_owned(),
+            ..Default::default()
         }
     }
 }
@@ -38,13 +344,13 @@ impl<T: HostType> HostFunction<T> for LogFunction {
     fn run(
         &self,
         arguments: &[WanderValue<T>],
-        _bindings: &Environment<T>,
+        _bindings: &mut Environment<T>,
     ) -> Result<WanderValue<T>, WanderError> {
         if let [message] = arguments {
             println!("{message}");
             Ok(WanderValue::Nothing)
         } else {
-            Err(WanderError(
+            Err(WanderError::new(
                 "`log` function requires a message to print.".to_owned(),
             ))
         }
@@ -55,7 +361,9 @@ impl<T: HostType> HostFunction<T> for LogFunction {
             name: "log".to_owned(),
             parameters: vec![("message".to_owned(), None)],
             result: None,
+            effectful: true,
             doc_string: "Log a message.".to_owned(),
+            ..Default::default()
         }
     }
 }
@@ -65,16 +373,16 @@ impl<T: HostType> HostFunction<T> for AssertEqFunction {
     fn run(
         &self,
         arguments: &[WanderValue<T>],
-        _bindings: &Environment<T>,
+        _bindings: &mut Environment<T>,
     ) -> Result<WanderValue<T>, WanderError> {
         if let [left, right] = arguments {
             if left == right {
                 Ok(crate::WanderValue::Nothing)
             } else {
-                Err(WanderError("Assertion failed!".to_owned()))
+                Err(WanderError::new("Assertion failed!".to_owned()))
             }
         } else {
-            Err(WanderError(
+            Err(WanderError::new(
                 "`assertEq` function requires two parameters.".to_owned(),
             ))
         }
@@ -85,7 +393,9 @@ impl<T: HostType> HostFunction<T> for AssertEqFunction {
             name: "Assert.assertEq".to_owned(),
             parameters: vec![("value".to_owned(), None), ("expected".to_owned(), None)],
             result: None,
+            pure: true,
             doc_string: "Assert that two values are equal.".to_owned(),
+            ..Default::default()
         }
     }
 }
@@ -95,12 +405,12 @@ impl<T: HostType> HostFunction<T> for AndFunction {
     fn run(
         &self,
         arguments: &[WanderValue<T>],
-        _bindings: &Environment<T>,
+        _bindings: &mut Environment<T>,
     ) -> Result<crate::WanderValue<T>, WanderError> {
         if let [WanderValue::Bool(left), WanderValue::Bool(right)] = arguments {
             Ok(crate::WanderValue::Bool(*left && *right))
         } else {
-            Err(WanderError(
+            Err(WanderError::new(
                 "`and` function requires two boolean parameters.".to_owned(),
             ))
         }
@@ -110,11 +420,75 @@ impl<T: HostType> HostFunction<T> for AndFunction {
         HostFunctionBinding {
             name: "Bool.and".to_owned(),
             parameters: vec![
-                ("left".to_owned(), None),  // bool
-                ("right".to_owned(), None), // bool
+                ("left".to_owned(), Some("Bool".to_owned())),
+                ("right".to_owned(), Some("Bool".to_owned())),
             ],
-            result: None, // bool
+            result: Some("Bool".to_owned()),
+            pure: true,
             doc_string: "Check if two boolean values are both true.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct OrFunction {}
+impl<T: HostType> HostFunction<T> for OrFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<crate::WanderValue<T>, WanderError> {
+        if let [WanderValue::Bool(left), WanderValue::Bool(right)] = arguments {
+            Ok(crate::WanderValue::Bool(*left || *right))
+        } else {
+            Err(WanderError::new(
+                "`or` function requires two boolean parameters.".to_owned(),
+            ))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Bool.or".to_owned(),
+            parameters: vec![
+                ("left".to_owned(), Some("Bool".to_owned())),
+                ("right".to_owned(), Some("Bool".to_owned())),
+            ],
+            result: Some("Bool".to_owned()),
+            pure: true,
+            doc_string: "Check if either of two boolean values is true.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct XorFunction {}
+impl<T: HostType> HostFunction<T> for XorFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<crate::WanderValue<T>, WanderError> {
+        if let [WanderValue::Bool(left), WanderValue::Bool(right)] = arguments {
+            Ok(crate::WanderValue::Bool(*left != *right))
+        } else {
+            Err(WanderError::new(
+                "`xor` function requires two boolean parameters.".to_owned(),
+            ))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Bool.xor".to_owned(),
+            parameters: vec![
+                ("left".to_owned(), Some("Bool".to_owned())),
+                ("right".to_owned(), Some("Bool".to_owned())),
+            ],
+            result: Some("Bool".to_owned()),
+            pure: true,
+            doc_string: "Check if exactly one of two boolean values is true.".to_owned(),
+            ..Default::default()
         }
     }
 }
@@ -124,12 +498,12 @@ impl<T: HostType> HostFunction<T> for NotFunction {
     fn run(
         &self,
         arguments: &[WanderValue<T>],
-        _bindings: &Environment<T>,
+        _bindings: &mut Environment<T>,
     ) -> Result<crate::WanderValue<T>, WanderError> {
         if let [WanderValue::Bool(value)] = arguments {
             Ok(crate::WanderValue::Bool(!value))
         } else {
-            Err(WanderError(
+            Err(WanderError::new(
                 "`not` function requires one boolean parameter.".to_owned(),
             ))
         }
@@ -138,9 +512,740 @@ impl<T: HostType> HostFunction<T> for NotFunction {
     fn binding(&self) -> HostFunctionBinding {
         HostFunctionBinding {
             name: "Bool.not".to_owned(),
-            parameters: vec![("value".to_owned(), None)], // bool
-            result: None,                                 // bool
+            parameters: vec![("value".to_owned(), Some("Bool".to_owned()))],
+            result: Some("Bool".to_owned()),
+            pure: true,
             doc_string: "Return the opposite of the boolean value passed.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+// How an arithmetic function treats Quantities, numbers tagged with a unit like `5<minutes>`.
+#[derive(Clone, Copy)]
+enum UnitRule {
+    // Both values need the same unit, which the result keeps.
+    Same,
+    // Only one value can have a unit, which the result keeps.
+    Scale,
+    // Like Scale, but two values with the same unit give a result with no unit.
+    Ratio,
+    // Both values need the same unit, the result has no unit.
+    Compare,
+}
+
+// A pair of arguments without their units, and the unit the result should have.
+type Unitless<T> = (WanderValue<T>, WanderValue<T>, Option<Identifier>);
+
+// Remove the units from a pair of arguments, returning the unit the result should have.
+fn strip_units<T: HostType>(
+    name: &str,
+    rule: UnitRule,
+    left: &WanderValue<T>,
+    right: &WanderValue<T>,
+) -> Result<Unitless<T>, WanderError> {
+    match (left, right, rule) {
+        (
+            WanderValue::Quantity(left, left_unit),
+            WanderValue::Quantity(right, right_unit),
+            UnitRule::Same | UnitRule::Compare,
+        ) if left_unit == right_unit => {
+            let unit = match rule {
+                UnitRule::Same => Some(left_unit.clone()),
+                _ => None,
+            };
+            Ok((*left.clone(), *right.clone(), unit))
+        }
+        (
+            WanderValue::Quantity(left, left_unit),
+            WanderValue::Quantity(right, right_unit),
+            UnitRule::Ratio,
+        ) if left_unit == right_unit => Ok((*left.clone(), *right.clone(), None)),
+        (WanderValue::Quantity(_, left_unit), WanderValue::Quantity(_, right_unit), _) => {
+            Err(WanderError::new(format!(
+                "`{name}` cannot combine {left_unit} and {right_unit}."
+            )))
+        }
+        (WanderValue::Quantity(left, unit), right, UnitRule::Scale | UnitRule::Ratio) => {
+            Ok((*left.clone(), right.clone(), Some(unit.clone())))
+        }
+        (left, WanderValue::Quantity(right, unit), UnitRule::Scale | UnitRule::Ratio) => {
+            Ok((left.clone(), *right.clone(), Some(unit.clone())))
+        }
+        (WanderValue::Quantity(_, unit), _, _) | (_, WanderValue::Quantity(_, unit), _) => Err(
+            WanderError::new(format!("`{name}` requires both values to be in {unit}.")),
+        ),
+        (left, right, _) => Ok((left.clone(), right.clone(), None)),
+    }
+}
+
+fn with_unit<T: HostType>(value: WanderValue<T>, unit: Option<Identifier>) -> WanderValue<T> {
+    match unit {
+        Some(unit) => WanderValue::Quantity(Box::new(value), unit),
+        None => value,
+    }
+}
+
+// Use the checked result of an Int operation, or the wrapping or saturating result
+// if it overflowed and the Environment allows it.
+fn overflowing(
+    overflow: IntOverflow,
+    checked: Option<i64>,
+    wrapping: i64,
+    saturating: i64,
+    error: impl FnOnce() -> String,
+) -> Result<i64, String> {
+    match (checked, overflow) {
+        (Some(value), _) => Ok(value),
+        (None, IntOverflow::Error) => Err(error()),
+        (None, IntOverflow::Wrap) => Ok(wrapping),
+        (None, IntOverflow::Saturate) => Ok(saturating),
+    }
+}
+
+fn overflow_add(left: i64, right: i64, overflow: IntOverflow) -> Result<i64, String> {
+    overflowing(
+        overflow,
+        left.checked_add(right),
+        left.wrapping_add(right),
+        left.saturating_add(right),
+        || format!("Adding {left} and {right} overflows."),
+    )
+}
+
+fn overflow_mul(left: i64, right: i64, overflow: IntOverflow) -> Result<i64, String> {
+    overflowing(
+        overflow,
+        left.checked_mul(right),
+        left.wrapping_mul(right),
+        left.saturating_mul(right),
+        || format!("Multiplying {left} and {right} overflows."),
+    )
+}
+
+struct IntUnaryFunction {
+    name: &'static str,
+    doc_string: &'static str,
+    operation: fn(i64, IntOverflow) -> Result<i64, String>,
+}
+impl<T: HostType> HostFunction<T> for IntUnaryFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<crate::WanderValue<T>, WanderError> {
+        let overflow = bindings.int_overflow();
+        match arguments {
+            [WanderValue::Int(value)] => (self.operation)(*value, overflow)
+                .map(WanderValue::Int)
+                .map_err(WanderError::new),
+            [WanderValue::Quantity(value, unit)] => match value.as_ref() {
+                WanderValue::Int(value) => (self.operation)(*value, overflow)
+                    .map(|value| {
+                        WanderValue::Quantity(Box::new(WanderValue::Int(value)), unit.clone())
+                    })
+                    .map_err(WanderError::new),
+                _ => Err(WanderError::new(format!(
+                    "`{}` function requires one Int parameter.",
+                    self.name
+                ))),
+            },
+            _ => Err(WanderError::new(format!(
+                "`{}` function requires one Int parameter.",
+                self.name
+            ))),
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.to_owned(),
+            parameters: vec![("value".to_owned(), Some("Int".to_owned()))],
+            // An Int, or a Quantity when the value has a unit.
+            result: None,
+            pure: true,
+            doc_string: self.doc_string.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct IntOperationFunction {
+    name: &'static str,
+    doc_string: &'static str,
+    units: UnitRule,
+    operation: fn(i64, i64, IntOverflow) -> Result<i64, String>,
+}
+impl<T: HostType> HostFunction<T> for IntOperationFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<crate::WanderValue<T>, WanderError> {
+        if let [left, right] = arguments {
+            let (left, right, unit) = strip_units(self.name, self.units, left, right)?;
+            if let (WanderValue::Int(left), WanderValue::Int(right)) = (left, right) {
+                return (self.operation)(left, right, bindings.int_overflow())
+                    .map(|value| with_unit(WanderValue::Int(value), unit))
+                    .map_err(WanderError::new);
+            }
+        }
+        Err(WanderError::new(format!(
+            "`{}` function requires two Int parameters.",
+            self.name
+        )))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.to_owned(),
+            parameters: vec![
+                ("left".to_owned(), Some("Int".to_owned())),
+                ("right".to_owned(), Some("Int".to_owned())),
+            ],
+            // An Int, or a Quantity when the values have units.
+            result: None,
+            pure: true,
+            doc_string: self.doc_string.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+struct IntComparisonFunction {
+    name: &'static str,
+    doc_string: &'static str,
+    comparison: fn(i64, i64) -> bool,
+}
+impl<T: HostType> HostFunction<T> for IntComparisonFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<crate::WanderValue<T>, WanderError> {
+        if let [left, right] = arguments {
+            let (left, right, _) = strip_units(self.name, UnitRule::Compare, left, right)?;
+            if let (WanderValue::Int(left), WanderValue::Int(right)) = (left, right) {
+                return Ok(WanderValue::Bool((self.comparison)(left, right)));
+            }
+        }
+        Err(WanderError::new(format!(
+            "`{}` function requires two Int parameters.",
+            self.name
+        )))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.to_owned(),
+            parameters: vec![
+                ("left".to_owned(), Some("Int".to_owned())),
+                ("right".to_owned(), Some("Int".to_owned())),
+            ],
+            result: Some("Bool".to_owned()),
+            pure: true,
+            doc_string: self.doc_string.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+fn add_int_functions<T: HostType>(bindings: &mut Environment<T>) {
+    let unary_functions: [IntUnaryFunction; 2] = [
+        IntUnaryFunction {
+            name: "Int.negate",
+            doc_string: "Return the negation of the Int value passed.",
+            operation: |value, overflow| {
+                overflowing(
+                    overflow,
+                    value.checked_neg(),
+                    value.wrapping_neg(),
+                    value.saturating_neg(),
+                    || format!("Cannot negate {value}."),
+                )
+            },
+        },
+        IntUnaryFunction {
+            name: "Int.abs",
+            doc_string: "Return the absolute value of the Int value passed.",
+            operation: |value, overflow| {
+                overflowing(
+                    overflow,
+                    value.checked_abs(),
+                    value.wrapping_abs(),
+                    value.saturating_abs(),
+                    || format!("Cannot take the absolute value of {value}."),
+                )
+            },
+        },
+    ];
+    let operation_functions: [IntOperationFunction; 8] = [
+        IntOperationFunction {
+            name: "Int.add",
+            doc_string: "Add two Ints.",
+            units: UnitRule::Same,
+            operation: overflow_add,
+        },
+        IntOperationFunction {
+            name: "Int.sub",
+            doc_string: "Subtract the second Int from the first.",
+            units: UnitRule::Same,
+            operation: |left, right, overflow| {
+                overflowing(
+                    overflow,
+                    left.checked_sub(right),
+                    left.wrapping_sub(right),
+                    left.saturating_sub(right),
+                    || format!("Subtracting {right} from {left} overflows."),
+                )
+            },
+        },
+        IntOperationFunction {
+            name: "Int.mul",
+            doc_string: "Multiply two Ints.",
+            units: UnitRule::Scale,
+            operation: overflow_mul,
+        },
+        IntOperationFunction {
+            name: "Int.div",
+            doc_string: "Divide the first Int by the second, rounding towards zero.",
+            units: UnitRule::Ratio,
+            operation: |left, right, overflow| match right {
+                0 => Err(format!("Cannot divide {left} by zero.")),
+                _ => overflowing(
+                    overflow,
+                    left.checked_div(right),
+                    left.wrapping_div(right),
+                    left.saturating_div(right),
+                    || format!("Dividing {left} by {right} overflows."),
+                ),
+            },
+        },
+        IntOperationFunction {
+            name: "Int.mod",
+            doc_string: "The remainder of dividing the first Int by the second.",
+            units: UnitRule::Same,
+            operation: |left, right, overflow| match right {
+                0 => Err(format!("Cannot divide {left} by zero.")),
+                // The remainder is 0 when dividing overflows, so wrapping and saturating agree.
+                _ => overflowing(
+                    overflow,
+                    left.checked_rem(right),
+                    left.wrapping_rem(right),
+                    left.wrapping_rem(right),
+                    || format!("Dividing {left} by {right} overflows."),
+                ),
+            },
+        },
+        IntOperationFunction {
+            name: "Int.compare",
+            doc_string: "Return -1, 0, or 1 if the first Int is less than, equal to, or greater than the second.",
+            units: UnitRule::Compare,
+            operation: |left, right, _| Ok(left.cmp(&right) as i64),
+        },
+        IntOperationFunction {
+            name: "Int.min",
+            doc_string: "Return the smaller of two Ints.",
+            units: UnitRule::Same,
+            operation: |left, right, _| Ok(left.min(right)),
+        },
+        IntOperationFunction {
+            name: "Int.max",
+            doc_string: "Return the larger of two Ints.",
+            units: UnitRule::Same,
+            operation: |left, right, _| Ok(left.max(right)),
+        },
+    ];
+    let comparison_functions: [IntComparisonFunction; 4] = [
+        IntComparisonFunction {
+            name: "Int.lt",
+            doc_string: "Check if the first Int is less than the second.",
+            comparison: |left, right| left < right,
+        },
+        IntComparisonFunction {
+            name: "Int.gt",
+            doc_string: "Check if the first Int is greater than the second.",
+            comparison: |left, right| left > right,
+        },
+        IntComparisonFunction {
+            name: "Int.lte",
+            doc_string: "Check if the first Int is less than or equal to the second.",
+            comparison: |left, right| left <= right,
+        },
+        IntComparisonFunction {
+            name: "Int.gte",
+            doc_string: "Check if the first Int is greater than or equal to the second.",
+            comparison: |left, right| left >= right,
+        },
+    ];
+    for function in unary_functions {
+        bindings.bind_host_function(Rc::new(function));
+    }
+    for function in operation_functions {
+        bindings.bind_host_function(Rc::new(function));
+    }
+    for function in comparison_functions {
+        bindings.bind_host_function(Rc::new(function));
+    }
+}
+
+struct FloatOperationFunction {
+    name: &'static str,
+    doc_string: &'static str,
+    units: UnitRule,
+    operation: fn(f64, f64) -> f64,
+}
+impl<T: HostType> HostFunction<T> for FloatOperationFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<crate::WanderValue<T>, WanderError> {
+        if let [left, right] = arguments {
+            let (left, right, unit) = strip_units(self.name, self.units, left, right)?;
+            if let (WanderValue::Float(left), WanderValue::Float(right)) = (left, right) {
+                return Ok(with_unit(WanderValue::Float((self.operation)(left, right)), unit));
+            }
+        }
+        Err(WanderError::new(format!(
+            "`{}` function requires two Float parameters.",
+            self.name
+        )))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.to_owned(),
+            parameters: vec![
+                ("left".to_owned(), Some("Float".to_owned())),
+                ("right".to_owned(), Some("Float".to_owned())),
+            ],
+            // A Float, or a Quantity when the values have units.
+            result: None,
+            pure: true,
+            doc_string: self.doc_string.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+fn add_float_functions<T: HostType>(bindings: &mut Environment<T>) {
+    let functions: [FloatOperationFunction; 4] = [
+        FloatOperationFunction {
+            name: "Float.add",
+            doc_string: "Add two Floats.",
+            units: UnitRule::Same,
+            operation: |left, right| left + right,
+        },
+        FloatOperationFunction {
+            name: "Float.sub",
+            doc_string: "Subtract the second Float from the first.",
+            units: UnitRule::Same,
+            operation: |left, right| left - right,
+        },
+        FloatOperationFunction {
+            name: "Float.mul",
+            doc_string: "Multiply two Floats.",
+            units: UnitRule::Scale,
+            operation: |left, right| left * right,
+        },
+        FloatOperationFunction {
+            name: "Float.div",
+            doc_string: "Divide the first Float by the second.",
+            units: UnitRule::Ratio,
+            operation: |left, right| left / right,
+        },
+    ];
+    for function in functions {
+        bindings.bind_host_function(Rc::new(function));
+    }
+}
+
+// Join a List of Strings or Chars with a separator, or None if any value isn't a String or Char.
+fn join_strings<T: HostType>(separator: &str, values: &[WanderValue<T>]) -> Option<String> {
+    let mut parts = vec![];
+    for value in values {
+        match value {
+            WanderValue::String(value) => parts.push(value.clone()),
+            WanderValue::Char(value) => parts.push(value.to_string()),
+            _ => return None,
+        }
+    }
+    Some(parts.join(separator))
+}
+
+// String functions take the String they operate on as their last parameter so they work with pipes.
+fn add_string_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 18] = [
+        TableFunction {
+            name: "String.length",
+            doc_string: "Count the characters in a String.",
+            parameters: &["value: String"],
+            requires: "a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(value)] => {
+                    Some(Ok(WanderValue::Int(value.chars().count() as i64)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.concat",
+            doc_string: "Join two Strings together.",
+            parameters: &["left: String", "right: String"],
+            requires: "two Strings",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(left), WanderValue::String(right)] => {
+                    Some(Ok(WanderValue::String(format!("{left}{right}"))))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.slice",
+            doc_string: "Get the characters of a String from a start index up to an end index.",
+            parameters: &["start: Int", "end: Int", "value: String"],
+            requires: "a start and end Int within the length of a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Int(start), WanderValue::Int(end), WanderValue::String(value)] => {
+                    let start = usize::try_from(*start).ok()?;
+                    let end = usize::try_from(*end).ok()?;
+                    if start > end || end > value.chars().count() {
+                        return None;
+                    }
+                    Some(Ok(WanderValue::String(
+                        value.chars().skip(start).take(end - start).collect(),
+                    )))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.split",
+            doc_string: "Split a String into a List of Strings on a separator.",
+            parameters: &["separator: String", "value: String"],
+            requires: "a separator String and a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(separator), WanderValue::String(value)] => {
+                    Some(Ok(WanderValue::List(
+                        value
+                            .split(separator.as_str())
+                            .map(|part| WanderValue::String(part.to_owned()))
+                            .collect(),
+                    )))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.toUpper",
+            doc_string: "Convert a String to upper case.",
+            parameters: &["value: String"],
+            requires: "a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(value)] => Some(Ok(WanderValue::String(value.to_uppercase()))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.toLower",
+            doc_string: "Convert a String to lower case.",
+            parameters: &["value: String"],
+            requires: "a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(value)] => Some(Ok(WanderValue::String(value.to_lowercase()))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.contains",
+            doc_string: "Check if a String contains another String.",
+            parameters: &["search: String", "value: String"],
+            requires: "two Strings",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(search), WanderValue::String(value)] => {
+                    Some(Ok(WanderValue::Bool(value.contains(search.as_str()))))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.replace",
+            doc_string: "Replace every occurrence of a String with another String.",
+            parameters: &["from: String", "to: String", "value: String"],
+            requires: "three Strings",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(from), WanderValue::String(to), WanderValue::String(value)] => {
+                    Some(Ok(WanderValue::String(value.replace(from.as_str(), to))))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.trim",
+            doc_string: "Remove whitespace from the start and end of a String.",
+            parameters: &["value: String"],
+            requires: "a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(value)] => Some(Ok(WanderValue::String(value.trim().to_owned()))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.toInt",
+            doc_string: "Parse a String as an Int.",
+            parameters: &["value: String"],
+            requires: "a String containing an Int",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(value)] => {
+                    value.trim().parse().ok().map(|value| Ok(WanderValue::Int(value)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.fromInt",
+            doc_string: "Convert an Int to a String.",
+            parameters: &["value: Int"],
+            requires: "an Int",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Int(value)] => Some(Ok(WanderValue::String(value.to_string()))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.compare",
+            doc_string: "Compare two Strings by their Unicode code points, returning -1, 0, or 1.",
+            parameters: &["left: String", "right: String"],
+            requires: "two Strings",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(left), WanderValue::String(right)] => {
+                    Some(Ok(WanderValue::Int(left.cmp(right) as i64)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.toList",
+            doc_string: "Split a String into a List of its characters.",
+            parameters: &["value: String"],
+            requires: "a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(value)] => Some(Ok(WanderValue::List(
+                    value
+                        .chars()
+                        .map(|character| WanderValue::String(character.to_string()))
+                        .collect(),
+                ))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.fromList",
+            doc_string: "Join a List of Strings or Chars into a single String.",
+            parameters: &["list: List"],
+            requires: "a List of Strings or Chars",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] => {
+                    join_strings("", values).map(|value| Ok(WanderValue::String(value)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.startsWith",
+            doc_string: "Check if a String starts with another String.",
+            parameters: &["prefix: String", "value: String"],
+            requires: "two Strings",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(prefix), WanderValue::String(value)] => {
+                    Some(Ok(WanderValue::Bool(value.starts_with(prefix.as_str()))))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.endsWith",
+            doc_string: "Check if a String ends with another String.",
+            parameters: &["suffix: String", "value: String"],
+            requires: "two Strings",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(suffix), WanderValue::String(value)] => {
+                    Some(Ok(WanderValue::Bool(value.ends_with(suffix.as_str()))))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.equalsIgnoreCase",
+            doc_string: "Check if two Strings are equal when both are lowercased, without depending on the locale.",
+            parameters: &["left: String", "right: String"],
+            requires: "two Strings",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(left), WanderValue::String(right)] => {
+                    Some(Ok(WanderValue::Bool(left.to_lowercase() == right.to_lowercase())))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "String.toChars",
+            doc_string: "Split a String into a List of its Chars.",
+            parameters: &["value: String"],
+            requires: "a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(value)] => {
+                    Some(Ok(WanderValue::List(value.chars().map(WanderValue::Char).collect())))
+                }
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
+
+struct CharToStringFunction {}
+impl<T: HostType> HostFunction<T> for CharToStringFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        if let [WanderValue::Char(value)] = arguments {
+            Ok(WanderValue::String(value.to_string()))
+        } else {
+            Err(WanderError::new(
+                "`Char.toString` function requires a Char.".to_owned(),
+            ))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Char.toString".to_owned(),
+            parameters: vec![("value".to_owned(), Some("Char".to_owned()))],
+            result: Some("String".to_owned()),
+            pure: true,
+            doc_string: "Convert a Char to a String.".to_owned(),
+            ..Default::default()
         }
     }
 }
@@ -150,7 +1255,7 @@ impl<T: HostType> HostFunction<T> for AtFunction {
     fn run(
         &self,
         arguments: &[WanderValue<T>],
-        _: &Environment<T>,
+        _: &mut Environment<T>,
     ) -> Result<WanderValue<T>, WanderError> {
         if let [WanderValue::Int(index), WanderValue::List(value)] = arguments {
             let index: usize = index.to_owned().try_into().unwrap();
@@ -158,13 +1263,13 @@ impl<T: HostType> HostFunction<T> for AtFunction {
                 let t: Option<&WanderValue<T>> = value.get(index);
                 match t {
                     Some(t) => Ok(t.to_owned()),
-                    None => Err(WanderError("`at` function err.".to_owned())),
+                    None => Err(WanderError::new("`at` function err.".to_owned())),
                 }
             } else {
-                Err(WanderError("`at` function err.".to_owned()))
+                Err(WanderError::new("`at` function err.".to_owned()))
             }
         } else {
-            Err(WanderError("`at` function err.".to_owned()))
+            Err(WanderError::new("`at` function err.".to_owned()))
         }
     }
 
@@ -172,81 +1277,1041 @@ impl<T: HostType> HostFunction<T> for AtFunction {
         HostFunctionBinding {
             name: "List.at".to_owned(),
             parameters: vec![
-                ("offset".to_owned(), None), //Int
-                ("list".to_owned(), None),   //List
+                ("offset".to_owned(), Some("Int".to_owned())),
+                ("list".to_owned(), Some("List".to_owned())),
             ],
             result: None,
+            pure: true,
             doc_string: "Get the value at a given location.".to_owned(),
+            ..Default::default()
         }
     }
 }
 
-//TODO https://github.com/almibe/ligature-rs/issues/305
-// struct EnvironmentFunction {}
-// impl HostFunction for EnvironmentFunction {
-//     fn run(
-//         &self,
-//         arguments: &[WanderValue],
-//         bindings: &Bindings,
-//     ) -> Result<WanderValue, WanderError> {
-//         if arguments.is_empty() {
-//             let b: BTreeSet<Statement> = bindings
-//                 .environment()
-//                 .iter()
-//                 .flat_map(|e| {
-//                     let mut statements = vec![];
-//                     let name = Identifier::new(e.name.as_str()).unwrap();
-//                     statements.push(Statement {
-//                         entity: name.clone(),
-//                         attribute: Identifier::new("doc").unwrap(),
-//                         value: Value::String(e.doc_string.clone()),
-//                     });
-//                     statements.push(Statement {
-//                         entity: name.clone(),
-//                         attribute: Identifier::new("parameters").unwrap(),
-//                         value: Value::String(format!("{:?}", e.parameters)),
-//                     });
-//                     statements.push(Statement {
-//                         entity: name.clone(),
-//                         attribute: Identifier::new("result").unwrap(),
-//                         value: Value::String(format!("{:?}", e.result)),
-//                     });
-//                     statements
-//                 })
-//                 .collect();
-//             Ok(WanderValue::Graph(Graph::new(b)))
-//         } else {
-//             panic!("should never reach")
-//         }
-//     }
-
-//     fn doc(&self) -> String {
-//         "All Functions in the current Environment.".to_owned()
-//     }
-
-//     fn params(&self) -> Vec<WanderType> {
-//         vec![]
-//     }
-
-//     fn returns(&self) -> WanderType {
-//         WanderType::Graph
-//     }
-
-//     fn name(&self) -> String {
-//         "Halp.environment".to_owned()
-//     }
-// }
+fn non_numeric_error<T: HostType>(name: &str, value: &WanderValue<T>) -> WanderError {
+    WanderError::new(format!(
+        "`{name}` function requires a List of Ints or Floats, found {value}."
+    ))
+}
+
+// Combine the numbers in a List, Ints stay Ints until a Float is reached.
+fn fold_numbers<T: HostType>(
+    name: &str,
+    initial: WanderValue<T>,
+    values: &[WanderValue<T>],
+    overflow: IntOverflow,
+    int_operation: fn(i64, i64, IntOverflow) -> Result<i64, String>,
+    float_operation: fn(f64, f64) -> f64,
+) -> Result<WanderValue<T>, WanderError> {
+    let mut result = initial;
+    for value in values {
+        result = match (result, value) {
+            (WanderValue::Int(left), WanderValue::Int(right)) => {
+                WanderValue::Int(int_operation(left, *right, overflow).map_err(WanderError::new)?)
+            }
+            (WanderValue::Int(left), WanderValue::Float(right)) => {
+                WanderValue::Float(float_operation(left as f64, *right))
+            }
+            (WanderValue::Float(left), WanderValue::Int(right)) => {
+                WanderValue::Float(float_operation(left, *right as f64))
+            }
+            (WanderValue::Float(left), WanderValue::Float(right)) => {
+                WanderValue::Float(float_operation(left, *right))
+            }
+            (_, value) => return Err(non_numeric_error(name, value)),
+        };
+    }
+    Ok(result)
+}
+
+// Find the smallest or largest number in a non-empty List, keeping it as an Int or Float.
+fn extreme_number<T: HostType>(
+    name: &str,
+    values: &[WanderValue<T>],
+    wanted: Ordering,
+) -> Result<WanderValue<T>, WanderError> {
+    let mut result = &values[0];
+    for value in values {
+        let ordering = match (value, result) {
+            (WanderValue::Int(value), WanderValue::Int(result)) => value.cmp(result),
+            (WanderValue::Int(value), WanderValue::Float(result)) => (*value as f64).total_cmp(result),
+            (WanderValue::Float(value), WanderValue::Int(result)) => value.total_cmp(&(*result as f64)),
+            (WanderValue::Float(value), WanderValue::Float(result)) => value.total_cmp(result),
+            (WanderValue::Int(_) | WanderValue::Float(_), result) => {
+                return Err(non_numeric_error(name, result))
+            }
+            (value, _) => return Err(non_numeric_error(name, value)),
+        };
+        if ordering == wanted {
+            result = value;
+        }
+    }
+    Ok(result.clone())
+}
+
+// List functions take the List they operate on as their last parameter so they work with pipes.
+fn add_list_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 19] = [
+        TableFunction {
+            name: "List.map",
+            doc_string: "Apply a function to every value in a List.",
+            parameters: &["function", "list: List"],
+            requires: "a function and a List",
+            pure: true,
+            run: |arguments, bindings| match arguments {
+                [function, WanderValue::List(values)] => Some(
+                    values
+                        .iter()
+                        .map(|value| call_function(function, std::slice::from_ref(value), bindings))
+                        .collect::<Result<_, _>>()
+                        .map(WanderValue::List),
+                ),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.filter",
+            doc_string: "Keep the values in a List that a function returns true for.",
+            parameters: &["function", "list: List"],
+            requires: "a function and a List",
+            pure: true,
+            run: |arguments, bindings| match arguments {
+                [function, WanderValue::List(values)] => {
+                    let mut results = vec![];
+                    for value in values {
+                        match call_function(function, std::slice::from_ref(value), bindings) {
+                            Ok(WanderValue::Bool(true)) => results.push(value.clone()),
+                            Ok(WanderValue::Bool(false)) => (),
+                            Ok(result) => {
+                                return Some(Err(WanderError::new(format!(
+                                    "`List.filter` function requires a function that returns a Bool, found {result}."
+                                ))))
+                            }
+                            Err(err) => return Some(Err(err)),
+                        }
+                    }
+                    Some(Ok(WanderValue::List(results)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.foldLeft",
+            doc_string: "Combine the values in a List from first to last, starting with an initial value.",
+            parameters: &["function", "initial", "list: List"],
+            requires: "a function, an initial value, and a List",
+            pure: true,
+            run: |arguments, bindings| match arguments {
+                [function, initial, WanderValue::List(values)] => {
+                    let mut result = initial.clone();
+                    for value in values {
+                        match call_function(function, &[result, value.clone()], bindings) {
+                            Ok(value) => result = value,
+                            Err(err) => return Some(Err(err)),
+                        }
+                    }
+                    Some(Ok(result))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.length",
+            doc_string: "Count the values in a List.",
+            parameters: &["list: List"],
+            requires: "a List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] => Some(Ok(WanderValue::Int(values.len() as i64))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.head",
+            doc_string: "Get the first value in a List.",
+            parameters: &["list: List"],
+            requires: "a non-empty List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] => values.first().cloned().map(Ok),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.tail",
+            doc_string: "Get every value in a List after the first.",
+            parameters: &["list: List"],
+            requires: "a non-empty List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] if !values.is_empty() => {
+                    Some(Ok(WanderValue::List(values[1..].to_vec())))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.concat",
+            doc_string: "Join two Lists together.",
+            parameters: &["left: List", "right: List"],
+            requires: "two Lists",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(left), WanderValue::List(right)] => {
+                    let mut values = left.clone();
+                    values.extend(right.iter().cloned());
+                    Some(Ok(WanderValue::List(values)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.reverse",
+            doc_string: "Reverse the order of the values in a List.",
+            parameters: &["list: List"],
+            requires: "a List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] => {
+                    Some(Ok(WanderValue::List(values.iter().rev().cloned().collect())))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.toSet",
+            doc_string: "Create a Set from the values in a List, dropping duplicates.",
+            parameters: &["list: List"],
+            requires: "a List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] => Some(Ok(WanderValue::Set(values.iter().cloned().collect()))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.sort",
+            doc_string: "Sort the values in a List from first to last, in the same order as `Core.compare`.",
+            parameters: &["list: List"],
+            requires: "a List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] => {
+                    let mut values = values.clone();
+                    values.sort_by(compare_values);
+                    Some(Ok(WanderValue::List(values)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.chunk",
+            doc_string: "Split a List into Lists of a given size, the last one can be shorter.",
+            parameters: &["size: Int", "list: List"],
+            requires: "a positive Int and a List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Int(size), WanderValue::List(values)] if *size > 0 => Some(Ok(
+                    WanderValue::List(
+                        values
+                            .chunks(*size as usize)
+                            .map(|chunk| WanderValue::List(chunk.to_vec()))
+                            .collect(),
+                    ),
+                )),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.window",
+            doc_string: "Get every run of consecutive values of a given size in a List.",
+            parameters: &["size: Int", "list: List"],
+            requires: "a positive Int and a List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Int(size), WanderValue::List(values)] if *size > 0 => Some(Ok(
+                    WanderValue::List(
+                        values
+                            .windows(*size as usize)
+                            .map(|window| WanderValue::List(window.to_vec()))
+                            .collect(),
+                    ),
+                )),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.distinct",
+            doc_string: "Remove repeated values from a List, keeping the first of each.",
+            parameters: &["list: List"],
+            requires: "a List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] => {
+                    let mut results: Vec<WanderValue<T>> = vec![];
+                    for value in values {
+                        if !results.contains(value) {
+                            results.push(value.clone());
+                        }
+                    }
+                    Some(Ok(WanderValue::List(results)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.groupBy",
+            doc_string: "Group the values in a List into a Record of Lists by the key a function returns for each, keys that aren't Strings or Keywords are written out.",
+            parameters: &["function", "list: List"],
+            requires: "a function and a List",
+            pure: true,
+            run: |arguments, bindings| match arguments {
+                [function, WanderValue::List(values)] => {
+                    let mut groups: IndexMap<String, WanderValue<T>> = IndexMap::new();
+                    for value in values {
+                        let key = match call_function(function, std::slice::from_ref(value), bindings) {
+                            Ok(WanderValue::String(key)) => key,
+                            Ok(WanderValue::Keyword(key)) => key.name().to_owned(),
+                            Ok(key) => key.to_string(),
+                            Err(err) => return Some(Err(err)),
+                        };
+                        if let WanderValue::List(group) = groups
+                            .entry(key)
+                            .or_insert_with(|| WanderValue::List(vec![]))
+                        {
+                            group.push(value.clone());
+                        }
+                    }
+                    Some(Ok(WanderValue::Record(groups)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.sum",
+            doc_string: "Add the numbers in a List, the result is a Float if any of them are.",
+            parameters: &["list: List"],
+            requires: "a List",
+            pure: true,
+            run: |arguments, bindings| match arguments {
+                [WanderValue::List(values)] => Some(fold_numbers(
+                    "List.sum",
+                    WanderValue::Int(0),
+                    values,
+                    bindings.int_overflow(),
+                    overflow_add,
+                    |left, right| left + right,
+                )),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.product",
+            doc_string: "Multiply the numbers in a List, the result is a Float if any of them are.",
+            parameters: &["list: List"],
+            requires: "a List",
+            pure: true,
+            run: |arguments, bindings| match arguments {
+                [WanderValue::List(values)] => Some(fold_numbers(
+                    "List.product",
+                    WanderValue::Int(1),
+                    values,
+                    bindings.int_overflow(),
+                    overflow_mul,
+                    |left, right| left * right,
+                )),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.min",
+            doc_string: "Get the smallest number in a List.",
+            parameters: &["list: List"],
+            requires: "a non-empty List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] if !values.is_empty() => {
+                    Some(extreme_number("List.min", values, Ordering::Less))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.max",
+            doc_string: "Get the largest number in a List.",
+            parameters: &["list: List"],
+            requires: "a non-empty List",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(values)] if !values.is_empty() => {
+                    Some(extreme_number("List.max", values, Ordering::Greater))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "List.joinWith",
+            doc_string: "Join a List of Strings or Chars into a single String, with a separator between each.",
+            parameters: &["separator: String", "list: List"],
+            requires: "a String and a List of Strings or Chars",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(separator), WanderValue::List(values)] => {
+                    join_strings(separator, values).map(|value| Ok(WanderValue::String(value)))
+                }
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
+
+struct GetPathFunction {}
+impl<T: HostType> HostFunction<T> for GetPathFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        _bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        if let [record @ WanderValue::Record(_), WanderValue::List(path)] = arguments {
+            let mut fields = vec![];
+            for field in path {
+                match field {
+                    WanderValue::String(field) => fields.push(field.as_str()),
+                    _ => {
+                        return Err(WanderError::new(
+                            "`Record.getPath` function requires a path of Strings.".to_owned(),
+                        ))
+                    }
+                }
+            }
+            read_path(record.clone(), &format!("{record}"), fields)
+        } else {
+            Err(WanderError::new(
+                "`Record.getPath` function requires a Record and a List of field names.".to_owned(),
+            ))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Record.getPath".to_owned(),
+            parameters: vec![
+                ("record".to_owned(), Some("Record".to_owned())),
+                ("path".to_owned(), Some("List".to_owned())),
+            ],
+            result: None,
+            pure: true,
+            doc_string: "Read a value nested in Records by following a List of field names."
+                .to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+fn add_set_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 6] = [
+        TableFunction {
+            name: "Set.union",
+            doc_string: "Create a Set of the values in either Set.",
+            parameters: &["left: Set", "right: Set"],
+            requires: "two Sets",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Set(left), WanderValue::Set(right)] => {
+                    Some(Ok(WanderValue::Set(left.union(right).cloned().collect())))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Set.intersection",
+            doc_string: "Create a Set of the values in both Sets.",
+            parameters: &["left: Set", "right: Set"],
+            requires: "two Sets",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Set(left), WanderValue::Set(right)] => {
+                    Some(Ok(WanderValue::Set(left.intersection(right).cloned().collect())))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Set.difference",
+            doc_string: "Create a Set of the values in the first Set that aren't in the second.",
+            parameters: &["left: Set", "right: Set"],
+            requires: "two Sets",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Set(left), WanderValue::Set(right)] => {
+                    Some(Ok(WanderValue::Set(left.difference(right).cloned().collect())))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Set.contains",
+            doc_string: "Check if a value is in a Set.",
+            parameters: &["value", "set: Set"],
+            requires: "a value and a Set",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [value, WanderValue::Set(values)] => Some(Ok(WanderValue::Bool(values.contains(value)))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Set.insert",
+            doc_string: "Create a Set with a value added.",
+            parameters: &["value", "set: Set"],
+            requires: "a value and a Set",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [value, WanderValue::Set(values)] => {
+                    let mut values = values.clone();
+                    values.insert(value.clone());
+                    Some(Ok(WanderValue::Set(values)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Set.toList",
+            doc_string: "Create a List of the values in a Set, in the order the Set is printed.",
+            parameters: &["set: Set"],
+            requires: "a Set",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Set(values)] => Some(Ok(WanderValue::List(
+                    ordered_set(values).into_iter().cloned().collect(),
+                ))),
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
+
+fn add_record_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 5] = [
+        TableFunction {
+            name: "Record.merge",
+            doc_string: "Combine two Records, fields in the second Record replace fields in the first.",
+            parameters: &["left: Record", "right: Record"],
+            requires: "two Records",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Record(left), WanderValue::Record(right)] => {
+                    let mut values = left.clone();
+                    values.extend(right.iter().map(|(name, value)| (name.clone(), value.clone())));
+                    Some(Ok(WanderValue::Record(values)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Record.keys",
+            doc_string: "Get a List of the field names in a Record.",
+            parameters: &["record: Record"],
+            requires: "a Record",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Record(values)] => Some(Ok(WanderValue::List(
+                    values.keys().map(|name| WanderValue::String(name.clone())).collect(),
+                ))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Record.values",
+            doc_string: "Get a List of the values in a Record.",
+            parameters: &["record: Record"],
+            requires: "a Record",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Record(values)] => {
+                    Some(Ok(WanderValue::List(values.values().cloned().collect())))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Record.get",
+            doc_string: "Read a field from a Record by name.",
+            parameters: &["record: Record", "field: String"],
+            requires: "a Record and a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [record @ WanderValue::Record(_), WanderValue::String(field)] => {
+                    Some(read_path(record.clone(), &format!("{record}"), [field.as_str()]))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Record.has",
+            doc_string: "Check if a Record has a field with the given name.",
+            parameters: &["record: Record", "field: String"],
+            requires: "a Record and a String",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Record(values), WanderValue::String(field)] => {
+                    Some(Ok(WanderValue::Bool(values.contains_key(field))))
+                }
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
+
+// Results are Records with either an `ok` or an `error` field, the same shape `try expr end` returns.
+fn result_field<T: HostType>(value: &WanderValue<T>) -> Option<(&str, &WanderValue<T>)> {
+    match value {
+        WanderValue::Record(record) if record.len() == 1 => {
+            let (name, value) = record.iter().next()?;
+            match name.as_str() {
+                "ok" | "error" => Some((name.as_str(), value)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn add_result_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 5] = [
+        TableFunction {
+            name: "Result.ok",
+            doc_string: "Create a successful Result holding a value.",
+            parameters: &["value"],
+            requires: "a value",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [value] => Some(Ok(WanderValue::Record(IndexMap::from([(
+                    "ok".to_owned(),
+                    value.clone(),
+                )])))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Result.error",
+            doc_string: "Create a failed Result with a message.",
+            parameters: &["message"],
+            requires: "a String message",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::String(message)] => Some(Ok(WanderValue::Record(IndexMap::from([(
+                    "error".to_owned(),
+                    error_record(WanderError::new(message.clone())),
+                )])))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Result.isOk",
+            doc_string: "Check if a Result succeeded.",
+            parameters: &["result"],
+            requires: "a Result",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [result] => result_field(result).map(|(name, _)| Ok(WanderValue::Bool(name == "ok"))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Result.isError",
+            doc_string: "Check if a Result failed.",
+            parameters: &["result"],
+            requires: "a Result",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [result] => {
+                    result_field(result).map(|(name, _)| Ok(WanderValue::Bool(name == "error")))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Result.unwrapOr",
+            doc_string: "Get the value of a successful Result, or the default value if it failed.",
+            parameters: &["default", "result"],
+            requires: "a default value and a Result",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [default, result] => match result_field(result)? {
+                    ("ok", value) => Some(Ok(value.clone())),
+                    _ => Some(Ok(default.clone())),
+                },
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
+
+// Optional values are either nothing or any other value, like values tagged `Int?`.
+fn add_option_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 3] = [
+        TableFunction {
+            name: "Option.isSome",
+            doc_string: "Check if a value isn't nothing.",
+            parameters: &["value"],
+            requires: "a value",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [value] => Some(Ok(WanderValue::Bool(*value != WanderValue::Nothing))),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Option.default",
+            doc_string: "Get a value, or the default value if it's nothing.",
+            parameters: &["default", "value"],
+            requires: "a default value and a value",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [default, WanderValue::Nothing] => Some(Ok(default.clone())),
+                [_, value] => Some(Ok(value.clone())),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Option.map",
+            doc_string: "Apply a function to a value unless it's nothing.",
+            parameters: &["function", "value"],
+            requires: "a function and a value",
+            pure: true,
+            run: |arguments, bindings| match arguments {
+                [_, WanderValue::Nothing] => Some(Ok(WanderValue::Nothing)),
+                [function, value] => {
+                    Some(call_function(function, std::slice::from_ref(value), bindings))
+                }
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
+
+// Validations return a List of error messages, which is empty when a value is valid.
+fn add_validate_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 2] = [
+        TableFunction {
+            name: "Validate.all",
+            doc_string: "Combine the errors from a List of validations into a single List.",
+            parameters: &["validations"],
+            requires: "a List of validations",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::List(validations)] => {
+                    let mut errors = vec![];
+                    for validation in validations {
+                        match validation {
+                            WanderValue::List(validation) => errors.extend(validation.iter().cloned()),
+                            _ => return None,
+                        }
+                    }
+                    Some(Ok(WanderValue::List(errors)))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Validate.field",
+            doc_string: "Validate a field of a Record with a predicate, returning a List of errors that's empty when the field is valid. The predicate can return a Bool, or the errors from validating a nested Record.",
+            parameters: &["record", "name", "predicate"],
+            requires: "a Record, a String field name, and a predicate",
+            pure: true,
+            run: |arguments, bindings| match arguments {
+                [WanderValue::Record(record), WanderValue::String(name), predicate] => {
+                    let error = |message: &str| WanderValue::String(format!("{name} {message}"));
+                    let Some(value) = record.get(name) else {
+                        return Some(Ok(WanderValue::List(vec![error("is missing")])));
+                    };
+                    Some(
+                        match call_function(predicate, std::slice::from_ref(value), bindings) {
+                            Ok(WanderValue::Bool(true)) => Ok(WanderValue::List(vec![])),
+                            Ok(WanderValue::Bool(false)) => Ok(WanderValue::List(vec![error("is invalid")])),
+                            // Errors from validating a nested Record are reported with the path to the field.
+                            Ok(WanderValue::List(errors)) => Ok(WanderValue::List(
+                                errors
+                                    .into_iter()
+                                    .map(|nested| match nested {
+                                        WanderValue::String(nested) => {
+                                            WanderValue::String(format!("{name}.{nested}"))
+                                        }
+                                        nested => nested,
+                                    })
+                                    .collect(),
+                            )),
+                            Ok(result) => Err(WanderError::new(format!(
+                                "`Validate.field` function requires a predicate that returns a Bool or a List of errors, found {result}."
+                            ))),
+                            Err(err) => Err(err),
+                        },
+                    )
+                }
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
+
+fn add_unit_functions<T: HostType + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 3] = [
+        TableFunction {
+            name: "Unit.of",
+            doc_string: "Tag an Int or Float with a unit.",
+            parameters: &["unit", "value"],
+            requires: "an Identifier and an Int or Float",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Identifier(unit), value @ (WanderValue::Int(_) | WanderValue::Float(_))] => {
+                    Some(Ok(WanderValue::Quantity(Box::new(value.clone()), unit.clone())))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Unit.value",
+            doc_string: "Get the number of a Quantity without its unit.",
+            parameters: &["quantity"],
+            requires: "a Quantity",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Quantity(value, _)] => Some(Ok(*value.clone())),
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Unit.unit",
+            doc_string: "Get the unit of a Quantity as an Identifier.",
+            parameters: &["quantity"],
+            requires: "a Quantity",
+            pure: true,
+            run: |arguments, _| match arguments {
+                [WanderValue::Quantity(_, unit)] => Some(Ok(WanderValue::Identifier(unit.clone()))),
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
+
+// Functions used by infix operators, they call the HostFunction for the type of their first argument.
+struct OperatorFunction {
+    name: &'static str,
+    doc_string: &'static str,
+    requires: &'static str,
+    overloads: &'static [(&'static str, &'static str)],
+}
+impl<T: HostType> HostFunction<T> for OperatorFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<crate::WanderValue<T>, WanderError> {
+        let function = match arguments {
+            [left, _] => self
+                .overloads
+                .iter()
+                .find(|(type_name, _)| Some(*type_name) == operand_type(left))
+                .and_then(|(_, function)| bindings.read_host_function(&function.to_string())),
+            _ => None,
+        };
+        match function {
+            Some(function) => bindings.call_host_function(&function, arguments),
+            None => Err(WanderError::new(format!(
+                "`{}` function requires {}.",
+                self.name, self.requires
+            ))),
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.to_owned(),
+            parameters: vec![("left".to_owned(), None), ("right".to_owned(), None)],
+            result: None,
+            pure: true,
+            doc_string: self.doc_string.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+// The name of the type an OperatorFunction picks an overload by, Quantities use the type of their number.
+fn operand_type<T: HostType>(value: &WanderValue<T>) -> Option<&'static str> {
+    match value {
+        WanderValue::Int(_) => Some("Int"),
+        WanderValue::Float(_) => Some("Float"),
+        WanderValue::String(_) => Some("String"),
+        WanderValue::List(_) => Some("List"),
+        WanderValue::Quantity(value, _) => operand_type(value),
+        _ => None,
+    }
+}
+
+fn add_operator_functions<T: HostType>(bindings: &mut Environment<T>) {
+    let numbers = "two Ints or two Floats";
+    let functions: [OperatorFunction; 9] = [
+        OperatorFunction {
+            name: "Core.add",
+            doc_string: "Add two numbers, used by `+`.",
+            requires: numbers,
+            overloads: &[("Int", "Int.add"), ("Float", "Float.add")],
+        },
+        OperatorFunction {
+            name: "Core.sub",
+            doc_string: "Subtract the second number from the first, used by `-`.",
+            requires: numbers,
+            overloads: &[("Int", "Int.sub"), ("Float", "Float.sub")],
+        },
+        OperatorFunction {
+            name: "Core.mul",
+            doc_string: "Multiply two numbers, used by `*`.",
+            requires: numbers,
+            overloads: &[("Int", "Int.mul"), ("Float", "Float.mul")],
+        },
+        OperatorFunction {
+            name: "Core.div",
+            doc_string: "Divide the first number by the second, used by `/`.",
+            requires: numbers,
+            overloads: &[("Int", "Int.div"), ("Float", "Float.div")],
+        },
+        OperatorFunction {
+            name: "Core.lt",
+            doc_string: "Check if the first Int is less than the second, used by `<`.",
+            requires: "two Ints",
+            overloads: &[("Int", "Int.lt")],
+        },
+        OperatorFunction {
+            name: "Core.gt",
+            doc_string: "Check if the first Int is greater than the second, used by `>`.",
+            requires: "two Ints",
+            overloads: &[("Int", "Int.gt")],
+        },
+        OperatorFunction {
+            name: "Core.lte",
+            doc_string: "Check if the first Int is less than or equal to the second, used by `<=`.",
+            requires: "two Ints",
+            overloads: &[("Int", "Int.lte")],
+        },
+        OperatorFunction {
+            name: "Core.gte",
+            doc_string: "Check if the first Int is greater than or equal to the second, used by `>=`.",
+            requires: "two Ints",
+            overloads: &[("Int", "Int.gte")],
+        },
+        OperatorFunction {
+            name: "Core.concat",
+            doc_string: "Join two Strings or two Lists, used by `++`.",
+            requires: "two Strings or two Lists",
+            overloads: &[("String", "String.concat"), ("List", "List.concat")],
+        },
+    ];
+    for function in functions {
+        bindings.bind_host_function(Rc::new(function));
+    }
+}
+
+struct EnvironmentFunction {}
+impl<T: HostType> HostFunction<T> for EnvironmentFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        if !arguments.is_empty() {
+            return Err(WanderError::new(
+                "`Halp.environment` function doesn't take any parameters.".to_owned(),
+            ));
+        }
+        let environment = bindings
+            .environment()
+            .into_iter()
+            .map(|binding| {
+                let mut record = IndexMap::new();
+                record.insert("name".to_owned(), WanderValue::String(binding.name));
+                record.insert(
+                    "parameters".to_owned(),
+                    WanderValue::List(
+                        binding
+                            .parameters
+                            .into_iter()
+                            .map(|(name, tag)| match tag {
+                                Some(tag) => WanderValue::String(format!("{name}: {tag}")),
+                                None => WanderValue::String(name),
+                            })
+                            .collect(),
+                    ),
+                );
+                record.insert(
+                    "result".to_owned(),
+                    binding
+                        .result
+                        .map(WanderValue::String)
+                        .unwrap_or(WanderValue::Nothing),
+                );
+                record.insert(
+                    "docString".to_owned(),
+                    WanderValue::String(binding.doc_string),
+                );
+                WanderValue::Record(record)
+            })
+            .collect();
+        Ok(WanderValue::List(environment))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Halp.environment".to_owned(),
+            parameters: vec![],
+            result: None,
+            doc_string: "All Functions and values in the current Environment.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
 
 /// Creates a set of Bindings for Wander that consists of all of the common
 /// functionality, but doesn't interact with an instance of Ligature.
-pub fn common<T: HostType>() -> Environment<T> {
+/// Tags are checked with a CoreTypeChecker.
+pub fn common<T: HostType + 'static>() -> Environment<T> {
     let mut bindings = Environment::new();
+    bindings.set_type_checker(Rc::new(CoreTypeChecker {}));
     bindings.bind_host_function(Rc::new(EqFunction {}));
+    bindings.bind_host_function(Rc::new(CompareFunction {}));
+    bindings.bind_host_function(Rc::new(ErrorFunction {}));
+    bindings.bind_host_function(Rc::new(ErrorWithFunction {}));
+    bindings.bind_host_function(Rc::new(VersionFunction {}));
+    bindings.bind_host_function(Rc::new(HasBindingFunction {}));
+    bindings.bind_host_function(Rc::new(EvalFunction {}));
+    bindings.bind_host_function(Rc::new(ParseFunction {}));
     bindings.bind_host_function(Rc::new(AssertEqFunction {}));
     bindings.bind_host_function(Rc::new(AndFunction {}));
+    bindings.bind_host_function(Rc::new(OrFunction {}));
+    bindings.bind_host_function(Rc::new(XorFunction {}));
     bindings.bind_host_function(Rc::new(NotFunction {}));
     bindings.bind_host_function(Rc::new(AtFunction {}));
-    // bindings.bind_host_function(Rc::new(EnvironmentFunction {}));
+    bindings.bind_host_function(Rc::new(CharToStringFunction {}));
+    bindings.bind_host_function(Rc::new(GetPathFunction {}));
+    add_int_functions(&mut bindings);
+    add_float_functions(&mut bindings);
+    add_string_functions(&mut bindings);
+    add_list_functions(&mut bindings);
+    add_record_functions(&mut bindings);
+    add_set_functions(&mut bindings);
+    add_result_functions(&mut bindings);
+    add_option_functions(&mut bindings);
+    add_validate_functions(&mut bindings);
+    add_unit_functions(&mut bindings);
+    add_operator_functions(&mut bindings);
+    bindings.bind_host_function(Rc::new(EnvironmentFunction {}));
     bindings
 }
 
@@ -0,0 +1,161 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Contains Refs, cells holding a value that scripts can replace, like a counter updated during a fold.
+
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    environment::Environment,
+    preludes::{add_table_functions, TableFunction},
+    HostType, HostValue, WanderError, WanderValue,
+};
+
+/// A handle to a cell created with `Ref.new`.
+/// The values of cells are kept in the Environment they were created in, as a context,
+/// and live as long as it does. Each Ref is tagged with the store of the Environment
+/// that created it, so using a Ref in another Environment is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Ref {
+    store: u64,
+    index: usize,
+}
+
+impl Display for Ref {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<Ref {}>", self.index)
+    }
+}
+
+/// A HostType that can hold Refs, needed to bind the Ref functions.
+pub trait RefHost: HostType {
+    /// Wrap a Ref in the HostType.
+    fn from_reference(reference: Ref) -> Self;
+    /// Get the Ref held by a value, if it is one.
+    fn as_reference(&self) -> Option<Ref>;
+}
+
+// The values of every cell created in an Environment, indexed by Ref,
+// with an id no other Environment's cells share.
+struct Cells<T: Clone + PartialEq + Eq> {
+    store: u64,
+    values: Vec<WanderValue<T>>,
+}
+
+static NEXT_STORE: AtomicU64 = AtomicU64::new(0);
+
+impl<T: Clone + PartialEq + Eq> Cells<T> {
+    fn new() -> Cells<T> {
+        Cells {
+            store: NEXT_STORE.fetch_add(1, Ordering::Relaxed),
+            values: vec![],
+        }
+    }
+
+    fn get(&self, reference: Ref) -> Option<&WanderValue<T>> {
+        match reference.store == self.store {
+            true => self.values.get(reference.index),
+            false => None,
+        }
+    }
+
+    fn get_mut(&mut self, reference: Ref) -> Option<&mut WanderValue<T>> {
+        match reference.store == self.store {
+            true => self.values.get_mut(reference.index),
+            false => None,
+        }
+    }
+}
+
+fn as_reference<T: RefHost>(value: &WanderValue<T>) -> Option<Ref> {
+    match value {
+        WanderValue::HostValue(HostValue { value }) => value.as_reference(),
+        _ => None,
+    }
+}
+
+fn missing_cell(reference: Ref) -> WanderError {
+    WanderError::new(format!("{reference} wasn't created in this Environment."))
+}
+
+/// Bind `Ref.new`, `Ref.get`, and `Ref.set`.
+pub fn add_ref_functions<T: RefHost + 'static>(bindings: &mut Environment<T>) {
+    let functions: [TableFunction<T>; 3] = [
+        TableFunction {
+            name: "Ref.new",
+            doc_string: "Create a Ref holding a value.",
+            parameters: &["value"],
+            requires: "a value",
+            pure: false,
+            run: |arguments, bindings| match arguments {
+                [value] => {
+                    if bindings.context::<Cells<T>>().is_none() {
+                        bindings.set_context(Cells::<T>::new());
+                    }
+                    let cells = bindings.context_mut::<Cells<T>>()?;
+                    cells.values.push(value.clone());
+                    let reference = Ref {
+                        store: cells.store,
+                        index: cells.values.len() - 1,
+                    };
+                    Some(Ok(WanderValue::HostValue(HostValue {
+                        value: T::from_reference(reference),
+                    })))
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Ref.get",
+            doc_string: "Get the value held by a Ref.",
+            parameters: &["ref"],
+            requires: "a Ref",
+            pure: false,
+            run: |arguments, bindings| match arguments {
+                [reference] => {
+                    let reference = as_reference(reference)?;
+                    Some(
+                        bindings
+                            .context::<Cells<T>>()
+                            .and_then(|cells| cells.get(reference))
+                            .cloned()
+                            .ok_or_else(|| missing_cell(reference)),
+                    )
+                }
+                _ => None,
+            },
+        },
+        TableFunction {
+            name: "Ref.set",
+            doc_string: "Replace the value held by a Ref.",
+            parameters: &["ref", "value"],
+            requires: "a Ref and a value",
+            pure: false,
+            run: |arguments, bindings| match arguments {
+                [reference, value] => {
+                    let reference = as_reference(reference)?;
+                    Some(
+                        match bindings
+                            .context_mut::<Cells<T>>()
+                            .and_then(|cells| cells.get_mut(reference))
+                        {
+                            Some(cell) => {
+                                *cell = value.clone();
+                                Ok(WanderValue::Nothing)
+                            }
+                            None => Err(missing_cell(reference)),
+                        },
+                    )
+                }
+                _ => None,
+            },
+        },
+    ];
+    add_table_functions(bindings, functions);
+}
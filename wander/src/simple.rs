@@ -0,0 +1,47 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A non-generic interface to Wander for applications that don't add their own host types.
+//! Everything here uses NoHostType, so no type parameters need to be written.
+//!
+//! ```
+//! use wander::simple::{bindings, run, run_with, Value};
+//!
+//! assert_eq!(run("Int.add 1 2"), Ok(Value::Int(3)));
+//!
+//! let mut bindings = bindings();
+//! run_with("val x = 5", &mut bindings).unwrap();
+//! assert_eq!(run_with("x", &mut bindings), Ok(Value::Int(5)));
+//! ```
+
+use indexmap::IndexMap;
+
+use crate::{environment::Environment, preludes::common, NoHostType, WanderError, WanderValue};
+
+/// Bindings without any host types.
+pub type Bindings = Environment<NoHostType>;
+
+/// A value without any host types.
+pub type Value = WanderValue<NoHostType>;
+
+/// The fields of a Record Value.
+pub type Record = IndexMap<String, Value>;
+
+/// The result of running a script.
+pub type RunResult = Result<Value, WanderError>;
+
+/// Create Bindings with the common prelude.
+pub fn bindings() -> Bindings {
+    common()
+}
+
+/// Run a script with new Bindings that only have the common prelude.
+pub fn run(script: &str) -> RunResult {
+    run_with(script, &mut bindings())
+}
+
+/// Run a script with the given Bindings, so values bound at the top level stay bound.
+pub fn run_with(script: &str, bindings: &mut Bindings) -> RunResult {
+    crate::run(script, bindings)
+}
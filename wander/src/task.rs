@@ -0,0 +1,160 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Contains `Task.par`, for running independent Lambdas on multiple threads.
+//! Only available with the `parallel` feature.
+
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+};
+
+use crate::{
+    call_lambda, combine_errors,
+    environment::{Environment, ForkedContext},
+    HostFunction, HostFunctionBinding, HostFunctionCall, HostType, RunStats, WanderError,
+    WanderValue,
+};
+
+/// Creates the Environment each Lambda passed to `Task.par` runs in,
+/// since Environments can't be shared between threads.
+/// It should bind the same HostFunctions as the Environment `Task.par` was bound in.
+pub type EnvironmentFactory<T> = Arc<dyn Fn() -> Environment<T> + Send + Sync>;
+
+struct ParFunction<T: HostType> {
+    environment: EnvironmentFactory<T>,
+}
+
+impl<T: HostType + Send + Sync + 'static> HostFunction<T> for ParFunction<T> {
+    fn run(
+        &self,
+        arguments: &[WanderValue<T>],
+        bindings: &mut Environment<T>,
+    ) -> Result<WanderValue<T>, WanderError> {
+        match arguments {
+            [WanderValue::List(lambdas)]
+                if lambdas
+                    .iter()
+                    .all(|lambda| matches!(lambda, WanderValue::Lambda(..))) =>
+            {
+                par(lambdas, bindings, &self.environment)
+            }
+            _ => Err(WanderError::new(
+                "`Task.par` function requires a List of Lambdas.".to_owned(),
+            )),
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Task.par".to_owned(),
+            parameters: vec![("lambdas".to_owned(), None)],
+            result: None, // List
+            pure: true,
+            doc_string: "Call each Lambda in a List with nothing, in parallel, and return a List of their results. \
+                Errors from every Lambda that failed are combined."
+                .to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+// A HostFunction call made on a `Task.par` thread that the Environment `Task.par` was called in handles,
+// so calls are recorded by its AuditSink and skipped as part of its dry run.
+enum ForkedCall<T: HostType> {
+    Audit(HostFunctionCall),
+    Skip(String, Vec<WanderValue<T>>, mpsc::Sender<WanderValue<T>>),
+}
+
+// Run each Lambda on its own thread, so Lambdas waiting on a Channel can't block the Lambdas that send to it.
+// Each thread gets its own Environment with the values bound where `Task.par` was called,
+// running in the same execution context. The steps the threads take count against the caller's limit.
+fn par<T: HostType + Send + Sync + 'static>(
+    lambdas: &[WanderValue<T>],
+    bindings: &mut Environment<T>,
+    environment: &EnvironmentFactory<T>,
+) -> Result<WanderValue<T>, WanderError> {
+    let values = bindings.bound_values();
+    let context = bindings.fork_context();
+    let (calls, forked_calls) = mpsc::channel();
+    let results: Vec<(Result<WanderValue<T>, WanderError>, RunStats)> = thread::scope(|scope| {
+        let threads: Vec<_> = lambdas
+            .iter()
+            .map(|lambda| {
+                let (values, context, calls) = (&values, &context, calls.clone());
+                scope.spawn(move || run_forked(lambda, values, context, calls, environment))
+            })
+            .collect();
+        drop(calls);
+        // Every thread's Sender is dropped when it finishes, which ends this loop.
+        for call in forked_calls {
+            match call {
+                ForkedCall::Audit(call) => bindings.record_call(call),
+                ForkedCall::Skip(name, arguments, reply) => {
+                    let _ = reply.send(bindings.skip_call(name, arguments));
+                }
+            }
+        }
+        threads
+            .into_iter()
+            .map(|thread| match thread.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    });
+    bindings.charge_steps(results.iter().map(|(_, stats)| stats.steps).sum())?;
+    let mut values = vec![];
+    let mut errors = vec![];
+    for (result, _) in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(err) => errors.push(err),
+        }
+    }
+    if errors.is_empty() {
+        Ok(WanderValue::List(values))
+    } else {
+        Err(combine_errors(errors))
+    }
+}
+
+// Call a Lambda in a new Environment running in the forked context,
+// sending the calls the caller's Environment handles over `calls`.
+fn run_forked<T: HostType + Send + Sync + 'static>(
+    lambda: &WanderValue<T>,
+    values: &[(String, WanderValue<T>)],
+    context: &ForkedContext,
+    calls: mpsc::Sender<ForkedCall<T>>,
+    environment: &EnvironmentFactory<T>,
+) -> (Result<WanderValue<T>, WanderError>, RunStats) {
+    let mut environment = environment();
+    for (name, value) in values {
+        environment.bind(name.clone(), value.clone());
+    }
+    if context.audited {
+        let calls = calls.clone();
+        environment.set_audit_sink(Box::new(move |call: HostFunctionCall| {
+            let _ = calls.send(ForkedCall::Audit(call));
+        }));
+    }
+    if context.dry_run {
+        environment.start_dry_run(Box::new(move |name, arguments| {
+            let (reply, value) = mpsc::channel();
+            let _ = calls.send(ForkedCall::Skip(name.to_owned(), arguments.to_vec(), reply));
+            value.recv().unwrap_or(WanderValue::Nothing)
+        }));
+    }
+    let previous = environment.join_context(context);
+    let result = call_lambda(&mut environment, lambda, &[WanderValue::Nothing]);
+    (result, environment.restore_run_config(previous))
+}
+
+/// Bind `Task.par`, which runs Lambdas in Environments created by `environment`.
+pub fn add_task_functions<T: HostType + Send + Sync + 'static>(
+    bindings: &mut Environment<T>,
+    environment: EnvironmentFactory<T>,
+) {
+    bindings.bind_host_function(std::rc::Rc::new(ParFunction { environment }));
+}
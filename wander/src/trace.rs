@@ -0,0 +1,131 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Contains `run_traced`, which runs a script while recording every expression it evaluates,
+//! for tooling that shows how a script produced its result.
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde::Serialize;
+
+use crate::{
+    environment::{summarize, Environment},
+    interpreter::{EvalObserver, Expression},
+    run, HostType, Location, WanderError, WanderValue,
+};
+
+/// An expression evaluated while running a traced script.
+/// The expressions evaluated to produce its result, like the arguments of an Application,
+/// are its children, so their results are its inputs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraceNode {
+    /// The offset into the script of the expression.
+    pub position: usize,
+    /// The kind of expression, or the name or literal for names and literals.
+    pub expression: String,
+    /// A summary of the value the expression evaluated to, cut short like the summaries in
+    /// HostFunctionCalls so large values aren't copied into every node, or the error it raised.
+    pub result: Result<String, WanderError>,
+    /// The expressions evaluated inside of this one, in the order they were evaluated.
+    pub children: Vec<TraceNode>,
+}
+
+// Builds TraceNodes, keeping the position, description, and finished children
+// of each expression that is still being evaluated.
+struct TraceRecorder {
+    stack: Vec<(usize, String, Vec<TraceNode>)>,
+    roots: Rc<RefCell<Vec<TraceNode>>>,
+}
+
+impl<T: HostType> EvalObserver<T> for TraceRecorder {
+    fn before_eval(
+        &mut self,
+        expression: &Location<Expression>,
+        _environment: &Environment<T>,
+    ) -> Result<(), WanderError> {
+        self.stack
+            .push((expression.1, describe(&expression.0), vec![]));
+        Ok(())
+    }
+
+    fn after_eval(
+        &mut self,
+        _expression: &Location<Expression>,
+        result: &Result<WanderValue<T>, WanderError>,
+        _environment: &Environment<T>,
+    ) {
+        let Some((position, expression, children)) = self.stack.pop() else {
+            return;
+        };
+        let node = TraceNode {
+            position,
+            expression,
+            result: result.as_ref().map(summarize).map_err(Clone::clone),
+            children,
+        };
+        match self.stack.last_mut() {
+            Some((_, _, siblings)) => siblings.push(node),
+            None => self.roots.borrow_mut().push(node),
+        }
+    }
+}
+
+fn describe(expression: &Expression) -> String {
+    match expression {
+        Expression::Boolean(value) => value.to_string(),
+        Expression::Int(value) => value.to_string(),
+        Expression::Float(value) => value.to_string(),
+        Expression::String(value) => format!("{value:?}"),
+        Expression::Char(value) => format!("{value:?}"),
+        Expression::Name(name) | Expression::HostFunction(name) => name.clone(),
+        Expression::TaggedName(name, _) => name.clone(),
+        Expression::Nothing => "nothing".to_owned(),
+        Expression::Identifier(identifier) => identifier.to_string(),
+        Expression::Keyword(keyword) => keyword.to_string(),
+        Expression::CustomLiteral(literal) => literal.clone(),
+        Expression::Let(..) => "Let".to_owned(),
+        Expression::Application(_) => "Application".to_owned(),
+        Expression::Conditional(..) => "Conditional".to_owned(),
+        Expression::Lambda(..) => "Lambda".to_owned(),
+        Expression::Tuple(_) => "Tuple".to_owned(),
+        Expression::List(_) => "List".to_owned(),
+        Expression::Set(_) => "Set".to_owned(),
+        Expression::Record(_) => "Record".to_owned(),
+        Expression::RecordUpdate(..) => "RecordUpdate".to_owned(),
+        Expression::TailCall(name, _) => name.clone(),
+        Expression::FieldAccess(_, field) => format!(".{field}"),
+        Expression::Match(..) => "Match".to_owned(),
+        Expression::Sequence(_) => "Sequence".to_owned(),
+        Expression::Try(..) => "Try".to_owned(),
+        Expression::TryResult(_) => "TryResult".to_owned(),
+        Expression::Ensure(..) => "Ensure".to_owned(),
+        Expression::Pure(_) => "Pure".to_owned(),
+        Expression::Quantity(_, unit) => format!("Quantity {unit}"),
+        Expression::Quote(_, _) => "Quote".to_owned(),
+    }
+}
+
+/// Run a script, returning its result along with a trace of every expression evaluated.
+/// Only summaries of the values in the trace are kept, so tracing a script that builds
+/// large values doesn't copy each of them into every node.
+/// The trace is empty when the script can't be compiled.
+/// Any EvalObserver already set on the Environment isn't called while the script runs.
+pub fn run_traced<T: HostType + 'static>(
+    script: &str,
+    environment: &mut Environment<T>,
+) -> (Result<WanderValue<T>, WanderError>, Vec<TraceNode>) {
+    let roots = Rc::new(RefCell::new(vec![]));
+    let previous = environment.take_eval_observer();
+    environment.set_eval_observer(Box::new(TraceRecorder {
+        stack: vec![],
+        roots: roots.clone(),
+    }));
+    let result = run(script, environment);
+    environment.take_eval_observer();
+    if let Some(previous) = previous {
+        environment.set_eval_observer(previous);
+    }
+    let trace = roots.take();
+    (result, trace)
+}
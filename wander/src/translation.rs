@@ -2,23 +2,85 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::collections::HashMap;
+use std::collections::HashSet;
 
-use crate::{interpreter::Expression, parser::Element, WanderError, Location};
+use indexmap::IndexMap;
+
+use crate::{
+    environment::Environment,
+    interpreter::{value_element, Expression},
+    lexer::{tokenize_and_filter, transform},
+    parser::{parse, Element, Pattern},
+    HostType, Location, Span, WanderError,
+};
 
 // Handle any tranlations needed before creating an expression.
-pub fn translate(element: Location<Element>) -> Result<Location<Expression>, WanderError> {
+// Every top-level Element is checked and all errors found are returned together.
+// `import` declarations are resolved first, then `use` declarations,
+// warnings about names they shadow are added to the Environment.
+pub fn translate<T: HostType>(
+    element: Location<Element>,
+    bindings: &Environment<T>,
+) -> Result<Location<Expression>, Vec<WanderError>> {
+    let mut element = element;
+    let mut errors = vec![];
+    let mut names = HashSet::new();
+    resolve_imports(&mut element, bindings, &mut vec![], &mut names, &mut errors);
+    names.extend(bindings.bound_names());
+    resolve_uses(&mut element, bindings, &names, &mut errors);
+    resolve_literals(&mut element, bindings, &mut errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    resolve_fields(&mut element, &names, &mut vec![]);
+    translate_element(element)
+}
+
+fn translate_element(element: Location<Element>) -> Result<Location<Expression>, Vec<WanderError>> {
+    if let Location(Element::Sequence(elements), position) = element {
+        let mut expressions = vec![];
+        let mut errors = vec![];
+        for element in elements {
+            match translate_element(element) {
+                Ok(expression) => expressions.push(expression),
+                Err(mut err) => errors.append(&mut err),
+            }
+        }
+        return if errors.is_empty() {
+            Ok(Location(Expression::Sequence(expressions), position))
+        } else {
+            Err(errors)
+        };
+    }
     let element = process_pipes(&element)?;
-    express(&element)
+    match element {
+        Location(Element::Grouping(elements), position) if !has_placeholder(&elements) => {
+            let mut expressions = vec![];
+            let mut errors = vec![];
+            for element in &elements {
+                match express(element) {
+                    Ok(expression) => expressions.push(expression),
+                    Err(err) => errors.push(err),
+                }
+            }
+            if errors.is_empty() {
+                Ok(group_expressions(expressions, position))
+            } else {
+                Err(errors)
+            }
+        }
+        element => express(&element).map_err(|err| vec![err]),
+    }
 }
 
-fn process_pipes(element: &Location<Element>) -> Result<Location<Element>, WanderError> {
+fn process_pipes(element: &Location<Element>) -> Result<Location<Element>, Vec<WanderError>> {
     let (elements, position) = match element {
         Location(Element::Grouping(elements), position) => (elements, position),
         e => return Ok(e.clone()),
     };
     let mut index = 0;
     let mut results = vec![];
+    let mut errors = vec![];
     while let Some(element) = elements.get(index) {
         if element.0 == Element::Pipe {
             index += 1;
@@ -30,14 +92,18 @@ fn process_pipes(element: &Location<Element>) -> Result<Location<Element>, Wande
                     new_results.push(Location(Element::Grouping(next_elements.clone()), element.1));
                     results = new_results;
                 }
-                _ => return Err(WanderError("Invalid pipe.".to_owned())),
+                _ => errors.push(WanderError::at("Invalid pipe".to_owned(), element.1)),
             }
         } else {
             results.push(element.clone());
         }
         index += 1;
     }
-    Ok(Location(Element::Grouping(results), *position))
+    if errors.is_empty() {
+        Ok(Location(Element::Grouping(results), *position))
+    } else {
+        Err(errors)
+    }
 }
 
 fn express_optional_name(name: &Option<String>) -> Result<Option<Location<Expression>>, WanderError> {
@@ -51,67 +117,856 @@ pub fn express(element: &Location<Element>) -> Result<Location<Expression>, Wand
     let expression = match element {
         Location(Element::Boolean(val), position) => Location(Expression::Boolean(*val), *position),
         Location(Element::Int(val), position) => Location(Expression::Int(*val), *position),
+        Location(Element::Float(val), position) => Location(Expression::Float(*val), *position),
         Location(Element::String(val), position) => Location(Expression::String(val.clone()), *position),
+        Location(Element::Char(val), position) => Location(Expression::Char(*val), *position),
         Location(Element::Identifier(value), position) => Location(Expression::Identifier(value.clone()), *position),
+        Location(Element::Keyword(value), position) => Location(Expression::Keyword(*value), *position),
         Location(Element::Name(name), position) => Location(Expression::Name(name.clone()), *position),
         Location(Element::Let(decls, body), position) => Location(Expression::Let(
             decls
-                .clone()
                 .iter()
                 .map(|e| {
-                    (
+                    Ok((
                         e.0.clone(),
-                        express_optional_name(&e.1).unwrap(),
-                        express(&e.2).unwrap(),
-                    )
+                        express_optional_name(&e.1)?,
+                        express(&mark_tail_calls(&e.0, &e.2))?,
+                    ))
                 })
-                .collect(),
-            Box::new(express(body).unwrap()),
+                .collect::<Result<Vec<_>, WanderError>>()?,
+            Box::new(express(body)?),
         ), *position),
-        Location(Element::Grouping(elements), position) => return handle_grouping(elements),
+        Location(Element::Grouping(elements), position) => {
+            if has_placeholder(elements) {
+                shorthand_lambda(elements, *position)
+            } else {
+                return handle_grouping(elements, *position);
+            }
+        }
         Location(Element::Conditional(i, ie, ee), position) => Location(Expression::Conditional(
-            Box::new(express(i).unwrap()),
-            Box::new(express(ie).unwrap()),
-            Box::new(express(ee).unwrap()),
+            Box::new(express(i)?),
+            Box::new(express(ie)?),
+            Box::new(express(ee)?),
         ), *position),
         Location(Element::Lambda(p, i, o, b), position) => {
             Location(Expression::Lambda(p.clone(), i.clone(), o.clone(), b.clone()), *position)
         }
         Location(Element::Tuple(values), position) => {
-            Location(Expression::Tuple(values.clone().iter().map(|e| express(e).unwrap()).collect()), *position)
+            Location(Expression::Tuple(values.iter().map(express).collect::<Result<_, _>>()?), *position)
         }
         Location(Element::List(values), position) => {
-            Location(Expression::List(values.clone().iter().map(|e| express(e).unwrap()).collect()), *position)
+            Location(Expression::List(values.iter().map(express).collect::<Result<_, _>>()?), *position)
         }
         Location(Element::Set(values), position) => {
-            Location(Expression::Set(values.clone().iter().map(|e| express(e).unwrap()).collect()), *position)
+            Location(Expression::Set(values.iter().map(express).collect::<Result<_, _>>()?), *position)
         }
         Location(Element::Record(values), position) => {
-            let mut result: HashMap<String, Location<Expression>> = HashMap::new();
-            values
-                .iter()
-                .map(|e| (e.0, express(e.1).unwrap()))
-                .for_each(|e| {
-                    result.insert(e.0.clone(), e.1);
-                });
+            let mut result: IndexMap<String, Location<Expression>> = IndexMap::new();
+            for (name, value) in values {
+                result.insert(name.clone(), express(value)?);
+            }
             Location(Expression::Record(result), *position)
         }
+        Location(Element::RecordUpdate(base, values), position) => {
+            let mut result: IndexMap<String, Location<Expression>> = IndexMap::new();
+            for (name, value) in values {
+                result.insert(name.clone(), express(value)?);
+            }
+            Location(Expression::RecordUpdate(Box::new(express(base)?), result), *position)
+        }
         Location(Element::Nothing, position) => Location(Expression::Nothing, *position),
         Location(Element::Pipe, position) => {
-            return Err(WanderError(
+            return Err(WanderError::new(
                 "Cannot process pipe, Should never reach.".to_owned(),
             ))
         }
         Location(Element::HostFunction(name), position) => Location(Expression::HostFunction(name.clone()), *position),
         Location(Element::TaggedName(name, tag), position) => {
-            Location(Expression::TaggedName(name.clone(), Box::new(express(tag).unwrap())), *position)
+            Location(Expression::TaggedName(name.clone(), Box::new(express(tag)?)), *position)
+        }
+        Location(Element::TailCall(name, arguments), position) => Location(
+            Expression::TailCall(name.clone(), arguments.iter().map(express).collect::<Result<_, _>>()?),
+            *position,
+        ),
+        Location(Element::Infix(operator, left, right), position) => {
+            express_infix(operator, express(left)?, express(right)?, *position)?
+        }
+        Location(Element::Prefix(operator, operand), position) => {
+            express_prefix(operator, express(operand)?, *position)?
+        }
+        Location(Element::FieldAccess(value, field), position) => Location(
+            Expression::FieldAccess(Box::new(express(value)?), field.clone()),
+            *position,
+        ),
+        Location(Element::Match(value, arms), position) => Location(
+            Expression::Match(
+                Box::new(express(value)?),
+                arms.iter()
+                    .map(|(pattern, body)| Ok((pattern.clone(), express(body)?)))
+                    .collect::<Result<_, WanderError>>()?,
+            ),
+            *position,
+        ),
+        Location(Element::TryResult(body), position) => {
+            Location(Expression::TryResult(Box::new(express(body)?)), *position)
+        }
+        Location(Element::Pure(body), position) => {
+            Location(Expression::Pure(Box::new(express(body)?)), *position)
+        }
+        Location(Element::Try(body, name, handler), position) => Location(
+            Expression::Try(Box::new(express(body)?), name.clone(), Box::new(express(handler)?)),
+            *position,
+        ),
+        Location(Element::Ensure(body, cleanup), position) => Location(
+            Expression::Ensure(Box::new(express(body)?), Box::new(express(cleanup)?)),
+            *position,
+        ),
+        Location(Element::Quantity(value, unit), position) => Location(
+            Expression::Quantity(Box::new(express(value)?), unit.clone()),
+            *position,
+        ),
+        Location(Element::CustomLiteral(literal), position) => {
+            Location(Expression::CustomLiteral(literal.clone()), *position)
+        }
+        Location(Element::Quote(quoted), position) => {
+            let mut unquoted = vec![];
+            replace_unquotes(&mut quoted.as_ref().clone(), &mut |element: &Location<Element>| {
+                if let Element::Unquote(value) | Element::Splice(value) = &element.0 {
+                    unquoted.push(express(value)?);
+                }
+                Ok(vec![element.clone()])
+            })?;
+            Location(Expression::Quote(quoted.clone(), unquoted), *position)
+        }
+        Location(Element::Unquote(_), position) => {
+            return Err(WanderError::at(
+                "`~` can only be used inside of a quote.".to_owned(),
+                *position,
+            ))
+        }
+        Location(Element::Splice(_), position) => {
+            return Err(WanderError::at(
+                "`~@` can only be used inside of a quote.".to_owned(),
+                *position,
+            ))
+        }
+        Location(Element::Sequence(elements), position) => {
+            Location(Expression::Sequence(elements.iter().map(express).collect::<Result<_, _>>()?), *position)
+        }
+        Location(Element::Use(namespace, _, _), position) => {
+            return Err(WanderError::at(
+                format!("`use {namespace}` should have been resolved before reaching express"),
+                *position,
+            ))
+        }
+        Location(Element::Import(namespace, _, _), position) => {
+            return Err(WanderError::at(
+                format!("`import {namespace}` should have been resolved before reaching express"),
+                *position,
+            ))
         }
     };
     Ok(expression)
 }
 
-fn handle_grouping(elements: &[Location<Element>]) -> Result<Location<Expression>, WanderError> {
-    let expressions: Vec<Location<Expression>> = elements.iter().map(|e| express(e).unwrap()).collect();
+// A namespace brought into scope by a `use` declaration.
+struct Import {
+    namespace: String,
+    // The alias from `use Namespace as Alias`, None for `use Namespace.*`.
+    alias: Option<String>,
+    // The unqualified names of the HostFunctions in the namespace.
+    names: HashSet<String>,
+}
+
+impl Import {
+    // The full name that a name refers to, if this Import applies to it.
+    fn resolve(&self, name: &str) -> Option<String> {
+        match &self.alias {
+            Some(alias) => name
+                .strip_prefix(alias.as_str())
+                .filter(|rest| rest.starts_with('.'))
+                .map(|rest| format!("{}{rest}", self.namespace)),
+            None => self
+                .names
+                .contains(name)
+                .then(|| format!("{}.{name}", self.namespace)),
+        }
+    }
+
+    // Check if binding a local value with this name hides part of this Import.
+    fn is_shadowed_by(&self, name: &str) -> bool {
+        match &self.alias {
+            Some(alias) => alias == name,
+            None => self.names.contains(name),
+        }
+    }
+}
+
+// Replace each `import Namespace from "path"` with the statements of the module it loads,
+// with the names the module binds at its top level moved into the namespace.
+// `importing` holds the paths of the modules being imported, to catch cycles,
+// and the full names of everything imported are added to `imported`.
+fn resolve_imports<T: HostType>(
+    element: &mut Location<Element>,
+    bindings: &Environment<T>,
+    importing: &mut Vec<String>,
+    imported: &mut HashSet<String>,
+    errors: &mut Vec<WanderError>,
+) {
+    if let Location(Element::Import(namespace, path, body), position) = element {
+        let position = *position;
+        let (namespace, path) = (namespace.clone(), path.clone());
+        let mut body = std::mem::replace(body.as_mut(), Location(Element::Nothing, 0));
+        resolve_imports(&mut body, bindings, importing, imported, errors);
+        *element = match load_module(&namespace, &path, position, bindings, importing, errors) {
+            Some((module, names)) => {
+                imported.extend(names.iter().map(|name| format!("{namespace}.{name}")));
+                Location(Element::Sequence(vec![module, body]), position)
+            }
+            None => body,
+        };
+    }
+}
+
+// Parse the module at `path` and qualify the names it binds with `namespace`.
+// Returns the module and the unqualified names it binds.
+fn load_module<T: HostType>(
+    namespace: &str,
+    path: &str,
+    position: usize,
+    bindings: &Environment<T>,
+    importing: &mut Vec<String>,
+    errors: &mut Vec<WanderError>,
+) -> Option<(Location<Element>, HashSet<String>)> {
+    let fail = |reason: &str| {
+        WanderError::at(
+            format!("Could not import {path}: {}", reason.trim_end_matches('.')),
+            position,
+        )
+    };
+    if importing.iter().any(|importing| importing == path) {
+        errors.push(fail("the module imports itself"));
+        return None;
+    }
+    let source = match bindings.module_resolver() {
+        Some(resolver) => resolver.resolve(path),
+        None => Err(WanderError::new("No ModuleResolver is set.".to_owned())),
+    };
+    let source = match source {
+        Ok(source) => source,
+        Err(err) => {
+            errors.push(fail(&err.message));
+            return None;
+        }
+    };
+    let module = tokenize_and_filter(&source)
+        .and_then(|tokens| transform(&tokens, bindings))
+        .and_then(parse);
+    let mut module = match module {
+        Ok(module) => module,
+        Err(err) => {
+            let message = err.locate(&source).message;
+            errors.push(WanderError::new(format!(
+                "{} in {path}.",
+                message.trim_end_matches('.')
+            )));
+            return None;
+        }
+    };
+    importing.push(path.to_owned());
+    resolve_imports(&mut module, bindings, importing, &mut HashSet::new(), errors);
+    importing.pop();
+    let mut names = HashSet::new();
+    module_names(&module, &mut names);
+    qualify(&mut module, namespace, &names, &mut vec![]);
+    relocate(&mut module, position);
+    Some((module, names))
+}
+
+// Collect the names a module binds at its top level.
+fn module_names(element: &Location<Element>, names: &mut HashSet<String>) {
+    match &element.0 {
+        Element::Sequence(values) => values.iter().for_each(|value| module_names(value, names)),
+        Element::Use(_, _, body) => module_names(body, names),
+        Element::Let(decls, body) => {
+            names.extend(decls.iter().map(|(name, _, _)| name.clone()));
+            module_names(body, names);
+        }
+        _ => (),
+    }
+}
+
+// Add the namespace to every binding of and reference to the names a module binds at its top level.
+// `locals` holds the names bound around the element, which hide the module's names.
+fn qualify(
+    element: &mut Location<Element>,
+    namespace: &str,
+    names: &HashSet<String>,
+    locals: &mut Vec<String>,
+) {
+    let scope = locals.len();
+    match &mut element.0 {
+        Element::Name(name) => qualify_name(name, namespace, names, locals),
+        Element::Let(decls, body) => {
+            for (name, _, value) in decls {
+                qualify_name(name, namespace, names, locals);
+                qualify(value, namespace, names, locals);
+            }
+            qualify(body, namespace, names, locals);
+        }
+        Element::Lambda(parameter, _, _, body) => {
+            locals.push(parameter.clone());
+            qualify(body, namespace, names, locals);
+        }
+        Element::Try(body, name, handler) => {
+            qualify(body, namespace, names, locals);
+            locals.push(name.clone());
+            qualify(handler, namespace, names, locals);
+        }
+        Element::Match(value, arms) => {
+            qualify(value, namespace, names, locals);
+            for (pattern, body) in arms {
+                pattern_names(pattern, locals);
+                qualify(body, namespace, names, locals);
+                locals.truncate(scope);
+            }
+        }
+        _ => visit_children(element, &mut |child| qualify(child, namespace, names, locals)),
+    }
+    locals.truncate(scope);
+}
+
+// Qualify a name, or a field access written as one like `config.port`, if it starts with a module name.
+fn qualify_name(name: &mut String, namespace: &str, names: &HashSet<String>, locals: &[String]) {
+    let base = name.split('.').next().unwrap_or_default();
+    let bound = names.contains(name.as_str())
+        || name
+            .match_indices('.')
+            .any(|(index, _)| names.contains(&name[..index]));
+    if bound && !locals.iter().any(|local| local == base) {
+        *name = format!("{namespace}.{name}");
+    }
+}
+
+// Move every Element to the given position, so errors in an imported module point at its import.
+fn relocate(element: &mut Location<Element>, position: usize) {
+    element.1 = position;
+    visit_children(element, &mut |child| relocate(child, position));
+}
+
+// Replace every `use` declaration with its body, after resolving the names in the body.
+fn resolve_uses<T: HostType>(
+    element: &mut Location<Element>,
+    bindings: &Environment<T>,
+    bound_names: &HashSet<String>,
+    errors: &mut Vec<WanderError>,
+) {
+    if let Location(Element::Use(namespace, alias, body), position) = element {
+        let prefix = format!("{namespace}.");
+        let names: HashSet<String> = bound_names
+            .iter()
+            .filter_map(|name| name.strip_prefix(&prefix))
+            .filter(|name| alias.is_some() || !name.contains('.'))
+            .map(|name| name.to_owned())
+            .collect();
+        if names.is_empty() {
+            errors.push(WanderError::at(
+                format!("Cannot use {namespace}, no module named {namespace} is bound"),
+                *position,
+            ));
+            return;
+        }
+        let import = Import {
+            namespace: namespace.clone(),
+            alias: alias.clone(),
+            names,
+        };
+        let mut body = std::mem::replace(body.as_mut(), Location(Element::Nothing, 0));
+        resolve_uses(&mut body, bindings, bound_names, errors);
+        let mut warnings = vec![];
+        use_import(&mut body, &import, &mut vec![], &mut warnings);
+        warnings.into_iter().for_each(|warning| bindings.warn(warning));
+        *element = body;
+    } else {
+        visit_children(element, &mut |child| resolve_uses(child, bindings, bound_names, errors));
+    }
+}
+
+// Turn dotted names like `record.a.b` into field accesses. A name whose first part is bound
+// in the script, by `let`, a lambda or a pattern, is always a field access on that binding,
+// even when the whole name is bound in the Environment. Other names that are bound in full,
+// like `Bool.not`, are left alone, and the rest become field accesses on their longest
+// bound prefix, or on their first part when no prefix is bound.
+// `locals` holds the names bound around the element.
+fn resolve_fields(
+    element: &mut Location<Element>,
+    bound_names: &HashSet<String>,
+    locals: &mut Vec<String>,
+) {
+    let scope = locals.len();
+    match &mut element.0 {
+        Element::Name(name) if name.contains('.') => {
+            if let Some(access) = field_access(name, element.1, bound_names, locals) {
+                *element = access;
+            }
+        }
+        Element::Let(decls, body) => {
+            for (name, _, value) in decls {
+                resolve_fields(value, bound_names, locals);
+                locals.push(name.clone());
+            }
+            resolve_fields(body, bound_names, locals);
+        }
+        Element::Lambda(parameter, _, _, body) => {
+            locals.push(parameter.clone());
+            resolve_fields(body, bound_names, locals);
+        }
+        Element::Try(body, name, handler) => {
+            resolve_fields(body, bound_names, locals);
+            locals.push(name.clone());
+            resolve_fields(handler, bound_names, locals);
+        }
+        Element::Match(value, arms) => {
+            resolve_fields(value, bound_names, locals);
+            for (pattern, body) in arms {
+                pattern_names(pattern, locals);
+                resolve_fields(body, bound_names, locals);
+                locals.truncate(scope);
+            }
+        }
+        Element::Sequence(values) => {
+            for value in values {
+                resolve_fields(value, bound_names, locals);
+                if let Element::Let(decls, _) = &value.0 {
+                    locals.extend(decls.iter().map(|(name, _, _)| name.clone()));
+                }
+            }
+        }
+        _ => visit_children(element, &mut |child| resolve_fields(child, bound_names, locals)),
+    }
+    locals.truncate(scope);
+}
+
+fn field_access(
+    name: &str,
+    position: usize,
+    bound_names: &HashSet<String>,
+    locals: &[String],
+) -> Option<Location<Element>> {
+    let first = name.split('.').next().unwrap_or_default();
+    let base = if locals.iter().any(|local| local == first) {
+        first
+    } else if bound_names.contains(name) {
+        return None;
+    } else {
+        name.rmatch_indices('.')
+            .map(|(index, _)| &name[..index])
+            .find(|prefix| bound_names.contains(*prefix))
+            .unwrap_or(first)
+    };
+    let mut result = Location(Element::Name(base.to_owned()), position);
+    for field in name[base.len() + 1..].split('.') {
+        result = Location(Element::FieldAccess(Box::new(result), field.to_owned()), position);
+    }
+    Some(result)
+}
+
+// Replace the names an Import applies to with their full names.
+// `locals` holds the names bound around the element, which hide the Import.
+fn use_import(
+    element: &mut Location<Element>,
+    import: &Import,
+    locals: &mut Vec<String>,
+    warnings: &mut Vec<WanderError>,
+) {
+    let scope = locals.len();
+    match &mut element.0 {
+        Element::Name(name) => {
+            let base = name.split('.').next().unwrap_or(name);
+            if !locals.iter().any(|local| local == base) {
+                if let Some(full_name) = import.resolve(name) {
+                    *name = full_name;
+                }
+            }
+        }
+        Element::Let(decls, body) => {
+            for (name, _, value) in decls {
+                use_import(value, import, locals, warnings);
+                if import.is_shadowed_by(name) {
+                    warnings.push(shadow_warning(name, import, value.1));
+                }
+                locals.push(name.clone());
+            }
+            use_import(body, import, locals, warnings);
+        }
+        Element::Lambda(parameter, _, _, body) => {
+            locals.push(parameter.clone());
+            use_import(body, import, locals, warnings);
+        }
+        Element::Try(body, name, handler) => {
+            use_import(body, import, locals, warnings);
+            locals.push(name.clone());
+            use_import(handler, import, locals, warnings);
+        }
+        Element::Match(value, arms) => {
+            use_import(value, import, locals, warnings);
+            for (pattern, body) in arms {
+                pattern_names(pattern, locals);
+                use_import(body, import, locals, warnings);
+                locals.truncate(scope);
+            }
+        }
+        // Values bound at the top of a script stay bound for the rest of it.
+        Element::Sequence(values) => {
+            for value in values {
+                use_import(value, import, locals, warnings);
+                if let Element::Let(decls, _) = &value.0 {
+                    locals.extend(decls.iter().map(|(name, _, _)| name.clone()));
+                }
+            }
+        }
+        _ => visit_children(element, &mut |child| use_import(child, import, locals, warnings)),
+    }
+    locals.truncate(scope);
+}
+
+fn shadow_warning(name: &str, import: &Import, position: usize) -> WanderError {
+    match &import.alias {
+        Some(_) => WanderError::at(
+            format!("{name} shadows the alias for {}", import.namespace),
+            position,
+        ),
+        None => WanderError::at(
+            format!("{name} shadows {}.{name}", import.namespace),
+            position,
+        ),
+    }
+}
+
+fn pattern_names(pattern: &Pattern, names: &mut Vec<String>) {
+    match pattern {
+        Pattern::Name(name) => names.push(name.clone()),
+        Pattern::Tuple(patterns) | Pattern::List(patterns) => {
+            patterns.iter().for_each(|pattern| pattern_names(pattern, names))
+        }
+        Pattern::Record(patterns) => patterns
+            .values()
+            .for_each(|pattern| pattern_names(pattern, names)),
+        _ => (),
+    }
+}
+
+// Parse every custom literal with the LiteralParser bound for it, so a literal without a parser
+// or with text its parser rejects is an error before the script runs. Literals whose values
+// can be written as Elements are replaced by them, others, like HostValues, are kept and
+// parsed again when they're evaluated. Quoted literals are parsed when the quote is evaluated.
+fn resolve_literals<T: HostType>(
+    element: &mut Location<Element>,
+    bindings: &Environment<T>,
+    errors: &mut Vec<WanderError>,
+) {
+    match &mut element.0 {
+        Element::CustomLiteral(literal) => {
+            let literal = literal.clone();
+            let value = match bindings.read_literal_parser(&literal) {
+                Some((parser, text)) => parser(text),
+                None => Err(WanderError::new(format!("No LiteralParser bound for {literal}"))),
+            };
+            match value {
+                Ok(value) => {
+                    if let Ok(resolved) = value_element(value, element.1) {
+                        *element = resolved;
+                    }
+                }
+                Err(err) => errors.push(WanderError {
+                    span: err.span.or(Some(Span {
+                        start: element.1,
+                        end: element.1,
+                    })),
+                    ..err
+                }),
+            }
+        }
+        Element::Quote(quoted) => {
+            // Errors from misplaced splices are reported when the quote is expressed.
+            let _ = replace_unquotes(quoted, &mut |unquote: &Location<Element>| {
+                let mut unquote = unquote.clone();
+                if let Element::Unquote(value) | Element::Splice(value) = &mut unquote.0 {
+                    resolve_literals(value, bindings, errors);
+                }
+                Ok(vec![unquote])
+            });
+        }
+        _ => visit_children(element, &mut |child| resolve_literals(child, bindings, errors)),
+    }
+}
+
+// Call a function on every Element directly inside of an Element.
+fn visit_children(element: &mut Location<Element>, visit: &mut dyn FnMut(&mut Location<Element>)) {
+    match &mut element.0 {
+        Element::TaggedName(_, value)
+        | Element::Lambda(_, _, _, value)
+        | Element::Prefix(_, value)
+        | Element::FieldAccess(value, _)
+        | Element::TryResult(value)
+        | Element::Pure(value)
+        | Element::Quantity(value, _)
+        | Element::Quote(value)
+        | Element::Unquote(value)
+        | Element::Splice(value)
+        | Element::Use(_, _, value)
+        | Element::Import(_, _, value) => visit(value),
+        Element::Let(decls, body) => {
+            for (_, _, value) in decls {
+                visit(value);
+            }
+            visit(body);
+        }
+        Element::Grouping(values)
+        | Element::Tuple(values)
+        | Element::List(values)
+        | Element::Set(values)
+        | Element::Sequence(values)
+        | Element::TailCall(_, values) => values.iter_mut().for_each(visit),
+        Element::Record(values) => values.values_mut().for_each(visit),
+        Element::RecordUpdate(base, values) => {
+            visit(base);
+            values.values_mut().for_each(visit);
+        }
+        Element::Conditional(condition, then, otherwise) => {
+            visit(condition);
+            visit(then);
+            visit(otherwise);
+        }
+        Element::Infix(_, left, right)
+        | Element::Try(left, _, right)
+        | Element::Ensure(left, right) => {
+            visit(left);
+            visit(right);
+        }
+        Element::Match(value, arms) => {
+            visit(value);
+            arms.iter_mut().for_each(|(_, body)| visit(body));
+        }
+        _ => (),
+    }
+}
+
+// Call `unquote` on every `~` and `~@` in a quoted Element, in order, and put the Elements
+// it returns in their place. `~` is replaced by a single Element, `~@` is replaced by any
+// number of Elements so it can only be used directly inside of a List, Tuple, Set, or call.
+// A quote inside of the quote keeps its own unquotes.
+pub(crate) fn replace_unquotes<F>(element: &mut Location<Element>, unquote: &mut F) -> Result<(), WanderError>
+where
+    F: FnMut(&Location<Element>) -> Result<Vec<Location<Element>>, WanderError>,
+{
+    match element.0 {
+        Element::Quote(_) => Ok(()),
+        Element::Unquote(_) => {
+            if let Some(replacement) = unquote(element)?.pop() {
+                *element = replacement;
+            }
+            Ok(())
+        }
+        Element::Splice(_) => Err(WanderError::at(
+            "`~@` can only be used inside of a List, Tuple, Set, or call.".to_owned(),
+            element.1,
+        )),
+        Element::Grouping(ref mut values)
+        | Element::Tuple(ref mut values)
+        | Element::List(ref mut values)
+        | Element::Set(ref mut values) => {
+            let mut replaced = vec![];
+            for mut value in std::mem::take(values) {
+                if matches!(value.0, Element::Splice(_)) {
+                    replaced.extend(unquote(&value)?);
+                } else {
+                    replace_unquotes(&mut value, unquote)?;
+                    replaced.push(value);
+                }
+            }
+            *values = replaced;
+            Ok(())
+        }
+        _ => {
+            let mut result = Ok(());
+            visit_children(element, &mut |child| {
+                if result.is_ok() {
+                    result = replace_unquotes(child, unquote);
+                }
+            });
+            result
+        }
+    }
+}
+
+// Boolean operators become Conditionals so the right side is only evaluated when needed,
+// other operators become applications of the matching HostFunction.
+// Arithmetic, comparison, and `++` use the Core functions that work for more than one type.
+fn express_infix(
+    operator: &str,
+    left: Location<Expression>,
+    right: Location<Expression>,
+    position: usize,
+) -> Result<Location<Expression>, WanderError> {
+    let boolean = |value| Box::new(Location(Expression::Boolean(value), position));
+    let check_bool = |value| {
+        Box::new(Location(
+            Expression::Conditional(Box::new(value), boolean(true), boolean(false)),
+            position,
+        ))
+    };
+    let apply = |name: &str, arguments: Vec<Location<Expression>>| {
+        let mut expressions = vec![Location(Expression::Name(name.to_owned()), position)];
+        expressions.extend(arguments);
+        Location(Expression::Application(expressions), position)
+    };
+    match operator {
+        "&&" => Ok(Location(
+            Expression::Conditional(Box::new(left), check_bool(right), boolean(false)),
+            position,
+        )),
+        "||" => Ok(Location(
+            Expression::Conditional(Box::new(left), boolean(true), check_bool(right)),
+            position,
+        )),
+        "==" => Ok(apply("Core.eq", vec![left, right])),
+        "!=" => Ok(apply("Bool.not", vec![apply("Core.eq", vec![left, right])])),
+        "<" => Ok(apply("Core.lt", vec![left, right])),
+        ">" => Ok(apply("Core.gt", vec![left, right])),
+        "<=" => Ok(apply("Core.lte", vec![left, right])),
+        ">=" => Ok(apply("Core.gte", vec![left, right])),
+        "++" => Ok(apply("Core.concat", vec![left, right])),
+        "+" => Ok(apply("Core.add", vec![left, right])),
+        "-" => Ok(apply("Core.sub", vec![left, right])),
+        "*" => Ok(apply("Core.mul", vec![left, right])),
+        "/" => Ok(apply("Core.div", vec![left, right])),
+        _ => Err(WanderError::new(format!("Unknown operator {operator}."))),
+    }
+}
+
+fn express_prefix(
+    operator: &str,
+    operand: Location<Expression>,
+    position: usize,
+) -> Result<Location<Expression>, WanderError> {
+    let name = match operator {
+        "!" => "Bool.not",
+        "-" => "Int.negate",
+        _ => return Err(WanderError::new(format!("Unknown operator {operator}."))),
+    };
+    Ok(Location(
+        Expression::Application(vec![Location(Expression::Name(name.to_owned()), position), operand]),
+        position,
+    ))
+}
+
+// Rewrite calls to `name` that are in tail position of the Lambda bound to `name`
+// into TailCalls, so the interpreter can evaluate them as a loop.
+fn mark_tail_calls(name: &str, element: &Location<Element>) -> Location<Element> {
+    match element {
+        Location(Element::Grouping(elements), position) if elements.len() == 1 => Location(
+            Element::Grouping(vec![mark_tail_calls(name, &elements[0])]),
+            *position,
+        ),
+        Location(Element::Lambda(..), _) => mark_lambda_body(name, 0, element),
+        e => e.clone(),
+    }
+}
+
+fn mark_lambda_body(name: &str, arity: usize, element: &Location<Element>) -> Location<Element> {
+    match element {
+        Location(Element::Lambda(p, _, _, _), _) if p == name => element.clone(),
+        Location(Element::Lambda(p, i, o, b), position) => Location(
+            Element::Lambda(p.clone(), i.clone(), o.clone(), Box::new(mark_lambda_body(name, arity + 1, b))),
+            *position,
+        ),
+        body => mark_tail_position(name, arity, body),
+    }
+}
+
+fn mark_tail_position(name: &str, arity: usize, element: &Location<Element>) -> Location<Element> {
+    match element {
+        Location(Element::Grouping(elements), position) => match &elements[..] {
+            [single] => Location(
+                Element::Grouping(vec![mark_tail_position(name, arity, single)]),
+                *position,
+            ),
+            [Location(Element::Name(callee), _), arguments @ ..]
+                if callee == name && arguments.len() == arity =>
+            {
+                Location(Element::TailCall(callee.clone(), arguments.to_vec()), *position)
+            }
+            _ => element.clone(),
+        },
+        Location(Element::Conditional(c, i, e), position) => Location(
+            Element::Conditional(
+                c.clone(),
+                Box::new(mark_tail_position(name, arity, i)),
+                Box::new(mark_tail_position(name, arity, e)),
+            ),
+            *position,
+        ),
+        Location(Element::Match(value, arms), position) => Location(
+            Element::Match(
+                value.clone(),
+                arms.iter()
+                    .map(|(pattern, body)| (pattern.clone(), mark_tail_position(name, arity, body)))
+                    .collect(),
+            ),
+            *position,
+        ),
+        Location(Element::Let(decls, body), position) if !decls.iter().any(|decl| decl.0 == name) => {
+            Location(
+                Element::Let(decls.clone(), Box::new(mark_tail_position(name, arity, body))),
+                *position,
+            )
+        }
+        e => e.clone(),
+    }
+}
+
+// The parameter name used by shorthand lambdas, it can't be written in a script.
+const PLACEHOLDER_PARAMETER: &str = "$_";
+
+fn has_placeholder(elements: &[Location<Element>]) -> bool {
+    elements
+        .iter()
+        .any(|element| element.0 == Element::Name("_".to_owned()))
+}
+
+// An application containing `_` arguments, like `Bool.and _ true`,
+// becomes a single parameter Lambda with `_` replaced by the parameter.
+fn shorthand_lambda(elements: &[Location<Element>], position: usize) -> Location<Expression> {
+    let body = elements
+        .iter()
+        .map(|element| match element {
+            Location(Element::Name(name), position) if name == "_" => {
+                Location(Element::Name(PLACEHOLDER_PARAMETER.to_owned()), *position)
+            }
+            element => element.clone(),
+        })
+        .collect();
+    Location(
+        Expression::Lambda(
+            PLACEHOLDER_PARAMETER.to_owned(),
+            None,
+            None,
+            Box::new(Location(Element::Grouping(body), position)),
+        ),
+        position,
+    )
+}
+
+fn handle_grouping(elements: &[Location<Element>], position: usize) -> Result<Location<Expression>, WanderError> {
+    let expressions = elements
+        .iter()
+        .map(express)
+        .collect::<Result<Vec<Location<Expression>>, WanderError>>()?;
+    Ok(group_expressions(expressions, position))
+}
+
+fn group_expressions(expressions: Vec<Location<Expression>>, position: usize) -> Location<Expression> {
     let expressions: Vec<Location<Expression>> = expressions
         .iter()
         .map(|e| match e {
@@ -125,11 +980,12 @@ fn handle_grouping(elements: &[Location<Element>]) -> Result<Location<Expression
             e => e.clone(),
         })
         .collect();
-    if expressions.len() == 1 {
-        Ok(expressions.first().unwrap().clone())
-    } else {
-        let position = expressions.first().unwrap().1;
-        let res = Location(Expression::Application(expressions), position);
-        Ok(res)
+    match expressions.first() {
+        None => Location(Expression::Nothing, position),
+        Some(expression) if expressions.len() == 1 => expression.clone(),
+        Some(Location(_, position)) => {
+            let position = *position;
+            Location(Expression::Application(expressions), position)
+        }
     }
 }
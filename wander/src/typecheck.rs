@@ -0,0 +1,536 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Infers the types of a script without running it, see `wander::check`.
+
+use std::{collections::HashMap, fmt::Display};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    environment::Environment,
+    interpreter::Expression,
+    parser::{Element, Pattern},
+    translation::express,
+    HostType, Location, WanderError, WanderValue,
+};
+
+/// The type of a Wander value, as far as it can be known before running a script.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum WanderType {
+    /// Any value, used when a type can't be inferred.
+    Any,
+    /// The nothing value.
+    Nothing,
+    /// A Bool.
+    Bool,
+    /// An Int.
+    Int,
+    /// A Float.
+    Float,
+    /// A String.
+    String,
+    /// A Char.
+    Char,
+    /// A Keyword.
+    Keyword,
+    /// An Identifier.
+    Identifier,
+    /// An Int or Float with a unit.
+    Quantity,
+    /// A List whose values all have the given type.
+    List(Box<WanderType>),
+    /// A Tuple of values with the given types.
+    Tuple(Vec<WanderType>),
+    /// A Set whose values all have the given type.
+    Set(Box<WanderType>),
+    /// A Record with the given fields, or any fields if None.
+    Record(Option<IndexMap<String, WanderType>>),
+    /// A function from its parameter's type to its result's type.
+    Lambda(Box<WanderType>, Box<WanderType>),
+    /// A HostValue.
+    HostValue,
+    /// An expression quoted to be evaluated later.
+    Quote,
+    /// Either nothing or a value of the given type, written as a tag like `Int?`.
+    Optional(Box<WanderType>),
+}
+
+impl WanderType {
+    /// The type named by a tag like `Int` or `Int?`, or None for tags that aren't one of Wander's own.
+    pub fn from_tag(tag: &str) -> Option<WanderType> {
+        if let Some(tag) = tag.strip_suffix('?') {
+            return WanderType::from_tag(tag).map(|tag| WanderType::Optional(Box::new(tag)));
+        }
+        let any = || Box::new(WanderType::Any);
+        match tag {
+            "Any" => Some(WanderType::Any),
+            "Nothing" => Some(WanderType::Nothing),
+            "Bool" => Some(WanderType::Bool),
+            "Int" => Some(WanderType::Int),
+            "Float" => Some(WanderType::Float),
+            "String" => Some(WanderType::String),
+            "Char" => Some(WanderType::Char),
+            "Keyword" => Some(WanderType::Keyword),
+            "Identifier" => Some(WanderType::Identifier),
+            "Quantity" => Some(WanderType::Quantity),
+            "List" => Some(WanderType::List(any())),
+            "Tuple" => Some(WanderType::Tuple(vec![])),
+            "Set" => Some(WanderType::Set(any())),
+            "Record" => Some(WanderType::Record(None)),
+            "Lambda" => Some(WanderType::Lambda(any(), any())),
+            "Quote" => Some(WanderType::Quote),
+            _ => None,
+        }
+    }
+
+    /// The type of a value.
+    pub fn of<T: HostType>(value: &WanderValue<T>) -> WanderType {
+        match value {
+            WanderValue::Nothing => WanderType::Nothing,
+            WanderValue::Bool(_) => WanderType::Bool,
+            WanderValue::Int(_) => WanderType::Int,
+            WanderValue::Float(_) => WanderType::Float,
+            WanderValue::String(_) => WanderType::String,
+            WanderValue::Char(_) => WanderType::Char,
+            WanderValue::Keyword(_) => WanderType::Keyword,
+            WanderValue::Identifier(_) => WanderType::Identifier,
+            WanderValue::Quantity(_, _) => WanderType::Quantity,
+            WanderValue::List(values) => {
+                WanderType::List(Box::new(join_all(values.iter().map(WanderType::of))))
+            }
+            WanderValue::Tuple(values) => {
+                WanderType::Tuple(values.iter().map(WanderType::of).collect())
+            }
+            WanderValue::Set(values) => {
+                WanderType::Set(Box::new(join_all(values.iter().map(WanderType::of))))
+            }
+            WanderValue::Record(fields) => WanderType::Record(Some(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), WanderType::of(value)))
+                    .collect(),
+            )),
+            WanderValue::Lambda(_, tag, _, body) => {
+                let result = lambda_type(body, WanderType::Any);
+                WanderType::Lambda(Box::new(tag_type(tag)), Box::new(result))
+            }
+            WanderValue::PartialApplication(_) => {
+                WanderType::Lambda(Box::new(WanderType::Any), Box::new(WanderType::Any))
+            }
+            WanderValue::HostValue(_) => WanderType::HostValue,
+            WanderValue::Quote(..) => WanderType::Quote,
+        }
+    }
+
+    /// Check if a value of the given type can be used where this type is expected.
+    /// Any is accepted everywhere, since it could be any value once the script runs.
+    pub fn accepts(&self, found: &WanderType) -> bool {
+        match (self, found) {
+            (WanderType::Any, _) | (_, WanderType::Any) => true,
+            (WanderType::Optional(_), WanderType::Nothing) => true,
+            (WanderType::Optional(expected), WanderType::Optional(found)) => {
+                expected.accepts(found)
+            }
+            (WanderType::Optional(expected), found) => expected.accepts(found),
+            (WanderType::List(expected), WanderType::List(found))
+            | (WanderType::Set(expected), WanderType::Set(found)) => expected.accepts(found),
+            (WanderType::Tuple(expected), WanderType::Tuple(found)) => {
+                expected.is_empty()
+                    || (expected.len() == found.len()
+                        && expected
+                            .iter()
+                            .zip(found)
+                            .all(|(expected, found)| expected.accepts(found)))
+            }
+            (WanderType::Record(None), WanderType::Record(_)) => true,
+            (WanderType::Record(Some(expected)), WanderType::Record(Some(found))) => {
+                expected.iter().all(|(name, expected)| {
+                    found.get(name).is_some_and(|found| expected.accepts(found))
+                })
+            }
+            (WanderType::Lambda(_, _), WanderType::Lambda(_, _)) => true,
+            // A Quantity is an Int or Float with a unit, Int and Float functions take them too.
+            (WanderType::Int | WanderType::Float, WanderType::Quantity) => true,
+            (expected, found) => expected == found,
+        }
+    }
+}
+
+impl Display for WanderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WanderType::Any => write!(f, "Any"),
+            WanderType::Nothing => write!(f, "Nothing"),
+            WanderType::Bool => write!(f, "Bool"),
+            WanderType::Int => write!(f, "Int"),
+            WanderType::Float => write!(f, "Float"),
+            WanderType::String => write!(f, "String"),
+            WanderType::Char => write!(f, "Char"),
+            WanderType::Keyword => write!(f, "Keyword"),
+            WanderType::Identifier => write!(f, "Identifier"),
+            WanderType::Quantity => write!(f, "Quantity"),
+            WanderType::List(value) if **value == WanderType::Any => write!(f, "List"),
+            WanderType::List(value) => write!(f, "List[{value}]"),
+            WanderType::Set(value) if **value == WanderType::Any => write!(f, "Set"),
+            WanderType::Set(value) => write!(f, "Set[{value}]"),
+            WanderType::Tuple(_) => write!(f, "Tuple"),
+            WanderType::Record(_) => write!(f, "Record"),
+            WanderType::Lambda(parameter, result) => write!(f, "({parameter} -> {result})"),
+            WanderType::HostValue => write!(f, "HostValue"),
+            WanderType::Quote => write!(f, "Quote"),
+            WanderType::Optional(value) => write!(f, "{value}?"),
+        }
+    }
+}
+
+/// A problem found while checking the types of a script, see `wander::check`.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone)]
+pub struct TypeDiagnostic {
+    /// The error, with the span of the expression that has the wrong type.
+    pub error: WanderError,
+    /// The type that was expected, or None if the script couldn't be compiled.
+    pub expected: Option<WanderType>,
+    /// The type that was found, or None if the script couldn't be compiled.
+    pub found: Option<WanderType>,
+}
+
+// Combine the types of values that end up in the same place, like the branches of a conditional.
+fn join(left: WanderType, right: WanderType) -> WanderType {
+    match (left, right) {
+        (left, right) if left == right => left,
+        (WanderType::Nothing, WanderType::Optional(value))
+        | (WanderType::Optional(value), WanderType::Nothing) => WanderType::Optional(value),
+        (WanderType::Nothing, value) | (value, WanderType::Nothing) => {
+            WanderType::Optional(Box::new(value))
+        }
+        _ => WanderType::Any,
+    }
+}
+
+fn join_all(types: impl Iterator<Item = WanderType>) -> WanderType {
+    types.reduce(join).unwrap_or(WanderType::Any)
+}
+
+fn tag_type(tag: &Option<String>) -> WanderType {
+    tag.as_deref()
+        .and_then(WanderType::from_tag)
+        .unwrap_or(WanderType::Any)
+}
+
+// The type of the rest of a curried Lambda, the parameters after the first are nested Lambda Elements.
+fn lambda_type(body: &Location<Element>, result: WanderType) -> WanderType {
+    match body {
+        Location(Element::Lambda(_, tag, _, body), _) => {
+            WanderType::Lambda(Box::new(tag_type(tag)), Box::new(lambda_type(body, result)))
+        }
+        _ => result,
+    }
+}
+
+// Infers types while walking an Expression, recording a diagnostic for every mismatch.
+pub(crate) struct Inference<'a, T: HostType> {
+    environment: &'a Environment<T>,
+    scope: HashMap<String, WanderType>,
+    diagnostics: Vec<TypeDiagnostic>,
+}
+
+impl<'a, T: HostType> Inference<'a, T> {
+    pub(crate) fn new(environment: &'a Environment<T>) -> Inference<'a, T> {
+        Inference {
+            environment,
+            scope: HashMap::new(),
+            diagnostics: vec![],
+        }
+    }
+
+    pub(crate) fn diagnostics(self) -> Vec<TypeDiagnostic> {
+        self.diagnostics
+    }
+
+    fn report(
+        &mut self,
+        message: String,
+        position: usize,
+        expected: WanderType,
+        found: WanderType,
+    ) {
+        self.diagnostics.push(TypeDiagnostic {
+            error: WanderError::at(message, position),
+            expected: Some(expected),
+            found: Some(found),
+        });
+    }
+
+    // Names bound by the script come first, then values and HostFunctions in the Environment.
+    fn name_type(&self, name: &String) -> WanderType {
+        if let Some(found) = self.scope.get(name) {
+            return found.clone();
+        }
+        match self.environment.read(name) {
+            Some(WanderValue::Lambda(_, tag, _, body)) => {
+                let result = self.host_result(&body);
+                WanderType::Lambda(
+                    Box::new(tag_type(&tag)),
+                    Box::new(lambda_type(&body, result)),
+                )
+            }
+            Some(value) => WanderType::of(&value),
+            None => match self.environment.read_host_function(name) {
+                Some(function) => tag_type(&function.binding().result),
+                None => WanderType::Any,
+            },
+        }
+    }
+
+    // The result tag of the HostFunction at the end of a chain of Lambdas.
+    fn host_result(&self, body: &Location<Element>) -> WanderType {
+        match body {
+            Location(Element::Lambda(_, _, _, body), _) => self.host_result(body),
+            Location(Element::HostFunction(name), _) => {
+                match self.environment.read_host_function(name) {
+                    Some(function) => tag_type(&function.binding().result),
+                    None => WanderType::Any,
+                }
+            }
+            _ => WanderType::Any,
+        }
+    }
+
+    pub(crate) fn infer(&mut self, expression: &Location<Expression>) -> WanderType {
+        let Location(expression, position) = expression;
+        match expression {
+            Expression::Boolean(_) => WanderType::Bool,
+            Expression::Int(_) => WanderType::Int,
+            Expression::Float(_) => WanderType::Float,
+            Expression::String(_) => WanderType::String,
+            Expression::Char(_) => WanderType::Char,
+            Expression::Identifier(_) => WanderType::Identifier,
+            Expression::Keyword(_) => WanderType::Keyword,
+            Expression::Nothing => WanderType::Nothing,
+            Expression::Quantity(value, _) => {
+                self.infer(value);
+                WanderType::Quantity
+            }
+            Expression::Name(name) | Expression::TaggedName(name, _) => self.name_type(name),
+            Expression::HostFunction(name) => match self.environment.read_host_function(name) {
+                Some(function) => tag_type(&function.binding().result),
+                None => WanderType::Any,
+            },
+            Expression::Let(decls, body) => {
+                for (name, tag, value) in decls {
+                    let tag = match tag {
+                        Some(Location(Expression::Name(tag), _)) => WanderType::from_tag(tag),
+                        _ => None,
+                    };
+                    // Bind the name first so recursive Lambdas can refer to themselves.
+                    self.scope
+                        .insert(name.clone(), tag.clone().unwrap_or(WanderType::Any));
+                    let found = self.infer(value);
+                    match tag {
+                        Some(expected) => {
+                            if !expected.accepts(&found) {
+                                self.report(
+                                    format!(
+                                        "`{name}` is tagged {expected} but its value is {found}"
+                                    ),
+                                    value.1,
+                                    expected,
+                                    found,
+                                );
+                            }
+                        }
+                        None => {
+                            self.scope.insert(name.clone(), found);
+                        }
+                    }
+                }
+                self.infer(body)
+            }
+            Expression::Lambda(name, tag, _, body) => {
+                let parameter = tag_type(tag);
+                let outer = self.scope.clone();
+                self.scope.insert(name.clone(), parameter.clone());
+                let result = match express(body) {
+                    Ok(body) => self.infer(&body),
+                    Err(_) => WanderType::Any,
+                };
+                self.scope = outer;
+                WanderType::Lambda(Box::new(parameter), Box::new(result))
+            }
+            Expression::Application(expressions) => match expressions.split_first() {
+                Some((function, arguments)) => {
+                    let function = self.infer(function);
+                    self.apply(function, arguments)
+                }
+                None => WanderType::Nothing,
+            },
+            Expression::TailCall(name, arguments) => {
+                let function = self.name_type(name);
+                self.apply(function, arguments)
+            }
+            Expression::Conditional(condition, then, otherwise) => {
+                let found = self.infer(condition);
+                if !WanderType::Bool.accepts(&found) {
+                    self.report(
+                        format!("Conditions must be Bool, found {found}"),
+                        condition.1,
+                        WanderType::Bool,
+                        found,
+                    );
+                }
+                let then = self.infer(then);
+                let otherwise = self.infer(otherwise);
+                join(then, otherwise)
+            }
+            Expression::List(values) => {
+                let types: Vec<WanderType> = values.iter().map(|value| self.infer(value)).collect();
+                WanderType::List(Box::new(join_all(types.into_iter())))
+            }
+            Expression::Set(values) => {
+                let types: Vec<WanderType> = values.iter().map(|value| self.infer(value)).collect();
+                WanderType::Set(Box::new(join_all(types.into_iter())))
+            }
+            Expression::Tuple(values) => {
+                WanderType::Tuple(values.iter().map(|value| self.infer(value)).collect())
+            }
+            Expression::Record(fields) => WanderType::Record(Some(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), self.infer(value)))
+                    .collect(),
+            )),
+            Expression::RecordUpdate(base, updates) => {
+                let found = self.infer(base);
+                let updates: IndexMap<String, WanderType> = updates
+                    .iter()
+                    .map(|(name, value)| (name.clone(), self.infer(value)))
+                    .collect();
+                match found {
+                    WanderType::Record(Some(mut fields)) => {
+                        fields.extend(updates);
+                        WanderType::Record(Some(fields))
+                    }
+                    WanderType::Record(None) | WanderType::Any => WanderType::Record(None),
+                    found => {
+                        self.report(
+                            format!("Only Records can be updated, found {found}"),
+                            base.1,
+                            WanderType::Record(None),
+                            found,
+                        );
+                        WanderType::Record(None)
+                    }
+                }
+            }
+            Expression::FieldAccess(base, field) => match self.infer(base) {
+                WanderType::Record(Some(fields)) => match fields.get(field) {
+                    Some(found) => found.clone(),
+                    None => {
+                        let found = WanderType::Record(Some(fields));
+                        self.report(
+                            format!("Record has no field `{field}`"),
+                            *position,
+                            WanderType::Any,
+                            found,
+                        );
+                        WanderType::Any
+                    }
+                },
+                _ => WanderType::Any,
+            },
+            Expression::Match(value, arms) => {
+                self.infer(value);
+                let mut types = vec![];
+                for (pattern, body) in arms {
+                    bind_pattern(pattern, &mut self.scope);
+                    types.push(self.infer(body));
+                }
+                join_all(types.into_iter())
+            }
+            Expression::Sequence(expressions) => {
+                let mut result = WanderType::Nothing;
+                for expression in expressions {
+                    result = self.infer(expression);
+                }
+                result
+            }
+            Expression::Try(body, name, handler) => {
+                let body = self.infer(body);
+                self.scope.insert(name.clone(), WanderType::Record(None));
+                let handler = self.infer(handler);
+                join(body, handler)
+            }
+            Expression::Pure(body) => self.infer(body),
+            Expression::TryResult(body) => {
+                self.infer(body);
+                WanderType::Record(None)
+            }
+            Expression::Ensure(body, cleanup) => {
+                let body = self.infer(body);
+                self.infer(cleanup);
+                body
+            }
+            Expression::CustomLiteral(_) => WanderType::Any,
+            Expression::Quote(_, unquoted) => {
+                unquoted.iter().for_each(|value| {
+                    self.infer(value);
+                });
+                WanderType::Quote
+            }
+        }
+    }
+
+    // Apply a function type to each argument in turn, checking them against the parameter types.
+    fn apply(
+        &mut self,
+        mut function: WanderType,
+        arguments: &[Location<Expression>],
+    ) -> WanderType {
+        for argument in arguments {
+            let found = self.infer(argument);
+            function = match function {
+                WanderType::Lambda(expected, result) => {
+                    if !expected.accepts(&found) {
+                        self.report(
+                            format!("Expected an argument of type {expected}, found {found}"),
+                            argument.1,
+                            *expected,
+                            found,
+                        );
+                    }
+                    *result
+                }
+                WanderType::Any => WanderType::Any,
+                found => {
+                    self.report(
+                        format!("{found} can't be applied to arguments"),
+                        argument.1,
+                        WanderType::Lambda(Box::new(WanderType::Any), Box::new(WanderType::Any)),
+                        found,
+                    );
+                    WanderType::Any
+                }
+            };
+        }
+        function
+    }
+}
+
+// Names captured by a Pattern could be any value.
+fn bind_pattern(pattern: &Pattern, scope: &mut HashMap<String, WanderType>) {
+    match pattern {
+        Pattern::Name(name) => {
+            scope.insert(name.clone(), WanderType::Any);
+        }
+        Pattern::Tuple(patterns) | Pattern::List(patterns) => patterns
+            .iter()
+            .for_each(|pattern| bind_pattern(pattern, scope)),
+        Pattern::Record(patterns) => patterns
+            .values()
+            .for_each(|pattern| bind_pattern(pattern, scope)),
+        _ => (),
+    }
+}
@@ -2,7 +2,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use wander::{environment::Environment, NoHostType, WanderValue};
+use wander::{environment::Environment, preludes::common, run, NoHostType, WanderError, WanderValue};
 
 #[test]
 fn new_bindings_should_be_empty() {
@@ -22,3 +22,85 @@ fn bind_single_value() {
     assert_eq!(read_result, Some(WanderValue::Int(3)));
     assert_eq!(none_result, None);
 }
+
+#[test]
+fn top_level_val_stays_bound_between_runs() {
+    let mut bindings = common::<NoHostType>();
+    assert_eq!(run("val x = 5", &mut bindings), Ok(WanderValue::Int(5)));
+    assert_eq!(run("val y = Int.add x 1; y", &mut bindings), Ok(WanderValue::Int(6)));
+    assert_eq!(
+        run("[x y]", &mut bindings),
+        Ok(WanderValue::List(vec![WanderValue::Int(5), WanderValue::Int(6)]))
+    );
+}
+
+#[test]
+fn use_namespace_alias() {
+    let input = "use Bool as B; [(B.and true false) (B.not false)]";
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(
+        res,
+        Ok(WanderValue::List(vec![WanderValue::Bool(false), WanderValue::Bool(true)]))
+    );
+}
+
+#[test]
+fn use_unknown_namespace() {
+    let input = "use Nope as N\nN.thing";
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(
+        res,
+        Err(WanderError::at(
+            "Cannot use Nope, no module named Nope is bound at line 1, column 1.".to_owned(),
+            0
+        ))
+    );
+}
+
+#[test]
+fn use_namespace_wildcard() {
+    let input = "use List.*; length (reverse [1 2 3])";
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Int(3)));
+}
+
+#[test]
+fn use_wildcard_in_let_block() {
+    let input = "let use Bool.* x = not false in and x true end";
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Bool(true)));
+}
+
+#[test]
+fn local_values_shadow_wildcard_imports() {
+    let mut bindings = common::<NoHostType>();
+    let input = "use List.*; let length = 5 in length end";
+    let res = run(input, &mut bindings);
+    assert_eq!(res, Ok(WanderValue::Int(5)));
+    assert_eq!(
+        bindings.take_warnings(),
+        vec![WanderError::at("length shadows List.length".to_owned(), 25)]
+    );
+}
+
+#[test]
+fn remove_and_inspect_scopes() {
+    let mut bindings = common::<NoHostType>();
+    bindings.bind("x".to_owned(), WanderValue::Int(1));
+    assert_eq!(bindings.scope_depth(), 1);
+    bindings.add_scope();
+    bindings.bind("x".to_owned(), WanderValue::Int(2));
+    bindings.bind("y".to_owned(), WanderValue::Int(3));
+    assert_eq!(bindings.scope_depth(), 2);
+    let mut names: Vec<String> = bindings.names_in_current_scope().into_iter().collect();
+    names.sort();
+    assert_eq!(names, vec!["x".to_owned(), "y".to_owned()]);
+    assert_eq!(bindings.remove("x"), Some(WanderValue::Int(2)));
+    assert_eq!(bindings.read(&"x".to_owned()), Some(WanderValue::Int(1)));
+    assert_eq!(bindings.remove("missing"), None);
+    assert!(bindings.contains("y"));
+    assert!(bindings.contains("Bool.not"));
+    assert!(!bindings.contains("missing"));
+    bindings.remove_scope();
+    assert!(!bindings.contains("y"));
+}
@@ -0,0 +1,146 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "parallel")]
+
+use std::{fmt::Display, sync::Arc, thread, time::Duration};
+
+use serde::Serialize;
+use wander::{
+    channel::{add_channel_functions, Channel, ChannelHost},
+    environment::Environment,
+    preludes::common,
+    run, run_with_config,
+    task::add_task_functions,
+    CancelFlag, RunConfig, WanderError, WanderValue,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+enum TestHost {
+    Channel(Channel<TestHost>),
+}
+
+impl Display for TestHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TestHost::Channel(channel) => Display::fmt(channel, f),
+        }
+    }
+}
+
+impl ChannelHost for TestHost {
+    fn from_channel(channel: Channel<Self>) -> Self {
+        TestHost::Channel(channel)
+    }
+
+    fn as_channel(&self) -> Option<&Channel<Self>> {
+        match self {
+            TestHost::Channel(channel) => Some(channel),
+        }
+    }
+}
+
+fn environment() -> Environment<TestHost> {
+    let mut bindings = common::<TestHost>();
+    add_channel_functions(&mut bindings);
+    bindings
+}
+
+fn bindings() -> Environment<TestHost> {
+    let mut bindings = environment();
+    add_task_functions(&mut bindings, Arc::new(environment));
+    bindings
+}
+
+#[test]
+fn values_are_received_in_order() {
+    let input = r#"
+        val channel = Channel.new;
+        Channel.send channel 1;
+        Channel.send channel 2;
+        Channel.close channel;
+        [(Channel.receive channel) (Channel.receive channel) (Channel.receive channel)]
+    "#;
+    let res = run(input, &mut bindings());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(1),
+        WanderValue::Int(2),
+        WanderValue::Nothing,
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn tasks_communicate_through_channels() {
+    let input = r#"
+        val numbers = Channel.new;
+        val doubled = Channel.new;
+        val produce = \_ -> do
+            Channel.send numbers 1;
+            Channel.send numbers 2;
+            Channel.close numbers
+        end;
+        val double = \_ -> do
+            Channel.send doubled (Int.mul (Channel.receive numbers) 2);
+            Channel.send doubled (Int.mul (Channel.receive numbers) 2);
+            Channel.close doubled
+        end;
+        val consume = \_ -> [(Channel.receive doubled) (Channel.receive doubled) (Channel.receive doubled)];
+        Task.par [consume double produce]
+    "#;
+    let res = run(input, &mut bindings());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::List(vec![
+            WanderValue::Int(2),
+            WanderValue::Int(4),
+            WanderValue::Nothing,
+        ]),
+        WanderValue::Nothing,
+        WanderValue::Nothing,
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn closed_channels_reject_values() {
+    let input = "val channel = Channel.new; Channel.close channel; Channel.send channel 1";
+    let res = run(input, &mut bindings());
+    let expected = Err(WanderError::new(
+        "Cannot send to a closed Channel.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+    let res = run("Channel.receive 1", &mut bindings());
+    let expected = Err(WanderError::new(
+        "`Channel.receive` function requires a Channel.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn waiting_receives_stop_when_cancelled() {
+    let cancel = CancelFlag::new();
+    let config = RunConfig {
+        cancel: Some(cancel.clone()),
+        ..RunConfig::default()
+    };
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        cancel.cancel();
+    });
+    let input = "val channel = Channel.new; Channel.receive channel";
+    let res = run_with_config(input, &mut bindings(), &config);
+    canceller.join().unwrap();
+    assert!(res.unwrap_err().message.starts_with("Script was cancelled"));
+}
+
+#[test]
+fn waiting_receives_stop_at_the_time_limit() {
+    let config = RunConfig {
+        max_time: Some(Duration::from_millis(50)),
+        ..RunConfig::default()
+    };
+    let input = "val channel = Channel.new; Channel.receive channel";
+    let res = run_with_config(input, &mut bindings(), &config);
+    assert!(res.unwrap_err().message.starts_with("Script is past its time limit"));
+}
@@ -0,0 +1,63 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{
+    compile, execute, preludes::common, CompiledScript, NoHostType, Span, WanderError, WanderValue,
+};
+
+#[test]
+fn execute_compiled_script_many_times() {
+    let mut environment = common::<NoHostType>();
+    let compiled = compile("Int.add 1 2", &environment).unwrap();
+    for _ in 0..3 {
+        assert_eq!(
+            execute(&compiled, &mut environment),
+            Ok(WanderValue::Int(3))
+        );
+    }
+}
+
+#[test]
+fn compiled_script_uses_current_bindings() {
+    let mut environment = common::<NoHostType>();
+    let compiled = compile("Int.add x 1", &environment).unwrap();
+    environment.bind("x".to_owned(), WanderValue::Int(1));
+    assert_eq!(
+        execute(&compiled, &mut environment),
+        Ok(WanderValue::Int(2))
+    );
+    environment.bind("x".to_owned(), WanderValue::Int(5));
+    assert_eq!(
+        execute(&compiled, &mut environment),
+        Ok(WanderValue::Int(6))
+    );
+}
+
+#[test]
+fn serialized_compiled_script() {
+    let mut environment = common::<NoHostType>();
+    let compiled = compile(r#"let f = \x -> [x x] in f 1 end"#, &environment).unwrap();
+    let json = serde_json::to_string(&compiled).unwrap();
+    let compiled: CompiledScript = serde_json::from_str(&json).unwrap();
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(1),
+        WanderValue::Int(1),
+    ]));
+    assert_eq!(execute(&compiled, &mut environment), expected);
+}
+
+#[test]
+fn execute_errors_are_located() {
+    let mut environment = common::<NoHostType>();
+    let script = "true;\nlet id = \\x -> x in id 1 2 end";
+    let compiled = compile(script, &environment).unwrap();
+    assert_eq!(compiled.script(), script);
+    let res = execute(&compiled, &mut environment);
+    let expected = Err(WanderError {
+        message: "Function of 1 argument applied to 2 at line 2, column 21.".to_owned(),
+        span: Some(Span { start: 26, end: 26 }),
+        code: None,
+    });
+    assert_eq!(res, expected);
+}
@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{preludes::common, run, NoHostType, WanderValue};
+
+#[test]
+fn nested_conditional() {
+    let input = "if false then 1 else (if true then 2 else 3 end) end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(2));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn else_if_chain() {
+    let input = "if false then 1 else if false then 2 else if true then 3 else 4 end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(3));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn else_if_chain_falls_through() {
+    let input = "if false then 1 else if false then 2 else 3 end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(3));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn long_else_if_chain() {
+    let mut input = String::new();
+    for i in 0..30 {
+        input += &format!("if false then {i} else ");
+    }
+    input += "30 end";
+    let res = run(&input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(30));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn else_if_chain_in_let() {
+    let input = "let x = 1 in if false then 1 else if true then 2 else 3 end end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(2));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn else_if_chain_in_do() {
+    let input = "do val x = 1; if false then 1 else if true then x else 3 end end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(1));
+    assert_eq!(res, expected);
+}
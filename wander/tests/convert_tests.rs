@@ -0,0 +1,63 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use wander::{preludes::common, run, FromWanderValue, IntoWanderValue, NoHostType, WanderValue};
+
+fn value(script: &str) -> WanderValue<NoHostType> {
+    run(script, &mut common::<NoHostType>()).unwrap()
+}
+
+#[test]
+fn convert_from_wander_values() {
+    assert_eq!(i64::from_wander_value(&value("5")), Some(5));
+    assert_eq!(i32::from_wander_value(&value("5")), Some(5));
+    assert_eq!(u32::from_wander_value(&value("-5")), None);
+    assert_eq!(bool::from_wander_value(&value("5")), None);
+    assert_eq!(
+        Vec::<String>::from_wander_value(&value(r#"["a" "b"]"#)),
+        Some(vec!["a".to_owned(), "b".to_owned()])
+    );
+    assert_eq!(Vec::<String>::from_wander_value(&value(r#"["a" 1]"#)), None);
+    assert_eq!(
+        Option::<f64>::from_wander_value(&value("nothing")),
+        Some(None)
+    );
+    assert_eq!(
+        Option::<f64>::from_wander_value(&value("1.5")),
+        Some(Some(1.5))
+    );
+    assert_eq!(
+        <(i64, bool)>::from_wander_value(&value("'(1 true)")),
+        Some((1, true))
+    );
+    assert_eq!(
+        <(i64, bool)>::from_wander_value(&value("'(1 true 2)")),
+        None
+    );
+    assert_eq!(
+        HashMap::<String, i64>::from_wander_value(&value("{a = 1 b = 2}")),
+        Some(HashMap::from([("a".to_owned(), 1), ("b".to_owned(), 2)]))
+    );
+}
+
+#[test]
+fn convert_into_wander_values() {
+    let converted: WanderValue<NoHostType> = (
+        vec![1i64, 2],
+        Some("a"),
+        None::<bool>,
+        HashMap::from([("b".to_owned(), true), ("a".to_owned(), false)]),
+    )
+        .into_wander_value();
+    assert_eq!(
+        converted,
+        value(r#"'([1 2] "a" nothing {a = false b = true})"#)
+    );
+    let record: WanderValue<NoHostType> =
+        IndexMap::from([("z".to_owned(), 1i32), ("y".to_owned(), 2)]).into_wander_value();
+    assert_eq!(format!("{record}"), "{z = 1 y = 2}");
+}
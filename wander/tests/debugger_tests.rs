@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wander::{
+    debugger::{DebugCommand, Debugger},
+    preludes::common,
+    run, NoHostType, Span, WanderValue,
+};
+
+// Run a script with a Debugger, returning its result and the position of each pause.
+fn debug(
+    script: &str,
+    breakpoints: &[usize],
+    step: bool,
+    command: DebugCommand,
+) -> (Result<WanderValue<NoHostType>, String>, Vec<usize>) {
+    let pauses = Rc::new(RefCell::new(vec![]));
+    let recorded = pauses.clone();
+    let mut debugger = Debugger::new(Box::new(move |pause| {
+        recorded.borrow_mut().push(pause.position);
+        command
+    }));
+    for start in breakpoints {
+        debugger.add_breakpoint(Span {
+            start: *start,
+            end: *start,
+        });
+    }
+    if step {
+        debugger.step();
+    }
+    let mut bindings = common::<NoHostType>();
+    bindings.set_eval_observer(Box::new(debugger));
+    let result = run(script, &mut bindings).map_err(|err| err.message);
+    let pauses = pauses.borrow().clone();
+    (result, pauses)
+}
+
+#[test]
+fn step_through_a_script() {
+    let (result, pauses) = debug("val x = 1;\nInt.add x 2", &[], true, DebugCommand::Step);
+    assert_eq!(result, Ok(WanderValue::Int(3)));
+    assert!(pauses.contains(&11) && pauses.contains(&19) && pauses.contains(&21));
+    let (_, over) = debug("val x = 1;\nInt.add x 2", &[], true, DebugCommand::StepOver);
+    assert!(over.len() < pauses.len());
+}
+
+#[test]
+fn pause_at_breakpoints() {
+    let script = "val x = 1;\nval y = Int.add x 2;\ny";
+    let (result, pauses) = debug(script, &[27], false, DebugCommand::Continue);
+    assert_eq!(result, Ok(WanderValue::Int(3)));
+    assert_eq!(pauses, vec![27]);
+}
+
+#[test]
+fn breakpoints_pause_each_time_they_are_reached() {
+    let script = r#"let f = \n -> if Core.eq n 0 then 0 else f (Int.sub n 1) end in f 3 end"#;
+    let position = script.find("Core.eq").unwrap();
+    let (result, pauses) = debug(script, &[position], false, DebugCommand::Continue);
+    assert_eq!(result, Ok(WanderValue::Int(0)));
+    assert_eq!(pauses, vec![position; 4]);
+}
+
+#[test]
+fn stop_a_script() {
+    let (result, pauses) = debug("Int.add 1 2", &[], true, DebugCommand::Stop);
+    assert!(result
+        .unwrap_err()
+        .starts_with("Script was stopped by the debugger at line 1"));
+    assert_eq!(pauses, vec![0]);
+}
+
+#[test]
+fn inspect_values_while_paused() {
+    let values = Rc::new(RefCell::new(vec![]));
+    let recorded = values.clone();
+    let mut debugger = Debugger::new(Box::new(move |pause| {
+        recorded
+            .borrow_mut()
+            .push(pause.environment.read(&"x".to_owned()));
+        DebugCommand::Continue
+    }));
+    debugger.add_breakpoint(Span { start: 11, end: 22 });
+    let mut bindings = common::<NoHostType>();
+    bindings.set_eval_observer(Box::new(debugger));
+    run("val x = 1;\nInt.add x 2", &mut bindings).unwrap();
+    assert_eq!(*values.borrow(), vec![Some(WanderValue::Int(1)); 3]);
+    assert!(bindings.take_eval_observer().is_some());
+}
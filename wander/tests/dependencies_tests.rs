@@ -0,0 +1,65 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{dependencies::BindingDependencies, introspect, preludes::common, NoHostType};
+
+fn dependencies(script: &str) -> Vec<BindingDependencies> {
+    introspect(script, &common::<NoHostType>())
+        .unwrap()
+        .dependencies
+}
+
+fn binding(name: &str, host_functions: &[&str], names: &[&str]) -> BindingDependencies {
+    BindingDependencies {
+        name: name.to_owned(),
+        host_functions: host_functions.iter().map(|name| name.to_string()).collect(),
+        names: names.iter().map(|name| name.to_string()).collect(),
+    }
+}
+
+#[test]
+fn val_dependencies() {
+    let script = r#"
+        val limit = 10;
+        val clamp = \x -> if Int.gt x limit then limit else x end;
+        val total = \list -> match list with
+            | [] -> 0
+            | [head] -> Int.add (clamp head) (total [])
+        end;
+        val checked = let double = \x -> Int.mul x 2 in double offset end;
+        clamp 20
+    "#;
+    let expected = vec![
+        binding("limit", &[], &[]),
+        binding("clamp", &["Int.gt"], &["limit"]),
+        binding("total", &["Int.add"], &["clamp"]),
+        binding("checked", &["Int.mul"], &["offset"]),
+    ];
+    assert_eq!(dependencies(script), expected);
+}
+
+#[test]
+fn let_dependencies() {
+    let script = r#"
+        val name = "Wander";
+        let greeting = String.concat "Hello, " name in greeting end
+    "#;
+    let expected = vec![
+        binding("name", &[], &[]),
+        binding("greeting", &["String.concat"], &["name"]),
+    ];
+    assert_eq!(dependencies(script), expected);
+}
+
+#[test]
+fn quote_dependencies() {
+    let script = "val q = `(Int.add ~y (let z = 1 in Int.mul z x end))";
+    let expected = vec![binding("q", &["Int.add", "Int.mul"], &["y", "x"])];
+    assert_eq!(dependencies(script), expected);
+}
+
+#[test]
+fn scripts_without_bindings() {
+    assert_eq!(dependencies("Int.add 1 2"), vec![]);
+}
@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{preludes::common, run, NoHostType, WanderValue};
+
+#[test]
+fn do_block_returns_last_value() {
+    let input = "do true; 5 end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(5));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn do_block_with_binds() {
+    let input = "do val x = true; val y = Bool.not x; Bool.and x y end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(false));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn do_block_ending_in_bind_returns_bound_value() {
+    let input = "do 1; val x = 2; end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(2));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn empty_do_block() {
+    let input = "do end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Nothing);
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn do_block_stops_at_first_error() {
+    let input = "do Bool.not 5; true end";
+    let res = run(input, &mut common::<NoHostType>());
+    assert!(res.is_err());
+}
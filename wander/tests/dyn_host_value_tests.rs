@@ -0,0 +1,104 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::{fmt::Display, rc::Rc};
+
+use serde::Serialize;
+use wander::{
+    dyn_host_value::DynHostValue, environment::Environment, preludes::common, run, HostFunction,
+    HostFunctionBinding, HostValue, WanderError, WanderValue,
+};
+
+// Two host modules that each add their own value type.
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Point({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Color(String);
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Color({})", self.0)
+    }
+}
+
+struct Constant {
+    name: &'static str,
+    value: fn() -> DynHostValue,
+}
+
+impl HostFunction<DynHostValue> for Constant {
+    fn run(
+        &self,
+        _arguments: &[WanderValue<DynHostValue>],
+        _bindings: &mut Environment<DynHostValue>,
+    ) -> Result<WanderValue<DynHostValue>, WanderError> {
+        Ok(WanderValue::HostValue(HostValue {
+            value: (self.value)(),
+        }))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.to_owned(),
+            parameters: vec![],
+            result: None,
+            pure: true,
+            doc_string: String::new(),
+            ..Default::default()
+        }
+    }
+}
+
+fn bindings() -> Environment<DynHostValue> {
+    let mut bindings = common::<DynHostValue>();
+    bindings.bind_host_function(Rc::new(Constant {
+        name: "Geometry.origin",
+        value: || DynHostValue::new(Point { x: 0, y: 0 }),
+    }));
+    bindings.bind_host_function(Rc::new(Constant {
+        name: "Paint.red",
+        value: || DynHostValue::new(Color("red".to_owned())),
+    }));
+    bindings
+}
+
+#[test]
+fn host_values_of_different_types() {
+    let res = run("[Geometry.origin Paint.red]", &mut bindings()).unwrap();
+    assert_eq!(format!("{res}"), "[Point(0, 0) Color(red)]");
+    assert_eq!(
+        serde_json::to_string(&res).unwrap(),
+        r#"{"List":[{"HostValue":{"value":{"x":0,"y":0}}},{"HostValue":{"value":"red"}}]}"#
+    );
+    if let WanderValue::List(values) = res {
+        match &values[0] {
+            WanderValue::HostValue(HostValue { value }) => {
+                assert_eq!(value.downcast_ref::<Point>(), Some(&Point { x: 0, y: 0 }));
+                assert_eq!(value.downcast_ref::<Color>(), None);
+            }
+            value => panic!("Expected a HostValue, found {value}"),
+        }
+    }
+}
+
+#[test]
+fn compare_host_values() {
+    let input = "[(Core.eq Geometry.origin Geometry.origin) (Core.eq Geometry.origin Paint.red)]";
+    let res = run(input, &mut bindings());
+    assert_eq!(
+        res,
+        Ok(WanderValue::List(vec![WanderValue::Bool(true), WanderValue::Bool(false)]))
+    );
+}
@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{
+    introspect, line_column, parser::Element, preludes::common, run, Location, NoHostType,
+    ParseDiagnostic, Span, WanderError,
+};
+
+#[test]
+fn line_column_of_offsets() {
+    let script = "true\nfalse\n  5";
+    assert_eq!(line_column(script, 0), (1, 1));
+    assert_eq!(line_column(script, 5), (2, 1));
+    assert_eq!(line_column(script, 13), (3, 3));
+}
+
+#[test]
+fn parse_error_reports_line_and_column() {
+    let input = "let x = 5 in\n  x\n  end end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError {
+        message: "Unexpected token End at line 3, column 7.".to_owned(),
+        span: Some(Span { start: 23, end: 23 }),
+        code: None,
+    });
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn tokenize_error_reports_line_and_column() {
+    let input = "true\n  %";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError {
+        message: "Unexpected input \"%\" at line 2, column 3.".to_owned(),
+        span: Some(Span { start: 7, end: 8 }),
+        code: None,
+    });
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn errors_without_a_span_are_unchanged() {
+    let error = WanderError::new("Some error.".to_owned()).locate("true");
+    assert_eq!(error.to_string(), "Some error.");
+}
+
+#[test]
+fn introspect_reports_every_parse_error() {
+    let input = "let x = [1 2 in x end;\nInt.add 1 2;\n(Bool.not true";
+    let introspection = introspect(input, &common::<NoHostType>()).unwrap();
+    let expected = vec![
+        ParseDiagnostic {
+            error: WanderError {
+                message: "Unexpected token EqualSign at line 1, column 7.".to_owned(),
+                span: Some(Span { start: 6, end: 6 }),
+                code: None,
+            },
+            expected: Some("CloseSquare".to_owned()),
+            found: Some("EqualSign".to_owned()),
+        },
+        ParseDiagnostic {
+            error: WanderError {
+                message: "Unexpected token OpenParen at line 3, column 1.".to_owned(),
+                span: Some(Span { start: 36, end: 36 }),
+                code: None,
+            },
+            expected: Some("CloseParen".to_owned()),
+            found: Some("OpenParen".to_owned()),
+        },
+    ];
+    assert_eq!(introspection.diagnostics, expected);
+    assert_eq!(
+        introspection.element,
+        Location(
+            Element::Sequence(vec![Location(
+                Element::Grouping(vec![
+                    Location(Element::Name("Int.add".to_owned()), 23),
+                    Location(Element::Int(1), 31),
+                    Location(Element::Int(2), 33),
+                ]),
+                23
+            )]),
+            23
+        )
+    );
+}
+
+#[test]
+fn stray_semicolons_only_skip_themselves() {
+    let input = "1;;2;3";
+    let introspection = introspect(input, &common::<NoHostType>()).unwrap();
+    assert_eq!(introspection.diagnostics.len(), 1);
+    assert_eq!(
+        introspection.element,
+        Location(
+            Element::Sequence(vec![
+                Location(Element::Int(1), 0),
+                Location(Element::Int(2), 3),
+                Location(Element::Int(3), 5),
+            ]),
+            0
+        )
+    );
+}
+
+#[test]
+fn unclosed_ensure_expects_end() {
+    let input = "ensure true finally false;\n1";
+    let introspection = introspect(input, &common::<NoHostType>()).unwrap();
+    assert_eq!(introspection.diagnostics.len(), 1);
+    assert_eq!(introspection.diagnostics[0].expected, Some("End".to_owned()));
+    assert_eq!(introspection.element, Location(Element::Int(1), 27));
+}
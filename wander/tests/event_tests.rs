@@ -0,0 +1,65 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{
+    environment::Environment, event::add_event_functions, preludes::common, run, NoHostType,
+    WanderValue,
+};
+
+fn bindings() -> Environment<NoHostType> {
+    let mut bindings = common::<NoHostType>();
+    add_event_functions(&mut bindings);
+    bindings
+}
+
+#[test]
+fn hosts_emit_events_to_stored_lambdas() {
+    let mut bindings = bindings();
+    let handler = run("\\x -> Int.add x 1", &mut bindings).unwrap();
+    bindings.on_event("tick", handler);
+    let handler = run("Int.mul 2", &mut bindings).unwrap();
+    bindings.on_event("tick", handler);
+    assert_eq!(
+        bindings.emit("tick", &[WanderValue::Int(5)]),
+        Ok(vec![WanderValue::Int(6), WanderValue::Int(10)])
+    );
+    assert_eq!(bindings.emit("click", &[]), Ok(vec![]));
+    assert_eq!(bindings.remove_event_handlers("tick").len(), 2);
+    assert_eq!(bindings.emit("tick", &[WanderValue::Int(5)]), Ok(vec![]));
+}
+
+#[test]
+fn scripts_add_handlers() {
+    let mut bindings = bindings();
+    run(
+        "val step = 10; Event.on :tick (\\x -> Int.add x step)",
+        &mut bindings,
+    )
+    .unwrap();
+    assert_eq!(
+        bindings.emit("tick", &[WanderValue::Int(1)]),
+        Ok(vec![WanderValue::Int(11)])
+    );
+    assert_eq!(
+        run("Event.emit \"tick\" [2]", &mut bindings),
+        Ok(WanderValue::List(vec![WanderValue::Int(12)]))
+    );
+    assert_eq!(
+        run("Event.on :tick 5", &mut bindings).unwrap_err().message,
+        "`Event.on` function requires a String or Keyword and a Lambda."
+    );
+}
+
+#[test]
+fn every_handler_runs_when_some_fail() {
+    let mut bindings = bindings();
+    run(
+        "Event.on :save (\\x -> Core.error \"failed\"); Event.on :save (\\x -> x)",
+        &mut bindings,
+    )
+    .unwrap();
+    let err = bindings.emit("save", &[]).unwrap_err();
+    assert!(err.message.contains("failed"));
+    assert_eq!(bindings.remove_event_handlers("save").len(), 2);
+}
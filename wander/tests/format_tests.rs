@@ -0,0 +1,151 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{
+    fmt, fmt_with_options, format_value, preludes::common, run, try_fmt, FormatOptions,
+    NoHostType,
+};
+
+const NARROW: FormatOptions = FormatOptions {
+    indent: 2,
+    width: 20,
+};
+
+#[test]
+fn short_values_stay_on_one_line() {
+    let value = run("[1 {a = 2} '(3 4)]", &mut common::<NoHostType>()).unwrap();
+    assert_eq!(
+        format_value(&value, FormatOptions::default()),
+        "[1 {a = 2} '(3 4)]"
+    );
+}
+
+#[test]
+fn long_values_are_broken_over_lines() {
+    let value = run(
+        "[1 {a = [10 20 30 40 50] b = '(1 2)} #(3 1 2)]",
+        &mut common::<NoHostType>(),
+    )
+    .unwrap();
+    let expected = "\
+[
+  1
+  {
+    a = [
+      10
+      20
+      30
+      40
+      50
+    ]
+    b = '(1 2)
+  }
+  #(1 2 3)
+]";
+    assert_eq!(format_value(&value, NARROW), expected);
+}
+
+#[test]
+fn spacing_is_normalized() {
+    assert_eq!(
+        fmt("val   x =  [ 1 2 3 ] ;val y = {a = 1}"),
+        "val x = [1 2 3];\nval y = {a = 1}\n"
+    );
+}
+
+#[test]
+fn comments_and_blank_lines_are_kept() {
+    let script = "-- top\nval x = 5;\n\n\nval y = [x 1];  -- trailing\ny";
+    assert_eq!(
+        fmt(script),
+        "-- top\nval x = 5;\n\nval y = [x 1]; -- trailing\ny\n"
+    );
+}
+
+#[test]
+fn blocks_are_indented_when_they_dont_fit() {
+    let script = "val y = let a = 1 in Int.add a 2 end; if Bool.and true false then \"yes\" else if false then 2 else 3 end";
+    let expected = "\
+val y = let
+  a = 1
+in
+  Int.add a 2
+end;
+if Bool.and true false then
+  \"yes\"
+else if false then
+  2
+else
+  3
+end
+";
+    assert_eq!(fmt_with_options(script, NARROW), expected);
+}
+
+#[test]
+fn match_arms_are_put_on_their_own_lines() {
+    let script = "match 2 with | 1 -> \"one\" | 2 -> \"two\" | _ -> nothing end";
+    let expected = "\
+match 2 with
+  | 1 -> \"one\"
+  | 2 -> \"two\"
+  | _ -> nothing
+end
+";
+    assert_eq!(fmt_with_options(script, NARROW), expected);
+}
+
+#[test]
+fn formatting_keeps_the_meaning_of_scripts() {
+    let scripts = [
+        "val xs = [1 -- one\n 2]; List.map (\\x -> Int.add x 1) xs",
+        "do val a = 1; {- note -} val b = 2; {a = a b = '(b 3)} end",
+        "match '(1 2) with | '(a b) -> Int.add a b | _ -> 0 end",
+        "let f = \\x -> if Bool.and x true then [x] else #(x) end in f true end",
+    ];
+    for script in scripts {
+        for options in [FormatOptions::default(), NARROW] {
+            let formatted = fmt_with_options(script, options);
+            assert_eq!(fmt_with_options(&formatted, options), formatted);
+            assert_eq!(
+                run(&formatted, &mut common::<NoHostType>()),
+                run(script, &mut common::<NoHostType>()),
+                "{formatted}"
+            );
+        }
+    }
+}
+
+#[test]
+fn unbalanced_scripts_are_unchanged() {
+    assert_eq!(fmt("let x = 5 in ("), "let x = 5 in (");
+    assert_eq!(fmt("[1 2))"), "[1 2))");
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let scripts = [
+        "val   x =  [ 1 2 3 ] ;val y = {a = 1}",
+        "-- top\nval x = 5;\n\n\nval y = [x 1];  -- trailing\ny",
+        "val y = let a = 1 in Int.add a 2 end; if Bool.and true false then \"yes\" else if false then 2 else 3 end",
+        "match 2 with | 1 -> \"one\" | 2 -> \"two\" | _ -> nothing end",
+        "try Core.error \"no\" handle err -> err end",
+    ];
+    for script in scripts {
+        for options in [FormatOptions::default(), NARROW] {
+            let formatted = fmt_with_options(script, options);
+            assert_eq!(fmt_with_options(&formatted, options), formatted);
+        }
+    }
+}
+
+#[test]
+fn unbalanced_scripts_cant_be_formatted() {
+    assert_eq!(try_fmt("let x = 5 in (", FormatOptions::default()), None);
+    assert_eq!(try_fmt("[1 2))", FormatOptions::default()), None);
+    assert_eq!(
+        try_fmt("[ 1 2 ]", FormatOptions::default()),
+        Some("[1 2]\n".to_owned())
+    );
+}
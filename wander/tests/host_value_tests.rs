@@ -2,11 +2,12 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
 
 use wander::{
-    interpreter::eval, interpreter::Expression, preludes::common, HostFunction,
-    HostFunctionBinding, HostValue, WanderError, WanderValue, Location,
+    call_lambda, interpreter::eval, interpreter::Expression, preludes::common, run, HostFunction,
+    HostFunctionBinding, HostFunctionCall, HostValue, SkippedCall, WanderError, WanderValue,
+    Location,
 };
 
 struct SayHello {}
@@ -14,7 +15,7 @@ impl HostFunction<String> for SayHello {
     fn run(
         &self,
         _arguments: &[WanderValue<String>],
-        _bindings: &wander::environment::Environment<String>,
+        _bindings: &mut wander::environment::Environment<String>,
     ) -> Result<WanderValue<String>, WanderError> {
         Ok(WanderValue::HostValue(HostValue {
             value: "hello!".to_owned(),
@@ -26,7 +27,9 @@ impl HostFunction<String> for SayHello {
             name: "hello".to_owned(),
             parameters: vec![],
             result: None,
+            pure: true,
             doc_string: "Say hello!".to_owned(),
+            ..Default::default()
         }
     }
 }
@@ -42,3 +45,382 @@ fn eval_host_value() {
     }));
     assert_eq!(res, expected);
 }
+
+#[test]
+fn host_value_through_application() {
+    let mut bindings = common::<String>();
+    bindings.bind(
+        "greeting".to_owned(),
+        WanderValue::HostValue(HostValue {
+            value: "hello!".to_owned(),
+        }),
+    );
+    let input = r#"let id = \x -> x in [(id greeting) (Core.eq (id greeting) greeting)] end"#;
+    let res = run(input, &mut bindings);
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::HostValue(HostValue {
+            value: "hello!".to_owned(),
+        }),
+        WanderValue::Bool(true),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn host_value_through_curried_applications() {
+    let scripts = [
+        r#"let k = \a b -> a in k greeting 1 end"#,
+        r#"let k = \a b -> b in (k 1) greeting end"#,
+        r#"val k = \a b -> a; val partial = k greeting; partial 2"#,
+        r#"greeting | (\x -> x)"#,
+        r#"val loop = \n acc -> if Core.eq n 0 then acc else loop (Int.sub n 1) acc end; loop 3 greeting"#,
+        r#"val f = \x -> \y -> y; (f 1) greeting"#,
+    ];
+    for script in scripts {
+        let mut bindings = common::<String>();
+        bindings.bind(
+            "greeting".to_owned(),
+            WanderValue::HostValue(HostValue {
+                value: "hello!".to_owned(),
+            }),
+        );
+        let res = run(script, &mut bindings);
+        let expected = Ok(WanderValue::HostValue(HostValue {
+            value: "hello!".to_owned(),
+        }));
+        assert_eq!(res, expected, "{script}");
+    }
+}
+
+struct Twice {}
+impl HostFunction<String> for Twice {
+    fn run(
+        &self,
+        arguments: &[WanderValue<String>],
+        bindings: &mut wander::environment::Environment<String>,
+    ) -> Result<WanderValue<String>, WanderError> {
+        if let [function, value] = arguments {
+            let once = call_lambda(bindings, function, std::slice::from_ref(value))?;
+            call_lambda(bindings, function, &[once])
+        } else {
+            Err(WanderError::new("`twice` requires a function and a value.".to_owned()))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Test.twice".to_owned(),
+            parameters: vec![("function".to_owned(), None), ("value".to_owned(), None)],
+            result: None,
+            pure: true,
+            doc_string: "Apply a function twice.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn host_function_calls_lambda() {
+    let mut bindings = common::<String>();
+    bindings.bind_host_function(Rc::new(Twice {}));
+    let input = r#"[(Test.twice (\x -> Int.add x 1) 1) (Test.twice Bool.not true) (Test.twice (Int.mul 3) 1)]"#;
+    let res = run(input, &mut bindings);
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(3),
+        WanderValue::Bool(true),
+        WanderValue::Int(9),
+    ]));
+    assert_eq!(res, expected);
+}
+
+struct Total(i64);
+
+struct AddToTotal {}
+impl HostFunction<String> for AddToTotal {
+    fn run(
+        &self,
+        arguments: &[WanderValue<String>],
+        bindings: &mut wander::environment::Environment<String>,
+    ) -> Result<WanderValue<String>, WanderError> {
+        match (arguments, bindings.context_mut::<Total>()) {
+            ([WanderValue::Int(value)], Some(total)) => {
+                total.0 += value;
+                Ok(WanderValue::Int(total.0))
+            }
+            _ => Err(WanderError::new("`addToTotal` requires an Int.".to_owned())),
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Test.addToTotal".to_owned(),
+            parameters: vec![("value".to_owned(), None)],
+            result: None,
+            pure: true,
+            doc_string: "Add to the running total kept by the host.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn host_function_updates_context() {
+    let mut bindings = common::<String>();
+    bindings.bind_host_function(Rc::new(AddToTotal {}));
+    bindings.set_context(Total(10));
+    let res = run("[(Test.addToTotal 1) (Test.addToTotal 2)]", &mut bindings);
+    let expected = Ok(WanderValue::List(vec![WanderValue::Int(11), WanderValue::Int(13)]));
+    assert_eq!(res, expected);
+    assert_eq!(bindings.context::<Total>().map(|total| total.0), Some(13));
+    assert_eq!(bindings.take_context::<Total>().map(|total| total.0), Some(13));
+    assert!(bindings.context::<Total>().is_none());
+}
+
+#[test]
+fn audit_host_function_calls() {
+    let calls = Rc::new(RefCell::new(vec![]));
+    let sink_calls = calls.clone();
+    let mut bindings = common::<String>();
+    bindings.set_audit_sink(Box::new(move |call: HostFunctionCall| {
+        sink_calls.borrow_mut().push(call)
+    }));
+    let res = run("Bool.not (Core.eq 1 2)", &mut bindings);
+    assert_eq!(res, Ok(WanderValue::Bool(true)));
+    assert!(run("List.head []", &mut bindings).is_err());
+    assert!(bindings.take_audit_sink().is_some());
+    run("Bool.not true", &mut bindings).unwrap();
+    let calls: Vec<(String, Vec<String>, Result<String, WanderError>)> = calls
+        .borrow()
+        .iter()
+        .map(|call| (call.name.clone(), call.arguments.clone(), call.result.clone()))
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            (
+                "Core.eq".to_owned(),
+                vec!["1".to_owned(), "2".to_owned()],
+                Ok("false".to_owned())
+            ),
+            (
+                "Bool.not".to_owned(),
+                vec!["false".to_owned()],
+                Ok("true".to_owned())
+            ),
+            (
+                "List.head".to_owned(),
+                vec!["[]".to_owned()],
+                Err(WanderError::new(
+                    "`List.head` function requires a non-empty List.".to_owned()
+                ))
+            ),
+        ]
+    );
+}
+
+struct Fetch {}
+impl HostFunction<String> for Fetch {
+    fn run(
+        &self,
+        arguments: &[WanderValue<String>],
+        _bindings: &mut wander::environment::Environment<String>,
+    ) -> Result<WanderValue<String>, WanderError> {
+        match arguments {
+            [WanderValue::String(url)] => Ok(WanderValue::String(format!("contents of {url}"))),
+            _ => Err(WanderError::new("`fetch` requires a String.".to_owned())),
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Test.fetch".to_owned(),
+            parameters: vec![("url".to_owned(), None)],
+            result: None,
+            capabilities: vec!["net".to_owned()],
+            effectful: true,
+            doc_string: "Pretend to fetch a URL.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn capabilities_must_be_granted() {
+    let mut bindings = common::<String>();
+    bindings.bind_host_function(Rc::new(Fetch {}));
+    let input = r#"Test.fetch "example.com""#;
+    let expected = Ok(WanderValue::String("contents of example.com".to_owned()));
+    assert_eq!(run(input, &mut bindings), expected);
+    bindings.set_granted_capabilities(Some(HashSet::new()));
+    assert_eq!(run("Bool.not true", &mut bindings), Ok(WanderValue::Bool(false)));
+    let denied = Err(WanderError {
+        message: "Permission denied, `Test.fetch` requires the net capability.".to_owned(),
+        span: None,
+        code: Some("PermissionDenied".to_owned()),
+    });
+    assert_eq!(run(input, &mut bindings), denied);
+    bindings.grant_capability("net");
+    assert_eq!(run(input, &mut bindings), expected);
+}
+
+#[test]
+fn functions_without_granted_capabilities_are_not_bound() {
+    let mut bindings = common::<String>();
+    bindings.bind_host_function(Rc::new(Fetch {}));
+    let input = r#"Core.hasBinding "Test.fetch""#;
+    assert_eq!(run(input, &mut bindings), Ok(WanderValue::Bool(true)));
+    bindings.set_granted_capabilities(Some(HashSet::new()));
+    assert_eq!(run(input, &mut bindings), Ok(WanderValue::Bool(false)));
+    bindings.grant_capability("net");
+    assert_eq!(run(input, &mut bindings), Ok(WanderValue::Bool(true)));
+}
+
+#[test]
+fn dry_run_skips_effectful_functions() {
+    let mut bindings = common::<String>();
+    bindings.bind_host_function(Rc::new(Fetch {}));
+    bindings.start_dry_run(Box::new(|name, _| WanderValue::String(format!("skipped {name}"))));
+    let res = run(r#"[(Test.fetch "example.com") (Bool.not true)]"#, &mut bindings);
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::String("skipped Test.fetch".to_owned()),
+        WanderValue::Bool(false),
+    ]));
+    assert_eq!(res, expected);
+    let skipped = vec![SkippedCall {
+        name: "Test.fetch".to_owned(),
+        arguments: vec![WanderValue::String("example.com".to_owned())],
+    }];
+    assert_eq!(bindings.stop_dry_run(), skipped);
+    let res = run(r#"Test.fetch "example.com""#, &mut bindings);
+    assert_eq!(res, Ok(WanderValue::String("contents of example.com".to_owned())));
+}
+
+#[test]
+fn pure_expressions_reject_impure_functions() {
+    let mut bindings = common::<String>();
+    bindings.bind_host_function(Rc::new(Fetch {}));
+    let impure = Err(WanderError {
+        message: "`Test.fetch` is impure and can't be called in a pure expression.".to_owned(),
+        span: None,
+        code: Some("Impure".to_owned()),
+    });
+    let input = r#"pure Test.fetch "example.com" end"#;
+    assert_eq!(run(input, &mut bindings), impure);
+    let input = r#"let cached = \x -> pure Int.add x 1 end in [(cached 1) (Test.fetch "a")] end"#;
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(2),
+        WanderValue::String("contents of a".to_owned()),
+    ]));
+    assert_eq!(run(input, &mut bindings), expected);
+    assert!(!bindings.is_pure());
+    bindings.set_pure(true);
+    assert_eq!(run("Bool.not true", &mut bindings), Ok(WanderValue::Bool(false)));
+    assert_eq!(run(r#"Test.fetch "example.com""#, &mut bindings), impure);
+}
+
+#[test]
+fn lambdas_created_in_pure_expressions_stay_pure() {
+    let mut bindings = common::<String>();
+    bindings.bind_host_function(Rc::new(Fetch {}));
+    let input = r#"val fetch = pure \url -> Test.fetch url end; fetch "a""#;
+    let res = run(input, &mut bindings).map_err(|err| err.code);
+    assert_eq!(res, Err(Some("Impure".to_owned())));
+    let input = r#"val add = pure \x y -> Int.add x y end; val inc = add 1; inc 2"#;
+    assert_eq!(run(input, &mut bindings), Ok(WanderValue::Int(3)));
+    assert!(!bindings.is_pure());
+}
+
+struct FixedValue {
+    name: &'static str,
+    parameters: &'static [&'static str],
+    value: WanderValue<String>,
+}
+impl HostFunction<String> for FixedValue {
+    fn run(
+        &self,
+        _arguments: &[WanderValue<String>],
+        _bindings: &mut wander::environment::Environment<String>,
+    ) -> Result<WanderValue<String>, WanderError> {
+        Ok(self.value.clone())
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: self.name.to_owned(),
+            parameters: self
+                .parameters
+                .iter()
+                .map(|parameter| (parameter.to_string(), None))
+                .collect(),
+            result: None,
+            pure: true,
+            doc_string: "Return a fixed value.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn override_host_functions() {
+    let mut bindings = common::<String>();
+    bindings.bind_host_function(Rc::new(Fetch {}));
+    let input = r#"[(Test.fetch "example.com") Time.now]"#;
+    {
+        let mut mocked = bindings.override_host_function(
+            "Test.fetch",
+            Rc::new(FixedValue {
+                name: "Test.fetch",
+                parameters: &["url"],
+                value: WanderValue::String("mocked".to_owned()),
+            }),
+        );
+        let mut mocked = mocked.override_host_function(
+            "Time.now",
+            Rc::new(FixedValue {
+                name: "Time.now",
+                parameters: &[],
+                value: WanderValue::Int(0),
+            }),
+        );
+        let expected = Ok(WanderValue::List(vec![
+            WanderValue::String("mocked".to_owned()),
+            WanderValue::Int(0),
+        ]));
+        assert_eq!(run(input, &mut mocked), expected);
+    }
+    assert!(run(input, &mut bindings).is_err());
+    let res = run(r#"Test.fetch "example.com""#, &mut bindings);
+    assert_eq!(res, Ok(WanderValue::String("contents of example.com".to_owned())));
+}
+
+#[test]
+fn snapshot_and_restore_bindings() {
+    let mut bindings = common::<String>();
+    bindings.bind("x".to_owned(), WanderValue::Int(1));
+    let snapshot = bindings.snapshot();
+    bindings.bind("x".to_owned(), WanderValue::Int(2));
+    bindings.bind("y".to_owned(), WanderValue::Int(3));
+    bindings.bind_host_function(Rc::new(Fetch {}));
+    assert_eq!(run("[x y]", &mut bindings).unwrap().to_string(), "[2 3]");
+    bindings.restore(snapshot);
+    assert_eq!(run("x", &mut bindings), Ok(WanderValue::Int(1)));
+    assert!(run("y", &mut bindings).is_err());
+    assert!(run(r#"Test.fetch "example.com""#, &mut bindings).is_err());
+    assert_eq!(run("Bool.not true", &mut bindings), Ok(WanderValue::Bool(false)));
+}
+
+#[test]
+fn forked_bindings_are_isolated() {
+    let mut parent = common::<String>();
+    parent.bind("x".to_owned(), WanderValue::Int(1));
+    let mut child = parent.fork();
+    child.bind("x".to_owned(), WanderValue::Int(2));
+    child.bind_host_function(Rc::new(Fetch {}));
+    parent.bind("y".to_owned(), WanderValue::Int(3));
+    assert_eq!(run("x", &mut child), Ok(WanderValue::Int(2)));
+    assert!(run("y", &mut child).is_err());
+    assert_eq!(run(r#"Int.add x 1"#, &mut child), Ok(WanderValue::Int(3)));
+    assert!(run(r#"Test.fetch "example.com""#, &mut child).is_ok());
+    assert_eq!(run("[x y]", &mut parent).unwrap().to_string(), "[1 3]");
+    assert!(run(r#"Test.fetch "example.com""#, &mut parent).is_err());
+}
@@ -0,0 +1,100 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::{collections::HashMap, rc::Rc};
+
+use wander::{
+    environment::Environment, preludes::common, run, FileModuleResolver, NoHostType, Span,
+    WanderError, WanderValue,
+};
+
+fn with_modules(modules: &[(&str, &str)]) -> Environment<NoHostType> {
+    let mut bindings = common::<NoHostType>();
+    let modules: HashMap<String, String> = modules
+        .iter()
+        .map(|(path, source)| (path.to_string(), source.to_string()))
+        .collect();
+    bindings.set_module_resolver(Rc::new(modules));
+    bindings
+}
+
+const UTIL: &str = r#"
+val double = \x -> Int.mul x 2;
+val triple = \x -> Int.add (double x) x;
+val config = {port = 8080}
+"#;
+
+#[test]
+fn import_module_into_namespace() {
+    let mut bindings = with_modules(&[("util.wander", UTIL)]);
+    let input = r#"import Util from "util.wander"; [(Util.triple 3) Util.config.port]"#;
+    let res = run(input, &mut bindings);
+    assert_eq!(
+        res,
+        Ok(WanderValue::List(vec![WanderValue::Int(9), WanderValue::Int(8080)]))
+    );
+    assert!(run("double 1", &mut bindings).is_err());
+}
+
+#[test]
+fn use_imported_module() {
+    let mut bindings = with_modules(&[("util.wander", UTIL)]);
+    let input = r#"import Util from "util.wander"; use Util as U; U.double 4"#;
+    assert_eq!(run(input, &mut bindings), Ok(WanderValue::Int(8)));
+}
+
+#[test]
+fn modules_import_modules() {
+    let mut bindings = with_modules(&[
+        ("util.wander", UTIL),
+        (
+            "math.wander",
+            r#"import U from "util.wander"; val sextuple = \x -> U.double (U.triple x)"#,
+        ),
+    ]);
+    let input = r#"import Math from "math.wander"; Math.sextuple 2"#;
+    assert_eq!(run(input, &mut bindings), Ok(WanderValue::Int(12)));
+}
+
+#[test]
+fn import_errors() {
+    let mut bindings = with_modules(&[
+        ("loop.wander", r#"import Loop from "loop.wander"; val x = 1"#),
+        ("bad.wander", "val x = 1;\nval y = ]"),
+    ]);
+    let res = run(r#"import Nope from "nope.wander"; 1"#, &mut bindings);
+    let expected = Err(WanderError {
+        message: "Could not import nope.wander: No module is registered with that path at line 1, column 1."
+            .to_owned(),
+        span: Some(Span { start: 0, end: 0 }),
+        code: None,
+    });
+    assert_eq!(res, expected);
+    let res = run(r#"import Loop from "loop.wander"; Loop.x"#, &mut bindings);
+    assert!(res
+        .unwrap_err()
+        .message
+        .starts_with("Could not import loop.wander: the module imports itself"));
+    let res = run(r#"import Bad from "bad.wander"; 1"#, &mut bindings);
+    let expected = Err(WanderError::new(
+        "Unexpected token Val at line 2, column 1 in bad.wander.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+    let res = run(r#"import Util from "util.wander"; 1"#, &mut common::<NoHostType>());
+    assert!(res
+        .unwrap_err()
+        .message
+        .starts_with("Could not import util.wander: No ModuleResolver is set"));
+}
+
+#[test]
+fn import_module_files() {
+    let root = std::env::temp_dir().join("wander_import_tests");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("util.wander"), UTIL).unwrap();
+    let mut bindings = common::<NoHostType>();
+    bindings.set_module_resolver(Rc::new(FileModuleResolver { root }));
+    let res = run(r#"import Util from "util.wander"; Util.double 5"#, &mut bindings);
+    assert_eq!(res, Ok(WanderValue::Int(10)));
+}
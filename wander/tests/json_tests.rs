@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use indexmap::IndexMap;
+use serde_json::json;
+use wander::{from_json, preludes::common, run, to_json, NoHostType, WanderError, WanderValue};
+
+#[test]
+fn wander_values_to_json() {
+    let input = r#"[{name = "Wander" tags = ["a" "b"]} 1 2.5 true nothing <id>]"#;
+    let value = run(input, &mut common::<NoHostType>()).unwrap();
+    let expected = json!([{"name": "Wander", "tags": ["a", "b"]}, 1, 2.5, true, null, "id"]);
+    assert_eq!(to_json(&value), Ok(expected));
+}
+
+#[test]
+fn json_to_wander_values() {
+    let input = json!({"count": 3, "ratio": 0.5, "items": [null, "x"]});
+    let expected: WanderValue<NoHostType> = WanderValue::Record(IndexMap::from([
+        ("count".to_owned(), WanderValue::Int(3)),
+        ("ratio".to_owned(), WanderValue::Float(0.5)),
+        (
+            "items".to_owned(),
+            WanderValue::List(vec![WanderValue::Nothing, WanderValue::String("x".to_owned())]),
+        ),
+    ]));
+    assert_eq!(from_json(&input), expected);
+}
+
+#[test]
+fn lambdas_cannot_be_converted_to_json() {
+    let value = run(r#"\x -> x"#, &mut common::<NoHostType>()).unwrap();
+    let res = to_json(&value).map_err(|err: WanderError| err.message.starts_with("Cannot convert"));
+    assert_eq!(res, Err(true));
+}
@@ -2,7 +2,15 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use wander::{parser::Element, preludes::common, run, NoHostType, WanderValue};
+use std::rc::Rc;
+
+use wander::environment::Environment;
+use wander::interpreter::eval;
+use wander::translation::express;
+use wander::{
+    parser::Element, preludes::common, run, HostFunction, HostFunctionBinding, Location,
+    NoHostType, Span, WanderError, WanderValue,
+};
 
 #[test]
 fn basic_currying() {
@@ -134,3 +142,197 @@ fn basic_currying() {
 //     let expected = WanderValue::Bool(false);
 //     assert_eq!(res, expected);
 // }
+
+struct DecFunction {}
+impl HostFunction<NoHostType> for DecFunction {
+    fn run(
+        &self,
+        arguments: &[WanderValue<NoHostType>],
+        _bindings: &mut Environment<NoHostType>,
+    ) -> Result<WanderValue<NoHostType>, WanderError> {
+        if let [WanderValue::Int(value)] = arguments {
+            Ok(WanderValue::Int(value - 1))
+        } else {
+            Err(WanderError::new("`dec` function requires an Int.".to_owned()))
+        }
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Test.dec".to_owned(),
+            parameters: vec![("value".to_owned(), None)],
+            result: None,
+            pure: true,
+            doc_string: "Decrement an Int.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn tail_recursive_lambda_runs_in_constant_stack() {
+    let input = r#"
+    let
+      loop = \n acc -> if Core.eq n 0 then acc else loop (Test.dec n) (Bool.not acc) end
+    in
+      loop 100000 true
+    end
+    "#;
+    let mut environment = common::<NoHostType>();
+    environment.bind_host_function(Rc::new(DecFunction {}));
+    let res = run(input, &mut environment);
+    let expected = Ok(WanderValue::Bool(true));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn tail_recursive_lambda_can_be_partially_applied() {
+    let input = r#"
+    let
+      loop = \n acc -> if Core.eq n 0 then acc else loop (Test.dec n) (Bool.not acc) end
+    in
+      let step = loop 0 in step false end
+    end
+    "#;
+    let mut environment = common::<NoHostType>();
+    environment.bind_host_function(Rc::new(DecFunction {}));
+    let res = run(input, &mut environment);
+    assert_eq!(res, Ok(WanderValue::Bool(false)));
+}
+
+#[test]
+fn tail_calls_with_the_wrong_number_of_arguments_report_arity() {
+    let at = |element| Location(element, 0);
+    let lambda = |name: &str, body| Element::Lambda(name.to_owned(), None, None, Box::new(at(body)));
+    // let loop = \n acc -> loop n in loop 1 true end, with `loop n` already marked as a tail call.
+    let element = at(Element::Let(
+        vec![(
+            "loop".to_owned(),
+            None,
+            at(lambda(
+                "n",
+                lambda(
+                    "acc",
+                    Element::TailCall("loop".to_owned(), vec![at(Element::Name("n".to_owned()))]),
+                ),
+            )),
+        )],
+        Box::new(at(Element::Grouping(vec![
+            at(Element::Name("loop".to_owned())),
+            at(Element::Int(1)),
+            at(Element::Boolean(true)),
+        ]))),
+    ));
+    let res = eval(&express(&element).unwrap(), &mut common::<NoHostType>());
+    assert_eq!(
+        res.map_err(|err| err.message),
+        Err("Function of 2 arguments applied to 1".to_owned())
+    );
+}
+
+#[test]
+fn too_many_arguments_reports_arity() {
+    let input = r#"let id = \x -> x in id 1 2 end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError {
+        message: "Function of 1 argument applied to 2 at line 1, column 21.".to_owned(),
+        span: Some(Span { start: 20, end: 20 }),
+        code: None,
+    });
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn shorthand_lambda() {
+    let input = "let isTrue = Bool.and _ true in [(isTrue true) (isTrue false)] end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn grouped_shorthand_lambda() {
+    let input = "(Core.eq _ 5) 5";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(true));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn partially_applied_host_function_keeps_arguments() {
+    let input = "let isTrue = Bool.and true in [(Bool.and false false) (isTrue true)] end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn partially_applied_host_function_passed_to_lambda() {
+    let input = r#"let apply = \f -> f false in apply (Core.eq false) end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(true));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn partially_applied_host_function_displays_as_application() {
+    let input = "Bool.and true";
+    let res = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(format!("{res}"), "(Bool.and true)");
+}
+
+#[test]
+fn tagged_parameter_without_space() {
+    let input = r#"let id = \x:Int -> x in id 5 end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(5));
+    assert_eq!(res, expected);
+}
+
+// Names in a Lambda's body are looked up when it's called, so Lambdas can refer to
+// themselves and to Lambdas bound after them without any special syntax.
+#[test]
+fn recursive_top_level_vals() {
+    let input = r#"
+    val fact = \n -> if Core.eq n 0 then 1 else Int.mul n (fact (Int.sub n 1)) end;
+    fact 5
+    "#;
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Int(120)));
+}
+
+// Each call of a named Lambda binds its parameters in its own scope,
+// so they can be read after a recursive call returns.
+#[test]
+fn recursive_calls_keep_their_callers_parameters() {
+    let input = r#"
+    val fact = \n -> if Core.eq n 0 then 1 else Int.mul (fact (Int.sub n 1)) n end;
+    val total = \n acc -> if Core.eq n 0 then acc else Int.add (total (Int.sub n 1) acc) n end;
+    [(fact 5) (total 3 10)]
+    "#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![WanderValue::Int(120), WanderValue::Int(16)]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn mutually_recursive_vals() {
+    let input = r#"
+    val isEven = \n -> if Core.eq n 0 then true else isOdd (Int.sub n 1) end;
+    val isOdd = \n -> if Core.eq n 0 then false else isEven (Int.sub n 1) end;
+    [(isEven 10) (isOdd 7) (isEven 3)]
+    "#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+    ]));
+    assert_eq!(res, expected);
+}
@@ -2,7 +2,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use wander::{lexer::{tokenize_and_filter, Token}, Location};
+use wander::{lexer::{tokenize, tokenize_and_filter, Token}, Location};
 
 // #[test]
 // fn tokenize_boolean_true() {
@@ -112,26 +112,58 @@ use wander::{lexer::{tokenize_and_filter, Token}, Location};
 //     assert_eq!(res, expected);
 // }
 
-// #[test]
-// fn tokenize_comment() {
-//     let input = "--hello";
-//     let res = tokenize_and_filter(input);
-//     let expected = Ok(vec![]);
-//     assert_eq!(res, expected);
-// }
+#[test]
+fn tokenize_comment() {
+    let input = "--hello";
+    let res = tokenize_and_filter(input);
+    let expected = Ok(vec![]);
+    assert_eq!(res, expected);
+}
 
-// #[test]
-// fn tokenize_complex_comment() {
-//     let input = "-- <<<>>> () {} }{ )( ><";
-//     let res = tokenize_and_filter(input);
-//     let expected = Ok(vec![]);
-//     assert_eq!(res, expected);
-// }
+#[test]
+fn tokenize_complex_comment() {
+    let input = "-- <<<>>> () {} }{ )( ><";
+    let res = tokenize_and_filter(input);
+    let expected = Ok(vec![]);
+    assert_eq!(res, expected);
+}
 
-// #[test]
-// fn multiline_comment() {
-//     let input = "-- <<<>>> () {} }{ )( ><\n5--five\n--comment";
-//     let res = tokenize_and_filter(input);
-//     let expected = Ok(vec![Location(Token::Int(5), 0)]);
-//     assert_eq!(res, expected);
-// }
+#[test]
+fn multiline_comment() {
+    let input = "-- <<<>>> () {} }{ )( ><\n5--five\n--comment";
+    let res = tokenize_and_filter(input);
+    let expected = Ok(vec![Location(Token::Int(5), 25)]);
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn block_comment() {
+    let input = "{- a {} -- b\n- c -} 5 {--}";
+    let res = tokenize_and_filter(input);
+    let expected = Ok(vec![Location(Token::Int(5), 20)]);
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn tokenize_keeps_comments() {
+    let input = "5 {- five -}";
+    let res = tokenize(input);
+    let expected = Ok(vec![
+        Location(Token::Int(5), 0),
+        Location(Token::WS(" ".to_owned()), 1),
+        Location(Token::Comment("{- five -}".to_owned()), 2),
+    ]);
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn brace_before_negative_number() {
+    let input = "{ -1}";
+    let res = tokenize_and_filter(input);
+    let expected = Ok(vec![
+        Location(Token::OpenBrace, 0),
+        Location(Token::Int(-1), 2),
+        Location(Token::CloseBrace, 4),
+    ]);
+    assert_eq!(res, expected);
+}
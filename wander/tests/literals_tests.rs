@@ -2,7 +2,13 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use wander::{preludes::common, run, WanderValue};
+use std::rc::Rc;
+
+use wander::{
+    environment::Environment, keyword::Keyword, preludes::common, run, LiteralParser, NoHostType,
+    WanderError,
+    WanderValue,
+};
 
 // #[test]
 // fn read_write_test_strings() {
@@ -33,3 +39,138 @@ use wander::{preludes::common, run, WanderValue};
 //     let res: Vec<String> = res.iter().map(|s| format!("{s}")).collect();
 //     assert_eq!(input, res);
 // }
+
+#[test]
+fn run_float_literals() {
+    let input = "[1.5 -0.25 5.0]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Float(1.5),
+        WanderValue::Float(-0.25),
+        WanderValue::Float(5.0),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn write_float_values() {
+    let res = run("[1.5 5.0]", &mut common::<NoHostType>()).unwrap();
+    assert_eq!(format!("{res}"), "[1.5 5.0]");
+}
+
+fn date_literal(text: &str) -> Result<WanderValue<NoHostType>, WanderError> {
+    let parts = text
+        .split('-')
+        .map(|part| part.parse::<i64>().map(WanderValue::Int))
+        .collect::<Result<Vec<_>, _>>();
+    match parts {
+        Ok(parts) if parts.len() == 3 => Ok(WanderValue::Tuple(parts)),
+        _ => Err(WanderError::new(format!("Invalid date {text}."))),
+    }
+}
+
+fn id_literal(text: &str) -> Result<WanderValue<NoHostType>, WanderError> {
+    Ok(WanderValue::String(text.to_owned()))
+}
+
+fn literal_environment() -> Environment<NoHostType> {
+    let mut environment = common::<NoHostType>();
+    environment.bind_literal_parser("@".to_owned(), Rc::new(date_literal as LiteralParser<NoHostType>));
+    environment.bind_literal_parser("#id:".to_owned(), Rc::new(id_literal as LiteralParser<NoHostType>));
+    environment
+}
+
+#[test]
+fn custom_literals() {
+    let input = "[@2024-01-01 #id:foo]";
+    let res = run(input, &mut literal_environment());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Tuple(vec![
+            WanderValue::Int(2024),
+            WanderValue::Int(1),
+            WanderValue::Int(1),
+        ]),
+        WanderValue::String("foo".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn custom_literal_errors() {
+    let input = "@tomorrow";
+    let res = run(input, &mut literal_environment());
+    let res = res.map_err(|err| err.message);
+    let expected = Err("Invalid date tomorrow. at line 1, column 1.".to_owned());
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn custom_literals_are_checked_before_running() {
+    let input = "Assert.assertEq 1 2; @tomorrow";
+    let res = run(input, &mut literal_environment()).map_err(|err| err.message);
+    let expected = Err("Invalid date tomorrow. at line 1, column 22.".to_owned());
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn custom_literals_end_at_separators() {
+    let input = r#"val id = #id:foo; [id #id:bar"baz"]"#;
+    let res = run(input, &mut literal_environment());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::String("foo".to_owned()),
+        WanderValue::String("bar".to_owned()),
+        WanderValue::String("baz".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn unbound_custom_literal() {
+    let input = "#unit:5kg";
+    let res = run(input, &mut literal_environment()).map_err(|err| err.message);
+    let expected = Err("No LiteralParser bound for #unit:5kg at line 1, column 1.".to_owned());
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn keyword_literals() {
+    let input = "[:red :green (Core.eq :red :red) (Core.eq :red :green)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Keyword(Keyword::new("red")),
+        WanderValue::Keyword(Keyword::new("green")),
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn run_char_literals() {
+    let input = r"['a' 'é' '\'' '\\' '\(' '\n']";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(
+        ['a', 'é', '\'', '\\', '(', '\n']
+            .into_iter()
+            .map(WanderValue::Char)
+            .collect(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn char_literals_in_tuples() {
+    let res = run("'('a' 'b')", &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Tuple(vec![
+        WanderValue::Char('a'),
+        WanderValue::Char('b'),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn write_char_values() {
+    let input = r"['a' '\'' '\\' '\(' '\n']";
+    let res = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(format!("{res}"), input);
+}
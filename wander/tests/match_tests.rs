@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{preludes::common, run, NoHostType, WanderError, WanderValue};
+
+#[test]
+fn match_literals() {
+    let input = r#"
+    match 2 with
+      | 1 -> "one"
+      | 2 -> "two"
+      | _ -> "many"
+    end
+    "#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::String("two".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn match_binds_names() {
+    let input = "match '(1 [true 3]) with | '(x [false y]) -> 0 | '(x [true y]) -> y end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(3));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn match_record() {
+    let input = r#"match {name = "Wander" version = 6} with | {version = v} -> v end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(6));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn match_no_arm() {
+    let input = "match nothing with | true -> 1 | [] -> 2 end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new("No pattern matched nothing.".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn match_keywords() {
+    let input = r#"
+    match :green with
+      | :red -> "stop"
+      | :green -> "go"
+      | _ -> "wait"
+    end
+    "#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::String("go".to_owned()));
+    assert_eq!(res, expected);
+}
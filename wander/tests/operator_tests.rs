@@ -0,0 +1,165 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{identifier::Identifier, preludes::common, run, NoHostType, WanderError, WanderValue};
+
+#[test]
+fn and_operator() {
+    let input = "[(true && true) (true && false) (false && true)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+        WanderValue::Bool(false),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn or_operator() {
+    let input = "[(false || true) (false || false) (true || false)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+    let input = "true || false && false";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(true));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn boolean_operators_short_circuit() {
+    let input = "[(false && Assert.assertEq 1 2) (true || Assert.assertEq 1 2)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn boolean_operators_with_applications() {
+    let input = "let x = true in if Bool.not x || x then 1 else 2 end end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(1));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn equality_operators() {
+    let input = "[(1 == 1) (1 == 2) (1 != 2) (\"a\" != \"a\")]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn equality_binds_tighter_than_boolean_operators() {
+    let input = "1 == 2 || 3 != 4 && true == true";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(true));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn not_operator() {
+    let input = "let done = false in if !done then 1 else 2 end end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(1));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn not_operator_on_grouping() {
+    let input = "!(true && false) && !false";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(true));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn unary_minus() {
+    let input = "let x = 5 in -x end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(-5));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn arithmetic_operators() {
+    let input = "[(1 + 2 * 3) ((1 + 2) * 3) (10 - 4 - 3) (7 / 2) (1.5 + 2.0) (5<minutes> + 3<minutes>)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(7),
+        WanderValue::Int(9),
+        WanderValue::Int(3),
+        WanderValue::Int(3),
+        WanderValue::Float(3.5),
+        WanderValue::Quantity(
+            Box::new(WanderValue::Int(8)),
+            Identifier::new("minutes").unwrap(),
+        ),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn subtraction_after_an_application() {
+    let input = "let x = 5 in [(Int.add x 1 - -x) (-x + 1)] end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(11),
+        WanderValue::Int(-4),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn comparison_operators() {
+    let input = "[(1 < 2) (1 > 2) (2 <= 2) (1 >= 2) (1 + 1 == 2 && 3 > 2)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn concat_operator() {
+    let input = r#"[("a" ++ "b" ++ "c") ([1] ++ [2])]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::String("abc".to_owned()),
+        WanderValue::List(vec![WanderValue::Int(1), WanderValue::Int(2)]),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn operators_require_matching_types() {
+    let input = r#"true + 1"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`Core.add` function requires two Ints or two Floats.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
@@ -2,7 +2,10 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use wander::{preludes::common, run, NoHostType, WanderValue};
+use wander::{
+    identifier::Identifier, preludes::common, run, IntOverflow, NoHostType, WanderError,
+    WanderValue,
+};
 
 // #[test]
 // fn calling_not() {
@@ -26,3 +29,546 @@ use wander::{preludes::common, run, NoHostType, WanderValue};
 //     let res = run(input, &mut common::<NoHostType>()).first().unwrap();
 //     assert!(res.is_err());
 // }
+
+#[test]
+fn float_arithmetic() {
+    let input = "[(Float.add 1.5 2.25) (Float.sub 1.5 2.0) (Float.mul 1.5 2.0) (Float.div 1.0 4.0)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Float(3.75),
+        WanderValue::Float(-0.5),
+        WanderValue::Float(3.0),
+        WanderValue::Float(0.25),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn string_functions() {
+    let input = r#"[
+        (String.length "héllo")
+        (String.concat "a" "b")
+        (String.slice 1 3 "hello")
+        (String.split "," "a,b")
+        (String.toUpper "abc")
+        (String.toLower "ABC")
+        (String.contains "ell" "hello")
+        (String.replace "l" "L" "hello")
+        (String.trim "  hi ")
+        (String.toInt "42")
+        (String.fromInt 42)
+    ]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(5),
+        WanderValue::String("ab".to_owned()),
+        WanderValue::String("el".to_owned()),
+        WanderValue::List(vec![
+            WanderValue::String("a".to_owned()),
+            WanderValue::String("b".to_owned()),
+        ]),
+        WanderValue::String("ABC".to_owned()),
+        WanderValue::String("abc".to_owned()),
+        WanderValue::Bool(true),
+        WanderValue::String("heLLo".to_owned()),
+        WanderValue::String("hi".to_owned()),
+        WanderValue::Int(42),
+        WanderValue::String("42".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn string_predicates() {
+    let input = r#"[
+        (String.startsWith "/api" "/api/users")
+        (String.startsWith "/api" "/home")
+        (String.endsWith ".json" "data.json")
+        (String.endsWith ".json" "data.xml")
+        (String.equalsIgnoreCase "GET" "get")
+        (String.equalsIgnoreCase "ÉTÉ" "été")
+        (String.equalsIgnoreCase "get" "post")
+    ]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(
+        [true, false, true, false, true, true, false]
+            .into_iter()
+            .map(WanderValue::Bool)
+            .collect(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn string_and_list_interop() {
+    let input = r#"[
+        (String.toList "hé")
+        (String.fromList ["a" "b"])
+        (List.joinWith ", " ["a" "b" "c"])
+        (String.fromList (List.reverse (String.toList "abc")))
+    ]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::List(vec![
+            WanderValue::String("h".to_owned()),
+            WanderValue::String("é".to_owned()),
+        ]),
+        WanderValue::String("ab".to_owned()),
+        WanderValue::String("a, b, c".to_owned()),
+        WanderValue::String("cba".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+    let res = run(r#"List.joinWith "," ["a" 1]"#, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`List.joinWith` function requires a String and a List of Strings or Chars.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn string_and_char_interop() {
+    let input = r#"[
+        (String.toChars "hé")
+        (Char.toString 'a')
+        (String.fromList ['a' "bc" 'd'])
+        (List.joinWith "-" (String.toChars "abc"))
+    ]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::List(vec![WanderValue::Char('h'), WanderValue::Char('é')]),
+        WanderValue::String("a".to_owned()),
+        WanderValue::String("abcd".to_owned()),
+        WanderValue::String("a-b-c".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+    let res = run(r#"Char.toString "a""#, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        r#"`value` requires a value tagged Char, found "a"."#.to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn string_functions_report_invalid_arguments() {
+    let input = r#"String.toInt "forty-two""#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`String.toInt` function requires a String containing an Int.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn int_arithmetic() {
+    let input = "[(Int.add 1 2) (Int.sub 1 2) (Int.mul 3 4) (Int.div 7 2) (Int.mod 7 2) (Int.abs -3) (Int.min 1 2) (Int.max 1 2) (Int.compare 1 2)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(3),
+        WanderValue::Int(-1),
+        WanderValue::Int(12),
+        WanderValue::Int(3),
+        WanderValue::Int(1),
+        WanderValue::Int(3),
+        WanderValue::Int(1),
+        WanderValue::Int(2),
+        WanderValue::Int(-1),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn int_comparison() {
+    let input = "[(Int.lt 1 2) (Int.gt 1 2) (Int.lte 2 2) (Int.gte 1 2)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn compare_values() {
+    let input = r#"[
+        (String.compare "b" "a") (String.compare "a" "a") (String.compare "Z" "a") (String.compare "é" "z")
+        (Core.compare 2 10) (Core.compare nothing false) (Core.compare 1 1.0) (Core.compare -0.0 0.0)
+        (Core.compare [1 2] [1 2 0]) (Core.compare {b = 1} {a = 2}) (Core.compare #(2 1) #(1 2))
+    ]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(
+        [1, 0, -1, 1, -1, -1, -1, -1, -1, 1, 0]
+            .into_iter()
+            .map(WanderValue::Int)
+            .collect(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn list_sort_agrees_with_compare() {
+    let input = r#"
+        val insert = \value sorted -> if Core.eq sorted [] then [value]
+            else if Core.eq (Core.compare value (List.head sorted)) 1
+                then List.concat [(List.head sorted)] (insert value (List.tail sorted))
+                else List.concat [value] sorted end;
+        val values = ["b" 2 1.5 "a" :c true nothing 10 [1] "B"];
+        Core.eq (List.sort values) (List.foldLeft (\sorted value -> insert value sorted) [] values)
+    "#;
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Bool(true)));
+    let res = run(r#"List.sort ["b" 2 "a" 1 nothing]"#, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Nothing,
+        WanderValue::Int(1),
+        WanderValue::Int(2),
+        WanderValue::String("a".to_owned()),
+        WanderValue::String("b".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn int_division_by_zero() {
+    let input = "Int.div 1 0";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new("Cannot divide 1 by zero.".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn list_higher_order_functions() {
+    let input = r#"[
+        (List.map (\x -> Bool.not x) [true false])
+        (List.filter (\x -> x) [true false true])
+        (List.foldLeft (\acc x -> Int.add acc x) 0 [1 2 3])
+        (List.map (Int.add 1) [1 2])
+    ]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::List(vec![WanderValue::Bool(false), WanderValue::Bool(true)]),
+        WanderValue::List(vec![WanderValue::Bool(true), WanderValue::Bool(true)]),
+        WanderValue::Int(6),
+        WanderValue::List(vec![WanderValue::Int(2), WanderValue::Int(3)]),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn boolean_functions() {
+    let input = "[(Bool.or false true) (Bool.or false false) (Bool.xor true true) (Bool.xor true false)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn list_functions() {
+    let input = "[(List.length [1 2]) (List.head [1 2]) (List.tail [1 2]) (List.concat [1] [2]) (List.reverse [1 2])]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(2),
+        WanderValue::Int(1),
+        WanderValue::List(vec![WanderValue::Int(2)]),
+        WanderValue::List(vec![WanderValue::Int(1), WanderValue::Int(2)]),
+        WanderValue::List(vec![WanderValue::Int(2), WanderValue::Int(1)]),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn list_chunk_window_and_distinct() {
+    let input = "[(List.chunk 2 [1 2 3]) (List.window 2 [1 2 3]) (List.window 4 [1 2 3]) (List.distinct [1 2 1 3 2])]";
+    let res = run(input, &mut common::<NoHostType>());
+    let list = |values: &[i64]| WanderValue::List(values.iter().copied().map(WanderValue::Int).collect());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::List(vec![list(&[1, 2]), list(&[3])]),
+        WanderValue::List(vec![list(&[1, 2]), list(&[2, 3])]),
+        WanderValue::List(vec![]),
+        list(&[1, 2, 3]),
+    ]));
+    assert_eq!(res, expected);
+    let res = run("List.chunk 0 [1 2]", &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`List.chunk` function requires a positive Int and a List.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn list_group_by() {
+    let input = r#"[
+        (List.groupBy (\x -> if Core.eq (Int.mod x 2) 0 then "even" else "odd" end) [1 2 3 4 5])
+        (List.groupBy (\r -> r.level) [{level = :warn} {level = :error} {level = :warn}])
+        (List.groupBy (\x -> x) [])
+    ]"#;
+    let res = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(
+        res.to_string(),
+        "[{odd = [1 3 5] even = [2 4]} {warn = [{level = :warn} {level = :warn}] error = [{level = :error}]} {}]"
+    );
+}
+
+#[test]
+fn list_numeric_folds() {
+    let input = "[(List.sum [1 2 3]) (List.sum [1 2.5]) (List.sum []) (List.product [2 3]) (List.min [3 1.5 2]) (List.max [3 1.5 2])]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(6),
+        WanderValue::Float(3.5),
+        WanderValue::Int(0),
+        WanderValue::Int(6),
+        WanderValue::Float(1.5),
+        WanderValue::Int(3),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn int_overflow_is_configurable() {
+    let input = "[(Int.add 9223372036854775807 1) (Int.sub -9223372036854775807 2) (Int.negate -9223372036854775808) (List.product [4611686018427387904 2])]";
+    let mut bindings = common::<NoHostType>();
+    let res = run(input, &mut bindings);
+    let expected = Err(WanderError::new(
+        "Adding 9223372036854775807 and 1 overflows.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+    bindings.set_int_overflow(IntOverflow::Wrap);
+    let res = run(input, &mut bindings);
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(i64::MIN),
+        WanderValue::Int(i64::MAX),
+        WanderValue::Int(i64::MIN),
+        WanderValue::Int(i64::MIN),
+    ]));
+    assert_eq!(res, expected);
+    bindings.set_int_overflow(IntOverflow::Saturate);
+    let res = run(input, &mut bindings);
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(i64::MAX),
+        WanderValue::Int(i64::MIN),
+        WanderValue::Int(i64::MAX),
+        WanderValue::Int(i64::MAX),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn list_numeric_fold_errors() {
+    let res = run("List.sum [9223372036854775807 1]", &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "Adding 9223372036854775807 and 1 overflows.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+    let res = run(r#"List.max [1 "2"]"#, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        r#"`List.max` function requires a List of Ints or Floats, found "2"."#.to_owned(),
+    ));
+    assert_eq!(res, expected);
+    let res = run("List.min []", &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`List.min` function requires a non-empty List.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn head_of_empty_list() {
+    let input = "List.head []";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`List.head` function requires a non-empty List.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn option_functions() {
+    let input = r#"
+        val name: String? = nothing;
+        val count: Int? = 2;
+        [
+            (Option.isSome name) (Option.isSome count)
+            (Option.default "anonymous" name) (Option.default 0 count)
+            (Option.map (\x -> Int.add x 1) count) (Option.map String.toUpper name)
+        ]
+    "#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(false),
+        WanderValue::Bool(true),
+        WanderValue::String("anonymous".to_owned()),
+        WanderValue::Int(2),
+        WanderValue::Int(3),
+        WanderValue::Nothing,
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn validate_records() {
+    let input = r#"
+        val validateAddress = \address -> Validate.all [
+            (Validate.field address "city" (\city -> Bool.not (Core.eq (String.length city) 0)))
+        ];
+        val validateUser = \user -> Validate.all [
+            (Validate.field user "name" (\name -> Int.gt (String.length name) 2))
+            (Validate.field user "age" (\age -> Int.gte age 0))
+            (Validate.field user "address" validateAddress)
+        ];
+        [
+            (validateUser {name = "Ada" age = 36 address = {city = "London"}})
+            (validateUser {name = "Al" address = {city = ""}})
+        ]
+    "#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::List(vec![]),
+        WanderValue::List(vec![
+            WanderValue::String("name is invalid".to_owned()),
+            WanderValue::String("age is missing".to_owned()),
+            WanderValue::String("address.city is invalid".to_owned()),
+        ]),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn quantity_arithmetic() {
+    let minutes = Identifier::new("minutes").unwrap();
+    let input = "[(Int.add 5<minutes> 3<minutes>) (Int.mul 2 5<minutes>) (Int.lt 1<minutes> 2<minutes>) (Unit.value 5<minutes>) (Unit.unit 5<minutes>)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Quantity(Box::new(WanderValue::Int(8)), minutes.clone()),
+        WanderValue::Quantity(Box::new(WanderValue::Int(10)), minutes.clone()),
+        WanderValue::Bool(true),
+        WanderValue::Int(5),
+        WanderValue::Identifier(minutes),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn dividing_quantities_with_the_same_unit() {
+    let input = "[(Int.div 10<m> 2<m>) (Float.div 5.0<m> 2.0<m>) (Int.div 10<m> 2)]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(5),
+        WanderValue::Float(2.5),
+        WanderValue::Quantity(Box::new(WanderValue::Int(5)), Identifier::new("m").unwrap()),
+    ]));
+    assert_eq!(res, expected);
+    let res = run("Int.mul 2<m> 3<m>", &mut common::<NoHostType>());
+    assert!(res.is_err());
+}
+
+#[test]
+fn units_are_written_next_to_their_number() {
+    let input = "[1 <a>]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(1),
+        WanderValue::Identifier(Identifier::new("a").unwrap()),
+    ]));
+    assert_eq!(res, expected);
+    let res = run("1<a>", &mut common::<NoHostType>()).unwrap();
+    assert_eq!(format!("{res}"), "1<a>");
+}
+
+#[test]
+fn quantity_units_must_match() {
+    let input = "Int.add 5<minutes> 3<seconds>";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`Int.add` cannot combine <minutes> and <seconds>.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn halp_environment() {
+    let input = r#"val double = \x:Int -> Int.mul x 2;
+        List.map (\b -> b.parameters) (List.filter (\b -> Core.eq b.name "double") Halp.environment)"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![WanderValue::List(vec![
+        WanderValue::String("x: Int".to_owned()),
+    ])]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn environment_includes_host_functions_and_values() {
+    let mut environment = common::<NoHostType>();
+    run("val five = 5", &mut environment).unwrap();
+    let names: Vec<String> = environment
+        .environment()
+        .into_iter()
+        .map(|binding| binding.name)
+        .collect();
+    assert!(names.contains(&"Bool.and".to_owned()));
+    assert!(names.contains(&"Halp.environment".to_owned()));
+    assert!(names.contains(&"five".to_owned()));
+}
+
+#[test]
+fn environment_marks_only_pure_lambdas_pure() {
+    let mut environment = common::<NoHostType>();
+    run(
+        r#"val five = 5; val double = \x -> Int.mul x 2; val triple = pure \x -> Int.mul x 3 end"#,
+        &mut environment,
+    )
+    .unwrap();
+    let pure: Vec<(String, bool)> = environment
+        .environment()
+        .into_iter()
+        .filter(|binding| ["five", "double", "triple"].contains(&binding.name.as_str()))
+        .map(|binding| (binding.name, binding.pure))
+        .collect();
+    assert_eq!(
+        pure,
+        vec![
+            ("double".to_owned(), false),
+            ("five".to_owned(), false),
+            ("triple".to_owned(), true),
+        ]
+    );
+}
+
+#[test]
+fn core_version() {
+    let res = run("Core.version", &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::String(env!("CARGO_PKG_VERSION").to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn core_has_binding() {
+    let mut environment = common::<NoHostType>();
+    run("val five = 5", &mut environment).unwrap();
+    let res = run(
+        r#"[(Core.hasBinding "Int.add") (Core.hasBinding "five") (Core.hasBinding "Http.get")]"#,
+        &mut environment,
+    );
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(true),
+        WanderValue::Bool(false),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn missing_bindings_can_fail_early() {
+    let input = r#"if Core.hasBinding "Http.get" then Http.get "example.com" else Core.error "This script needs Http.get." end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(
+        res.map_err(|err| err.message),
+        Err("This script needs Http.get.".to_owned())
+    );
+}
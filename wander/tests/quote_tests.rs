@@ -0,0 +1,195 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::rc::Rc;
+
+use indexmap::IndexMap;
+
+use wander::{lexer::Token, preludes::common, run, Location, NoHostType, WanderError, WanderValue};
+
+#[test]
+fn quotes_are_not_evaluated() {
+    let res = run("`(Int.add 1 missing)", &mut common::<NoHostType>());
+    assert!(matches!(res, Ok(WanderValue::Quote(..))));
+}
+
+#[test]
+fn eval_quotes() {
+    let input = "val q = `(Int.add 1 2); Core.eval q";
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Int(3)));
+}
+
+#[test]
+fn quoted_names_are_read_when_evaluated() {
+    let input = r#"val q = `[x 2];
+        val x = 1;
+        val f = \x -> Core.eval q;
+        '((Core.eval q) (f 5))"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Tuple(vec![
+        WanderValue::List(vec![WanderValue::Int(1), WanderValue::Int(2)]),
+        WanderValue::List(vec![WanderValue::Int(5), WanderValue::Int(2)]),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn parse_strings_into_quotes() {
+    let input = r#"val field = "name";
+        val query = Core.parse ("{" ++ field ++ " = 5}");
+        Core.eval query"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let mut expected = IndexMap::new();
+    expected.insert("name".to_owned(), WanderValue::Int(5));
+    assert_eq!(res, Ok(WanderValue::Record(expected)));
+}
+
+#[test]
+fn values_bound_while_evaluating_dont_leak() {
+    let mut environment = common::<NoHostType>();
+    let res = run(
+        r#"Core.eval (Core.parse "val a = 1; Int.add a 1")"#,
+        &mut environment,
+    );
+    assert_eq!(res, Ok(WanderValue::Int(2)));
+    assert_eq!(environment.read(&"a".to_owned()), None);
+}
+
+#[test]
+fn parse_errors_are_located_in_the_parsed_string() {
+    let res = run(r#"Core.parse "let x = in""#, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "Unexpected token EqualSign at line 1, column 7.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn eval_errors_are_located_in_the_parsed_string() {
+    let input = r#"val q = Core.parse "val f = \\x -> x;\nf 1 2"; Core.eval q"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "Function of 1 argument applied to 2 at line 2, column 1.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn quotes_display_as_source() {
+    let res = run("`(Int.add 1 x)", &mut common::<NoHostType>()).unwrap();
+    assert_eq!(res.to_string(), "`(Int.add 1 x)");
+    let input = r#"`let f = \x: Int -> Int.add x 1 in if true then [(f 1) "a\nb"] else {a = '(1 2)} end end"#;
+    let quote = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(
+        quote.to_string(),
+        r#"`let f = (\x: Int -> (Int.add x 1)) in if true then [(f 1) "a\nb"] else {a = '(1 2)} end end"#
+    );
+    let reparsed = run(&quote.to_string(), &mut common::<NoHostType>()).unwrap();
+    assert_eq!(reparsed.to_string(), quote.to_string());
+    let res = run(&format!("Core.eval {reparsed}"), &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(2),
+        WanderValue::String("a\nb".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn eval_requires_a_quote() {
+    let res = run("Core.eval 5", &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`quote` requires a value tagged Quote, found 5.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn quotes_can_be_tagged() {
+    let res = run(
+        "val q: Quote = `x; Core.eval `1",
+        &mut common::<NoHostType>(),
+    );
+    assert_eq!(res, Ok(WanderValue::Int(1)));
+    let res = run("val q: Quote = 1; q", &mut common::<NoHostType>());
+    assert!(res.is_err());
+}
+
+fn upper_case(input: &[Location<Token>]) -> Result<Vec<Location<Token>>, WanderError> {
+    match input.first() {
+        Some(Location(Token::String(value), position)) => Ok(vec![Location(
+            Token::String(value.to_ascii_uppercase()),
+            *position,
+        )]),
+        _ => Ok(vec![]),
+    }
+}
+
+#[test]
+fn backticks_after_names_are_still_token_transforms() {
+    let mut environment = common::<NoHostType>();
+    environment.bind_token_transformer("Case".to_owned(), "upper".to_owned(), Rc::new(upper_case));
+    let res = run(r#"Core.eval `Case.upper`"test"`"#, &mut environment);
+    assert_eq!(res, Ok(WanderValue::String("TEST".to_owned())));
+}
+
+#[test]
+fn unquoted_values_are_put_into_quotes() {
+    let input = r#"val x = 2;
+        val q = `(Int.add 1 ~x);
+        val x = 10;
+        Core.eval q"#;
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Int(3)));
+}
+
+#[test]
+fn unquoted_strings_keep_their_escapes() {
+    let input = r#"val s = "a\"b\\c"; Core.eval `~s"#;
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::String("a\"b\\c".to_owned())));
+}
+
+#[test]
+fn spliced_lists_are_put_into_quotes() {
+    let input = "val xs = [2 3]; Core.eval `[1 ~@xs 4]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(1),
+        WanderValue::Int(2),
+        WanderValue::Int(3),
+        WanderValue::Int(4),
+    ]));
+    assert_eq!(res, expected);
+    let input = "val args = [1 2]; Core.eval `(Int.add ~@args)";
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Int(3)));
+}
+
+#[test]
+fn nested_quotes_keep_their_unquotes() {
+    let input = "val x = 1; Core.eval `(Core.eval `~x)";
+    let mut environment = common::<NoHostType>();
+    let res = run(input, &mut environment);
+    assert_eq!(res, Ok(WanderValue::Int(1)));
+}
+
+#[test]
+fn splices_require_a_list() {
+    let res = run("val x = 1; `[~@x]", &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`~@` requires a List, Tuple, or Set, found 1.".to_owned(),
+    ));
+    assert_eq!(res.map_err(|error| WanderError::new(error.message)), expected);
+    let res = run("val xs = [1]; `{a = ~@xs}", &mut common::<NoHostType>());
+    assert!(res.is_err());
+}
+
+#[test]
+fn unquotes_outside_of_quotes_are_errors() {
+    let res = run("val x = 1; ~x", &mut common::<NoHostType>());
+    assert!(res.is_err());
+    let res = run("val x = [1]; [~@x]", &mut common::<NoHostType>());
+    assert!(res.is_err());
+}
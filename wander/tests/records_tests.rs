@@ -3,7 +3,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use std::collections::HashMap;
-use wander::{preludes::common, run, NoHostType, WanderValue};
+use wander::{preludes::common, run, NoHostType, WanderError, WanderValue};
 
 // #[test]
 // fn basic_record() {
@@ -85,3 +85,149 @@ use wander::{preludes::common, run, NoHostType, WanderValue};
 //     let res = res.first().unwrap();
 //     assert!(res.is_err());
 // }
+
+#[test]
+fn missing_record_field_lists_available_fields() {
+    let input = "let x = {a = 24 b = true} in x.c end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "Could not read field c in x, available fields are [a, b].".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn field_access_on_non_record() {
+    let input = "let x = {a = 5} in x.a.b end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "Could not access field b in x.a, x.a is 5 not a Record.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn field_access_on_unbound_name() {
+    let input = "y.a";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "Error looking up y, y is not bound.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn field_access_on_record_literal() {
+    let input = "{a = {b = 45}}.a.b";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(45));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn field_access_on_application() {
+    let input = r#"let f = \x -> {a = x} in (f 5).a end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(5));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn field_access_in_application() {
+    let input = "let x = {a = true} in Bool.not {b = x}.b.a end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(false));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn field_access_on_lambda_parameter() {
+    let input = r#"let get = \r -> r.a.b in get {a = {b = 5}} end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(5));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn field_access_prefers_local_bindings() {
+    let input = r#"let Bool = {not = 5} in Bool.not end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(5));
+    assert_eq!(res, expected);
+    let input = r#"let f = \Bool -> Bool.not in f {not = 6} end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(6));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn field_access_on_nested_application() {
+    let input = r#"let makeConfig = \port -> {server = {port = port}} in (makeConfig 8080).server.port end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(8080));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn records_keep_field_order() {
+    let input = "{zebra = 1 apple = 2 mango = 3}";
+    let res = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(format!("{res}"), "{zebra = 1 apple = 2 mango = 3}");
+    let json = wander::to_json(&res).unwrap();
+    assert_eq!(json.to_string(), r#"{"zebra":1,"apple":2,"mango":3}"#);
+}
+
+#[test]
+fn get_path_from_nested_records() {
+    let input = r#"Record.getPath {a = {b = {c = 5}}} ["a" "b" "c"]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Int(5)));
+}
+
+#[test]
+fn get_path_on_wander_value() {
+    let record = run("{a = {b = true}}", &mut common::<NoHostType>()).unwrap();
+    assert_eq!(record.get_path("a.b"), Ok(WanderValue::Bool(true)));
+    assert_eq!(
+        record.get_path("a.c"),
+        Err(WanderError::new(
+            "Could not read field c in {a = {b = true}}.a, available fields are [b].".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn record_update() {
+    let input = "let base = {host = \"localhost\" port = 80} in { base with port = 8080 debug = true } end";
+    let res = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(format!("{res}"), "{host = \"localhost\" port = 8080 debug = true}");
+}
+
+#[test]
+fn record_update_requires_record() {
+    let input = "{ 5 with a = 1 }";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "Could not update 5, only Records can be updated.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn record_functions() {
+    let input = r#"
+    let record = Record.merge {a = 1 b = 2} {b = 3 c = 4} in
+      [record (Record.keys record) (Record.values record) (Record.get record "c")
+       (Record.has record "a") (Record.has record "d")]
+    end"#;
+    let res = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(
+        format!("{res}"),
+        "[{a = 1 b = 3 c = 4} [\"a\" \"b\" \"c\"] [1 3 4] 4 true false]"
+    );
+    let res = run(r#"Record.get {a = 1} "b""#, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "Could not read field b in {a = 1}, available fields are [a].".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
@@ -0,0 +1,107 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::Display;
+
+use serde::Serialize;
+use wander::{
+    environment::Environment,
+    preludes::common,
+    reference::{add_ref_functions, Ref, RefHost},
+    run, WanderError, WanderValue,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+enum TestHost {
+    Ref(Ref),
+}
+
+impl Display for TestHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TestHost::Ref(reference) => Display::fmt(reference, f),
+        }
+    }
+}
+
+impl RefHost for TestHost {
+    fn from_reference(reference: Ref) -> Self {
+        TestHost::Ref(reference)
+    }
+
+    fn as_reference(&self) -> Option<Ref> {
+        match self {
+            TestHost::Ref(reference) => Some(*reference),
+        }
+    }
+}
+
+fn bindings() -> Environment<TestHost> {
+    let mut bindings = common::<TestHost>();
+    add_ref_functions(&mut bindings);
+    bindings
+}
+
+#[test]
+fn refs_hold_values() {
+    let input = r#"
+        val count = Ref.new 0;
+        val seen = Ref.new [];
+        List.foldLeft (\total value -> do
+            Ref.set count (Int.add (Ref.get count) 1);
+            Ref.set seen (List.concat (Ref.get seen) [value]);
+            Int.add total value
+        end) 0 [4 5 6];
+        [(Ref.get count) (Ref.get seen)]
+    "#;
+    let res = run(input, &mut bindings());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(3),
+        WanderValue::List(vec![
+            WanderValue::Int(4),
+            WanderValue::Int(5),
+            WanderValue::Int(6),
+        ]),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn refs_are_distinct() {
+    let input = "val a = Ref.new 1; val b = Ref.new 1; Ref.set a 2; [(Core.eq a b) (Ref.get b) a]";
+    let res = run(input, &mut bindings()).unwrap();
+    assert_eq!(format!("{res}"), "[false 1 <Ref 0>]");
+}
+
+#[test]
+fn refs_belong_to_their_environment() {
+    let mut bindings = bindings();
+    let reference = run("Ref.new 1", &mut bindings).unwrap();
+    let mut other = common::<TestHost>();
+    add_ref_functions(&mut other);
+    other.bind("r".to_owned(), reference);
+    let expected = Err(WanderError::new(
+        "<Ref 0> wasn't created in this Environment.".to_owned(),
+    ));
+    assert_eq!(run("Ref.get r", &mut other), expected);
+    let expected = Err(WanderError::new(
+        "`Ref.set` function requires a Ref and a value.".to_owned(),
+    ));
+    assert_eq!(run("Ref.set 1 2", &mut bindings), expected);
+}
+
+#[test]
+fn refs_dont_read_cells_of_other_environments() {
+    let mut bindings = bindings();
+    let reference = run("Ref.new 1", &mut bindings).unwrap();
+    let mut other = self::bindings();
+    run("Ref.new 2", &mut other).unwrap();
+    other.bind("r".to_owned(), reference);
+    let expected = Err(WanderError::new(
+        "<Ref 0> wasn't created in this Environment.".to_owned(),
+    ));
+    assert_eq!(run("Ref.get r", &mut other), expected);
+    assert_eq!(run("Ref.set r 3", &mut other), expected);
+    assert_eq!(run("Ref.get r", &mut bindings), Ok(WanderValue::Int(1)));
+}
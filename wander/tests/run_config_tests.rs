@@ -0,0 +1,163 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::{rc::Rc, time::Duration};
+
+use wander::{
+    environment::Environment, preludes::common, run_with_config, CancelFlag, HostFunction,
+    ExecutionContext, HostFunctionBinding, NoHostType, RunConfig, WanderError, WanderValue,
+};
+
+const COUNTDOWN: &str = r#"
+let
+  countdown = \n -> if Core.eq n 0 then 0 else countdown (Int.sub n 1) end
+in
+  countdown 1000
+end
+"#;
+
+const NESTED: &str = r#"
+let
+  depth = \n -> if Core.eq n 0 then 0 else Int.add 1 (depth (Int.sub n 1)) end
+in
+  depth 10
+end
+"#;
+
+#[test]
+fn no_limits_by_default() {
+    let res = run_with_config(
+        COUNTDOWN,
+        &mut common::<NoHostType>(),
+        &RunConfig::default(),
+    );
+    assert_eq!(res, Ok(WanderValue::Int(0)));
+}
+
+#[test]
+fn step_limit() {
+    let config = RunConfig {
+        max_steps: Some(100),
+        ..RunConfig::default()
+    };
+    let res = run_with_config(COUNTDOWN, &mut common::<NoHostType>(), &config);
+    assert!(res
+        .unwrap_err()
+        .message
+        .starts_with("Script exceeded the limit of 100 evaluation steps at line"));
+}
+
+#[test]
+fn depth_limit() {
+    let config = RunConfig {
+        max_depth: Some(10),
+        ..RunConfig::default()
+    };
+    let res = run_with_config(NESTED, &mut common::<NoHostType>(), &config);
+    assert!(res
+        .unwrap_err()
+        .message
+        .starts_with("Script exceeded the maximum evaluation depth of 10 at line"));
+    let config = RunConfig {
+        max_depth: Some(500),
+        ..RunConfig::default()
+    };
+    let res = run_with_config(NESTED, &mut common::<NoHostType>(), &config);
+    assert_eq!(res, Ok(WanderValue::Int(10)));
+}
+
+#[test]
+fn time_limit() {
+    let input = r#"let forever = \n -> forever n in forever 1 end"#;
+    let config = RunConfig {
+        max_time: Some(Duration::from_millis(10)),
+        ..RunConfig::default()
+    };
+    let res = run_with_config(input, &mut common::<NoHostType>(), &config);
+    assert!(res
+        .unwrap_err()
+        .message
+        .starts_with("Script exceeded the time limit of 10ms at line"));
+}
+
+#[test]
+fn cancelled_scripts_stop() {
+    let cancel = CancelFlag::new();
+    let config = RunConfig {
+        cancel: Some(cancel.clone()),
+        ..RunConfig::default()
+    };
+    let res = run_with_config("Int.add 1 2", &mut common::<NoHostType>(), &config);
+    assert_eq!(res, Ok(WanderValue::Int(3)));
+    cancel.cancel();
+    let res = run_with_config("Int.add 1 2", &mut common::<NoHostType>(), &config);
+    assert!(res
+        .unwrap_err()
+        .message
+        .starts_with("Script was cancelled at line"));
+}
+
+#[test]
+fn host_functions_see_the_same_cancellation_error() {
+    let context = ExecutionContext {
+        remaining_steps: None,
+        deadline: None,
+        cancelled: true,
+    };
+    let error = context.check().unwrap_err();
+    assert_eq!(error.message, "Script was cancelled");
+}
+
+// Does work in chunks of 10 steps until the script's limits stop it, returning how many chunks it did.
+struct Work {}
+impl HostFunction<NoHostType> for Work {
+    fn run(
+        &self,
+        _arguments: &[WanderValue<NoHostType>],
+        bindings: &mut Environment<NoHostType>,
+    ) -> Result<WanderValue<NoHostType>, WanderError> {
+        let mut chunks = 0;
+        while bindings.execution_context().check().is_ok() && chunks < 1000 {
+            bindings.charge_steps(10)?;
+            chunks += 1;
+        }
+        Ok(WanderValue::Int(chunks))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "work".to_owned(),
+            parameters: vec![],
+            result: None,
+            pure: true,
+            doc_string: String::new(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn host_functions_honor_limits() {
+    let mut bindings = common::<NoHostType>();
+    bindings.bind_host_function(Rc::new(Work {}));
+    assert_eq!(bindings.execution_context().remaining_steps, None);
+    let res = run_with_config("work", &mut bindings, &RunConfig::default());
+    assert_eq!(res, Ok(WanderValue::Int(1000)));
+    let config = RunConfig {
+        max_steps: Some(100),
+        ..RunConfig::default()
+    };
+    let res = run_with_config("work", &mut bindings, &config);
+    assert!(res
+        .unwrap_err()
+        .message
+        .starts_with("Script exceeded the limit of 100 evaluation steps at line 1, column 1."));
+    let config = RunConfig {
+        cancel: Some(CancelFlag::new()),
+        max_time: Some(Duration::from_secs(60)),
+        ..RunConfig::default()
+    };
+    let res = run_with_config("work", &mut bindings, &config);
+    assert_eq!(res, Ok(WanderValue::Int(1000)));
+}
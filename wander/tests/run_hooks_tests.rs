@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wander::{
+    compile, execute, preludes::common, run, NoHostType, RunHooks, RunStats, WanderError,
+    WanderValue,
+};
+
+// Records each hook that was called.
+struct Recorder(Rc<RefCell<Vec<String>>>);
+
+impl RunHooks<NoHostType> for Recorder {
+    fn before_run(&mut self, script: &str) {
+        self.0.borrow_mut().push(format!("before {script}"));
+    }
+
+    fn after_run(
+        &mut self,
+        script: &str,
+        result: &Result<WanderValue<NoHostType>, WanderError>,
+        stats: &RunStats,
+    ) {
+        let outcome = match result {
+            Ok(value) => value.to_string(),
+            Err(_) => "error".to_owned(),
+        };
+        self.0
+            .borrow_mut()
+            .push(format!("after {script} {outcome} {}", stats.steps > 0));
+    }
+
+    fn on_error(&mut self, script: &str, error: &WanderError, _stats: &RunStats) {
+        self.0
+            .borrow_mut()
+            .push(format!("error {script} {}", error.message));
+    }
+}
+
+#[test]
+fn hooks_are_called_around_runs() {
+    let calls = Rc::new(RefCell::new(vec![]));
+    let mut bindings = common::<NoHostType>();
+    bindings.add_run_hooks(Box::new(Recorder(calls.clone())));
+    assert_eq!(run("Int.add 1 2", &mut bindings), Ok(WanderValue::Int(3)));
+    let compiled = compile("true", &bindings).unwrap();
+    assert_eq!(
+        execute(&compiled, &mut bindings),
+        Ok(WanderValue::Bool(true))
+    );
+    assert_eq!(
+        *calls.borrow(),
+        vec![
+            "before Int.add 1 2",
+            "after Int.add 1 2 3 true",
+            "before true",
+            "after true true true",
+        ]
+    );
+}
+
+#[test]
+fn hooks_see_errors() {
+    let calls = Rc::new(RefCell::new(vec![]));
+    let mut bindings = common::<NoHostType>();
+    bindings.add_run_hooks(Box::new(Recorder(calls.clone())));
+    assert!(run("(", &mut bindings).is_err());
+    assert!(run("Core.error \"failed\"", &mut bindings).is_err());
+    let calls = calls.borrow();
+    assert_eq!(calls[0], "before (");
+    assert!(calls[1].starts_with("error ( "));
+    assert_eq!(calls[2], "after ( error false");
+    assert_eq!(calls[3], "before Core.error \"failed\"");
+    assert!(calls[4].starts_with("error Core.error \"failed\" failed"));
+    assert_eq!(calls[5], "after Core.error \"failed\" error true");
+}
+
+#[test]
+fn take_run_hooks_stops_calling_them() {
+    let calls = Rc::new(RefCell::new(vec![]));
+    let mut bindings = common::<NoHostType>();
+    bindings.add_run_hooks(Box::new(Recorder(calls.clone())));
+    assert_eq!(bindings.take_run_hooks().len(), 1);
+    run("5", &mut bindings).unwrap();
+    assert!(calls.borrow().is_empty());
+}
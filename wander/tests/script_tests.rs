@@ -46,7 +46,7 @@ fn run_script_tests() {
                                 if res == expected {
                                     successes.push(name);
                                 } else {
-                                    failures.insert(name, WanderError(format!("Not equal:\n\tExpected: {}\n\tRecieved: {}", expected, res)));
+                                    failures.insert(name, WanderError::new(format!("Not equal:\n\tExpected: {}\n\tRecieved: {}", expected, res)));
                                 }
                             },
                             _ => todo!(),
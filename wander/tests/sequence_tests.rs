@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{preludes::common, run, NoHostType, WanderValue};
+
+#[test]
+fn sequence_returns_last_value() {
+    let input = "true; 5; \"hello\"";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::String("hello".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn sequence_of_applications() {
+    let input = "Bool.not true; Bool.and true true";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(true));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn sequence_allows_trailing_semicolon() {
+    let input = "1; 2;";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(2));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn sequence_stops_at_first_error() {
+    let input = "Bool.not 5; true";
+    let res = run(input, &mut common::<NoHostType>());
+    assert!(res.is_err());
+}
+
+#[test]
+fn empty_sequence_entry_is_an_error() {
+    let input = "1;; 2";
+    let res = run(input, &mut common::<NoHostType>());
+    assert!(res.is_err());
+}
@@ -4,7 +4,8 @@
 
 use std::collections::HashSet;
 
-use wander::{preludes::common, run, NoHostType, WanderValue};
+use serde_json::json;
+use wander::{preludes::common, run, to_json, NoHostType, WanderValue};
 
 // #[test]
 // fn empty_set() {
@@ -13,3 +14,67 @@ use wander::{preludes::common, run, NoHostType, WanderValue};
 //     let expected = WanderValue::Set(HashSet::new());
 //     assert_eq!(res, expected);
 // }
+
+#[test]
+fn sets_print_in_a_stable_order() {
+    let res = run("#(3 1 2 1)", &mut common::<NoHostType>()).unwrap();
+    assert_eq!(format!("{res}"), "#(1 2 3)");
+    let printed = format!("{res}");
+    assert_eq!(run(&printed, &mut common::<NoHostType>()), Ok(res));
+}
+
+#[test]
+fn nested_sets_round_trip() {
+    let res = run(r#"#(#("b" "a") [1 2] {a = :b} #())"#, &mut common::<NoHostType>()).unwrap();
+    let printed = format!("{res}");
+    assert_eq!(printed, r#"#(#("a" "b") #() [1 2] {a = :b})"#);
+    assert_eq!(run(&printed, &mut common::<NoHostType>()), Ok(res));
+}
+
+#[test]
+fn set_functions() {
+    let input = r#"
+    let left = #(1 2 3) in
+      let right = List.toSet [3 4 4] in
+        [(Set.union left right) (Set.intersection left right) (Set.difference left right)
+         (Set.contains 2 left) (Set.contains 2 right) (Set.insert 5 right) (Set.toList left)]
+      end
+    end"#;
+    let res = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(
+        format!("{res}"),
+        "[#(1 2 3 4) #(3) #(1 2) true false #(3 4 5) [1 2 3]]"
+    );
+    let expected = WanderValue::Set(HashSet::from([WanderValue::Int(3), WanderValue::Int(4)]));
+    assert_eq!(run("List.toSet [4 3]", &mut common::<NoHostType>()), Ok(expected));
+}
+
+#[test]
+fn sets_have_a_stable_order() {
+    let input = r#"[#(10 9 8 7 6 5 4 3 2 1) {b = 1 a = 2}]"#;
+    let res = run(input, &mut common::<NoHostType>()).unwrap();
+    assert_eq!(
+        format!("{res}"),
+        "[#(1 10 2 3 4 5 6 7 8 9) {b = 1 a = 2}]"
+    );
+    assert_eq!(
+        to_json(&res),
+        Ok(json!([[1, 10, 2, 3, 4, 5, 6, 7, 8, 9], {"b": 1, "a": 2}]))
+    );
+    assert_eq!(
+        serde_json::to_string(&res).unwrap(),
+        r#"{"List":[{"Set":[{"Int":1},{"Int":10},{"Int":2},{"Int":3},{"Int":4},{"Int":5},{"Int":6},{"Int":7},{"Int":8},{"Int":9}]},{"Record":{"b":{"Int":1},"a":{"Int":2}}}]}"#
+    );
+}
+
+#[test]
+fn nan_is_kept_once_in_a_set() {
+    let input = "val nan = Float.div 0.0 0.0; [(Core.eq nan nan) (Set.contains nan #(nan nan 1.0)) (List.length (Set.toList #(nan nan 1.0)))]";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(true),
+        WanderValue::Int(2),
+    ]));
+    assert_eq!(res, expected);
+}
@@ -0,0 +1,20 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::simple::{bindings, run, run_with, Record, Value};
+
+#[test]
+fn run_without_type_parameters() {
+    assert_eq!(run("Bool.not false"), Ok(Value::Bool(true)));
+    let expected = Value::Record(Record::from([("a".to_owned(), Value::Int(1))]));
+    assert_eq!(run("{a = 1}"), Ok(expected));
+}
+
+#[test]
+fn run_with_bindings() {
+    let mut bindings = bindings();
+    bindings.bind("x".to_owned(), Value::Int(2));
+    assert_eq!(run_with("Int.add x 1", &mut bindings), Ok(Value::Int(3)));
+    assert!(run("x").is_err());
+}
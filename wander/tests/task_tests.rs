@@ -0,0 +1,129 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "parallel")]
+
+use std::{collections::HashSet, rc::Rc, sync::Arc};
+
+use wander::{
+    environment::Environment, preludes::common, run, run_with_config, task::add_task_functions,
+    HostFunction, HostFunctionBinding, NoHostType, RunConfig, SkippedCall, WanderError,
+    WanderValue,
+};
+
+fn bindings() -> Environment<NoHostType> {
+    let mut bindings = common::<NoHostType>();
+    add_task_functions(&mut bindings, Arc::new(common::<NoHostType>));
+    bindings
+}
+
+#[test]
+fn par_returns_results_in_order() {
+    let input = r#"
+        val base = 10;
+        Task.par [(\_ -> Int.add base 1) (\_ -> List.sum [1 2 3]) (\_ -> "three")]
+    "#;
+    let res = run(input, &mut bindings());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Int(11),
+        WanderValue::Int(6),
+        WanderValue::String("three".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn par_combines_errors() {
+    let input = r#"Task.par [(\_ -> Core.error "first") (\_ -> 2) (\_ -> Core.error "second")]"#;
+    let res = run(input, &mut bindings()).unwrap_err();
+    assert_eq!(res.message, "first\nsecond");
+}
+
+#[test]
+fn par_requires_lambdas() {
+    let res = run("Task.par [1 2]", &mut bindings());
+    let expected = Err(WanderError::new(
+        "`Task.par` function requires a List of Lambdas.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+struct Fetch {}
+impl HostFunction<NoHostType> for Fetch {
+    fn run(
+        &self,
+        _arguments: &[WanderValue<NoHostType>],
+        _bindings: &mut Environment<NoHostType>,
+    ) -> Result<WanderValue<NoHostType>, WanderError> {
+        Ok(WanderValue::String("fetched".to_owned()))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Test.fetch".to_owned(),
+            parameters: vec![("url".to_owned(), None)],
+            result: None,
+            capabilities: vec!["net".to_owned()],
+            effectful: true,
+            doc_string: "Pretend to fetch a URL.".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+fn fetch_bindings() -> Environment<NoHostType> {
+    let environment = || {
+        let mut bindings = common::<NoHostType>();
+        bindings.bind_host_function(Rc::new(Fetch {}));
+        bindings
+    };
+    let mut bindings = environment();
+    add_task_functions(&mut bindings, Arc::new(environment));
+    bindings
+}
+
+#[test]
+fn par_keeps_the_granted_capabilities() {
+    let mut bindings = fetch_bindings();
+    bindings.set_granted_capabilities(Some(HashSet::new()));
+    let input = r#"Task.par [(\_ -> Test.fetch "a") (\_ -> 2)]"#;
+    let res = run(input, &mut bindings).map_err(|err| err.code);
+    assert_eq!(res, Err(Some("PermissionDenied".to_owned())));
+    bindings.grant_capability("net");
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::String("fetched".to_owned()),
+        WanderValue::Int(2),
+    ]));
+    assert_eq!(run(input, &mut bindings), expected);
+}
+
+#[test]
+fn par_calls_are_skipped_in_dry_runs() {
+    let mut bindings = fetch_bindings();
+    bindings.start_dry_run(Box::new(|name, _| WanderValue::String(format!("skipped {name}"))));
+    let input = r#"Task.par [(\_ -> Test.fetch "a")]"#;
+    let expected = Ok(WanderValue::List(vec![WanderValue::String(
+        "skipped Test.fetch".to_owned(),
+    )]));
+    assert_eq!(run(input, &mut bindings), expected);
+    let skipped = vec![SkippedCall {
+        name: "Test.fetch".to_owned(),
+        arguments: vec![WanderValue::String("a".to_owned())],
+    }];
+    assert_eq!(bindings.stop_dry_run(), skipped);
+}
+
+#[test]
+fn par_keeps_the_step_limit() {
+    let config = RunConfig {
+        max_steps: Some(100),
+        ..RunConfig::default()
+    };
+    let input = r#"
+        val countdown = \n -> if Core.eq n 0 then 0 else countdown (Int.sub n 1) end;
+        Task.par [(\_ -> countdown 1000) (\_ -> 2)]
+    "#;
+    let res = run_with_config(input, &mut bindings(), &config).unwrap_err();
+    assert!(res.message.starts_with("Script exceeded the limit of"));
+}
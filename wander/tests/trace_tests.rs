@@ -0,0 +1,103 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{
+    preludes::common,
+    trace::{run_traced, TraceNode},
+    NoHostType, WanderValue,
+};
+
+fn flatten(nodes: &[TraceNode]) -> Vec<(String, Option<String>)> {
+    nodes
+        .iter()
+        .flat_map(|node| {
+            let mut flat = vec![(node.expression.clone(), node.result.clone().ok())];
+            flat.extend(flatten(&node.children));
+            flat
+        })
+        .collect()
+}
+
+#[test]
+fn traces_record_the_evaluation_tree() {
+    let mut environment = common::<NoHostType>();
+    let (result, trace) = run_traced("Int.add 1 2", &mut environment);
+    assert_eq!(result, Ok(WanderValue::Int(3)));
+    assert_eq!(trace.len(), 1);
+    assert_eq!(trace[0].expression, "Application");
+    assert_eq!(trace[0].position, 0);
+    assert_eq!(
+        flatten(&trace[0].children)[1..],
+        [
+            ("1".to_owned(), Some("1".to_owned())),
+            ("2".to_owned(), Some("2".to_owned())),
+        ]
+    );
+    assert_eq!(trace[0].children[0].expression, "Int.add");
+}
+
+#[test]
+fn traces_include_names_and_lambda_calls() {
+    let mut environment = common::<NoHostType>();
+    let (result, trace) = run_traced(
+        "val double = \\x -> Int.add x x;\ndouble 4",
+        &mut environment,
+    );
+    assert_eq!(result, Ok(WanderValue::Int(8)));
+    let call = &trace[0].children[1];
+    assert_eq!(call.expression, "Application");
+    assert_eq!(call.result, Ok("8".to_owned()));
+    let names: Vec<&str> = call
+        .children
+        .iter()
+        .map(|node| node.expression.as_str())
+        .collect();
+    assert_eq!(names, vec!["double", "4", "Application"]);
+    assert_eq!(
+        flatten(&call.children[2].children)[1..],
+        [
+            ("x".to_owned(), Some("4".to_owned())),
+            ("x".to_owned(), Some("4".to_owned())),
+        ]
+    );
+}
+
+#[test]
+fn errors_are_recorded_in_the_trace() {
+    let mut environment = common::<NoHostType>();
+    let (result, trace) = run_traced("Int.add 1 missing", &mut environment);
+    assert!(result.is_err());
+    let flat = flatten(&trace);
+    assert_eq!(flat.last(), Some(&("missing".to_owned(), None)));
+    assert!(trace[0].result.is_err());
+}
+
+#[test]
+fn scripts_that_dont_compile_have_empty_traces() {
+    let mut environment = common::<NoHostType>();
+    let (result, trace) = run_traced("let x = in", &mut environment);
+    assert!(result.is_err());
+    assert!(trace.is_empty());
+}
+
+#[test]
+fn traces_can_be_serialized() {
+    let mut environment = common::<NoHostType>();
+    let (_, trace) = run_traced("[1 true]", &mut environment);
+    let json = serde_json::to_value(&trace).unwrap();
+    assert_eq!(json[0]["expression"], "List");
+    assert_eq!(json[0]["children"][1]["expression"], "true");
+    assert_eq!(json[0]["children"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn traces_keep_summaries_of_large_values() {
+    let mut environment = common::<NoHostType>();
+    let script = format!("\"{}\"", "a".repeat(1000));
+    let (result, trace) = run_traced(&script, &mut environment);
+    assert_eq!(result, Ok(WanderValue::String("a".repeat(1000))));
+    let summary = trace[0].result.clone().unwrap();
+    assert_eq!(summary.chars().count(), 80);
+    assert!(summary.ends_with("..."));
+}
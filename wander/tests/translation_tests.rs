@@ -0,0 +1,72 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use indexmap::IndexMap;
+
+use wander::parser::Element;
+use wander::translation::{express, translate};
+use wander::environment::Environment;
+use wander::{Location, NoHostType, WanderError};
+
+#[test]
+fn translate_collects_all_pipe_errors() {
+    let input = Location(
+        Element::Grouping(vec![
+            Location(Element::Boolean(true), 0),
+            Location(Element::Pipe, 5),
+            Location(Element::Int(5), 7),
+            Location(Element::Pipe, 9),
+        ]),
+        0,
+    );
+    let res = translate(input, &Environment::<NoHostType>::new());
+    let expected = Err(vec![
+        WanderError::at("Invalid pipe".to_owned(), 5),
+        WanderError::at("Invalid pipe".to_owned(), 9),
+    ]);
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn express_nested_pipe_in_record_returns_error() {
+    let mut record = IndexMap::new();
+    record.insert(
+        "a".to_owned(),
+        Location(Element::List(vec![Location(Element::Pipe, 6)]), 5),
+    );
+    let input = Location(Element::Record(record), 0);
+    let res = express(&input);
+    assert!(res.is_err());
+}
+
+#[test]
+fn express_nested_pipe_in_let_returns_error() {
+    let input = Location(
+        Element::Let(
+            vec![(
+                "x".to_owned(),
+                None,
+                Location(Element::Tuple(vec![Location(Element::Pipe, 10)]), 9),
+            )],
+            Box::new(Location(Element::Name("x".to_owned()), 15)),
+        ),
+        0,
+    );
+    let res = express(&input);
+    assert!(res.is_err());
+}
+
+#[test]
+fn translate_nested_pipe_in_conditional_returns_error() {
+    let input = Location(
+        Element::Conditional(
+            Box::new(Location(Element::Boolean(true), 3)),
+            Box::new(Location(Element::Pipe, 13)),
+            Box::new(Location(Element::Int(5), 20)),
+        ),
+        0,
+    );
+    let res = translate(input, &Environment::<NoHostType>::new());
+    assert!(res.is_err());
+}
@@ -0,0 +1,177 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::{cell::Cell, rc::Rc};
+
+use wander::{
+    environment::Environment, preludes::common, run, HostFunction, HostFunctionBinding,
+    NoHostType, WanderError, WanderValue,
+};
+
+#[test]
+fn try_without_error_returns_value() {
+    let input = "try Bool.not true handle err -> 5 end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(false));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn try_runs_handler_on_error() {
+    let input = "try Bool.not 5 handle err -> true end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Bool(true));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn try_binds_error_record() {
+    let input = "let x = {a = 1} in try x.b handle err -> err.message end end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::String(
+        "Could not read field b in x, available fields are [a].".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn try_error_record_includes_span() {
+    let input = r#"let id = \x -> x in try id 1 2 handle err -> err.span.start end end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(24));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn errors_in_handler_are_not_caught() {
+    let input = "try Bool.not 5 handle err -> Bool.not 6 end";
+    let res = run(input, &mut common::<NoHostType>());
+    assert!(res.is_err());
+}
+
+#[test]
+fn core_error_fails_script() {
+    let input = r#"Core.error "failed""#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new("failed".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn core_error_with_sets_code() {
+    let input = r#"Core.errorWith {message = "missing" code = "notFound"}"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError {
+        message: "missing".to_owned(),
+        span: None,
+        code: Some("notFound".to_owned()),
+    });
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn try_handles_script_errors() {
+    let input = r#"try Core.errorWith {message = "missing" code = "notFound"} handle err -> [err.message err.code] end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::String("missing".to_owned()),
+        WanderValue::String("notFound".to_owned()),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn ensure_returns_body_value() {
+    let input = "ensure 5 finally true end";
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::Int(5));
+    assert_eq!(res, expected);
+}
+
+struct CountFunction {
+    count: Rc<Cell<i64>>,
+}
+impl HostFunction<NoHostType> for CountFunction {
+    fn run(
+        &self,
+        _arguments: &[WanderValue<NoHostType>],
+        _bindings: &mut Environment<NoHostType>,
+    ) -> Result<WanderValue<NoHostType>, WanderError> {
+        self.count.set(self.count.get() + 1);
+        Ok(WanderValue::Int(self.count.get()))
+    }
+
+    fn binding(&self) -> HostFunctionBinding {
+        HostFunctionBinding {
+            name: "Test.count".to_owned(),
+            parameters: vec![("value".to_owned(), None)],
+            result: None,
+            pure: true,
+            doc_string: "".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn ensure_runs_cleanup_when_body_fails() {
+    let count = Rc::new(Cell::new(0));
+    let mut environment = common::<NoHostType>();
+    environment.bind_host_function(Rc::new(CountFunction { count: count.clone() }));
+    let input = r#"ensure Core.error "body" finally Test.count nothing end"#;
+    let res = run(input, &mut environment);
+    let expected = Err(WanderError::new("body".to_owned()));
+    assert_eq!(res, expected);
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn ensure_cleanup_error_does_not_replace_body_error() {
+    let input = r#"ensure Core.error "body" finally Core.error "cleanup" end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new("body".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn ensure_reports_cleanup_errors() {
+    let input = r#"ensure 5 finally Core.error "cleanup" end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new("cleanup".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn try_without_handler_returns_result() {
+    let input = r#"[
+        (Result.isOk (try 5 end))
+        (Result.isError (try Bool.not 5 end))
+        (Result.unwrapOr 0 (try 5 end))
+        (Result.unwrapOr 0 (try Core.error "failed" end))
+    ]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![
+        WanderValue::Bool(true),
+        WanderValue::Bool(true),
+        WanderValue::Int(5),
+        WanderValue::Int(0),
+    ]));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn inspect_failed_result() {
+    let input = r#"let result = try Core.error "failed" end in result.error.message end"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::String("failed".to_owned()));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn result_constructors() {
+    let input = r#"[(Result.isOk (Result.ok 1)) (Result.isError (Result.error "failed"))]"#;
+    let res = run(input, &mut common::<NoHostType>());
+    let expected = Ok(WanderValue::List(vec![WanderValue::Bool(true), WanderValue::Bool(true)]));
+    assert_eq!(res, expected);
+}
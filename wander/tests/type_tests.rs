@@ -3,8 +3,15 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 //use crate::utilities::introspect_str;
+use std::{fmt::Display, rc::Rc};
+
+use serde::Serialize;
 use wander::interpreter::Expression;
 use wander::parser::Element;
+use wander::{
+    dyn_host_value::DynHostValue, environment::Environment, preludes::common, run, HostValue,
+    NoHostType, TypeChecker, WanderError, WanderValue,
+};
 
 #[path = "utilities.rs"]
 mod utilities;
@@ -64,3 +71,126 @@ mod utilities;
 //     );
 //     assert_eq!(res.expression, expected);
 // }
+
+#[test]
+fn tagged_vals_are_checked() {
+    let res = run("val x: Int = 5; x", &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Int(5)));
+    let res = run(r#"val x: Int = "five"; x"#, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        r#"`x` requires a value tagged Int, found "five"."#.to_owned(),
+    ));
+    assert_eq!(res, expected);
+    let res = run("val x: Any = [1]; x", &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::List(vec![WanderValue::Int(1)])));
+}
+
+#[test]
+fn tagged_lambda_parameters_are_checked() {
+    let res = run(r#"val not = \b: Bool -> Bool.not b; not true"#, &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Bool(false)));
+    let res = run(r#"val not = \b: Bool -> Bool.not b; not 1"#, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`b` requires a value tagged Bool, found 1.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+    let res = run(r#"(\x:String y:Int -> y) "a" "b""#, &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        r#"`y` requires a value tagged Int, found "b"."#.to_owned(),
+    ));
+    assert_eq!(res.map_err(|err| WanderError::new(err.message)), expected);
+}
+
+#[test]
+fn unknown_tags_are_errors() {
+    let res = run("val x: Widget = 5; x", &mut common::<NoHostType>());
+    assert_eq!(res, Err(WanderError::new("Unknown tag Widget.".to_owned())));
+}
+
+struct EvenChecker {}
+
+impl TypeChecker<NoHostType> for EvenChecker {
+    fn check(
+        &self,
+        value: WanderValue<NoHostType>,
+        tag: WanderValue<NoHostType>,
+    ) -> Result<bool, WanderError> {
+        Ok(match (value, tag) {
+            (WanderValue::Int(value), WanderValue::String(tag)) if tag == "Even" => value % 2 == 0,
+            _ => true,
+        })
+    }
+}
+
+#[test]
+fn custom_type_checker() {
+    let mut environment = Environment::<NoHostType>::new();
+    environment.set_type_checker(Rc::new(EvenChecker {}));
+    assert_eq!(run("val x: Even = 2; x", &mut environment), Ok(WanderValue::Int(2)));
+    let res = run("val x: Even = 3; x", &mut environment);
+    let expected = Err(WanderError::new(
+        "`x` requires a value tagged Even, found 3.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn optional_tags() {
+    let res = run("val x: Int? = nothing; x", &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Nothing));
+    let res = run("val x: Int? = 5; x", &mut common::<NoHostType>());
+    assert_eq!(res, Ok(WanderValue::Int(5)));
+    let res = run("val x: Int? = true; x", &mut common::<NoHostType>());
+    let expected = Err(WanderError::new(
+        "`x` requires a value tagged Int?, found true.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Point({}, {})", self.x, self.y)
+    }
+}
+
+// Adds a Point tag to the default checks.
+struct PointChecker {}
+
+impl TypeChecker<DynHostValue> for PointChecker {
+    fn check_host_tag(
+        &self,
+        value: &WanderValue<DynHostValue>,
+        tag: &str,
+    ) -> Result<bool, WanderError> {
+        match (tag, value) {
+            ("Point", WanderValue::HostValue(value)) => {
+                Ok(value.value.downcast_ref::<Point>().is_some())
+            }
+            ("Point", _) => Ok(false),
+            _ => Err(WanderError::new(format!("Unknown tag {tag}."))),
+        }
+    }
+}
+
+#[test]
+fn host_values_in_tag_checks() {
+    let mut environment = common::<DynHostValue>();
+    environment.set_type_checker(Rc::new(PointChecker {}));
+    let origin = WanderValue::HostValue(HostValue {
+        value: DynHostValue::new(Point { x: 0, y: 0 }),
+    });
+    environment.bind("origin".to_owned(), origin.clone());
+    assert_eq!(run("val p: Point = origin; p", &mut environment), Ok(origin));
+    let res = run("val p: Point? = 5; p", &mut environment);
+    let expected = Err(WanderError::new(
+        "`p` requires a value tagged Point?, found 5.".to_owned(),
+    ));
+    assert_eq!(res, expected);
+    assert_eq!(run("val i: Int = 5; i", &mut environment), Ok(WanderValue::Int(5)));
+}
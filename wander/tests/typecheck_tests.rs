@@ -0,0 +1,125 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use wander::{
+    check,
+    preludes::common,
+    typecheck::{TypeDiagnostic, WanderType},
+    NoHostType, Span, WanderError,
+};
+
+fn messages(script: &str) -> Vec<String> {
+    check(script, &common::<NoHostType>())
+        .into_iter()
+        .map(|diagnostic| diagnostic.error.message)
+        .collect()
+}
+
+#[test]
+fn well_typed_scripts_have_no_diagnostics() {
+    let script = r#"
+        val double = \x: Int -> Int.mul x 2;
+        val name: String? = nothing;
+        val point = {x = 1 y = 2};
+        if Core.eq point.x 1 then double point.y else 0 end
+    "#;
+    assert_eq!(messages(script), Vec::<String>::new());
+}
+
+#[test]
+fn tagged_val_mismatch() {
+    let res = check(r#"val x: Int = "five""#, &common::<NoHostType>());
+    let expected = vec![TypeDiagnostic {
+        error: WanderError {
+            message: "`x` is tagged Int but its value is String at line 1, column 14.".to_owned(),
+            span: Some(Span { start: 13, end: 13 }),
+            code: None,
+        },
+        expected: Some(WanderType::Int),
+        found: Some(WanderType::String),
+    }];
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn lambda_argument_mismatch() {
+    let script = r#"val not = \b: Bool -> Bool.not b;
+not 1;
+val f = \x: Int y: String -> y;
+f 1 2"#;
+    assert_eq!(
+        messages(script),
+        vec![
+            "Expected an argument of type Bool, found Int at line 2, column 5.",
+            "Expected an argument of type String, found Int at line 4, column 5.",
+        ]
+    );
+}
+
+#[test]
+fn types_flow_through_bindings() {
+    let script = r#"val n = 5;
+val id = \x -> x;
+val list: List = n;
+val record = {a = [1 2]};
+val a: String = record.a;
+val b = record.b;
+val c: Int? = id nothing"#;
+    assert_eq!(
+        messages(script),
+        vec![
+            "`list` is tagged List but its value is Int at line 3, column 18.",
+            "`a` is tagged String but its value is List[Int] at line 5, column 17.",
+            "Record has no field `b` at line 6, column 9.",
+        ]
+    );
+}
+
+#[test]
+fn prelude_functions_are_tagged() {
+    let script = r#"Int.add "a" 1;
+String.length 5;
+val q = Int.add 5<m> 2<m>;
+val b: Bool = Int.lt 1 2;
+val s: String = Core.version"#;
+    assert_eq!(
+        messages(script),
+        vec![
+            "Expected an argument of type Int, found String at line 1, column 9.",
+            "Expected an argument of type String, found Int at line 2, column 15.",
+        ]
+    );
+}
+
+#[test]
+fn conditions_and_applications() {
+    let script = r#"val x = if 1 then 2 else 3 end; 5 6"#;
+    assert_eq!(
+        messages(script),
+        vec![
+            "Conditions must be Bool, found Int at line 1, column 12.",
+            "Int can't be applied to arguments at line 1, column 35.",
+        ]
+    );
+}
+
+#[test]
+fn scripts_that_dont_compile() {
+    let res = check("[1 2", &common::<NoHostType>());
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].expected, None);
+}
+
+#[test]
+fn types_of_tags() {
+    assert_eq!(
+        WanderType::from_tag("Int?"),
+        Some(WanderType::Optional(Box::new(WanderType::Int)))
+    );
+    assert_eq!(WanderType::from_tag("Widget"), None);
+    assert!(WanderType::from_tag("Int?")
+        .unwrap()
+        .accepts(&WanderType::Nothing));
+    assert!(!WanderType::Int.accepts(&WanderType::Float));
+}